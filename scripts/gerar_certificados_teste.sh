@@ -0,0 +1,25 @@
+#!/bin/sh
+# Certificados para testar o PostgreSQL com TLS localmente (ver docker-compose.tls.yml).
+# Gera uma CA de teste, o certificado do servidor (localhost / 127.0.0.1) e um
+# certificado de cliente para o usuário do banco.
+#
+#   sh scripts/gerar_certificados_teste.sh [pasta]   (padrão: lib/certs)
+set -e
+
+PASTA=${1:-lib/certs}
+USUARIO=${POSTGRES_USER:-usuario}
+mkdir -p "$PASTA"
+cd "$PASTA"
+
+openssl req -x509 -newkey rsa:2048 -nodes -days 365 -subj "/CN=CA de teste do banco" -keyout ca.key -out ca.crt
+
+openssl req -newkey rsa:2048 -nodes -subj "/CN=localhost" -keyout servidor.key -out servidor.csr
+printf "subjectAltName=DNS:localhost,IP:127.0.0.1\n" > servidor.ext
+openssl x509 -req -in servidor.csr -CA ca.crt -CAkey ca.key -CAcreateserial -days 365 -extfile servidor.ext -out servidor.crt
+
+openssl req -newkey rsa:2048 -nodes -subj "/CN=$USUARIO" -keyout cliente.key -out cliente.csr
+openssl x509 -req -in cliente.csr -CA ca.crt -CAkey ca.key -CAcreateserial -days 365 -out cliente.crt
+
+rm -f servidor.csr servidor.ext cliente.csr ca.srl
+chmod 600 ca.key servidor.key cliente.key
+echo "Certificados gerados em $PASTA"
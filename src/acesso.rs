@@ -0,0 +1,223 @@
+// src/acesso.rs
+// Papéis, permissões e visibilidade dos casos. Toda leitura ou alteração de
+// caso, documento ou resposta de IA passa por aqui: as rotas `api/*`, as
+// páginas SSR e os endpoints de IA pedem a permissão da ação com
+// `exigir_permissao` e conferem o caso/documento com `exigir_caso` ou
+// `exigir_documentos`; as listagens filtram por `escopo_casos`.
+//
+// Sócios, administradores e o financeiro veem todos os casos. Advogados veem
+// os casos em que são `Caso.id_advogado` e os compartilhados com eles
+// (`Caso_Acesso`); estagiários, só os compartilhados.
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Client as DbClient;
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::axum::response::Json;
+use tuono_lib::{Request, Response};
+
+use crate::auth::{exigir_sessao, exigir_sessao_pagina, UsuarioSessao};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Papel {
+    Admin,
+    Socio,
+    Advogado,
+    Estagiario,
+    Financeiro,
+}
+
+impl Papel {
+    pub const TODOS: [Papel; 5] = [Papel::Admin, Papel::Socio, Papel::Advogado, Papel::Estagiario, Papel::Financeiro];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Papel::Admin => "admin",
+            Papel::Socio => "socio",
+            Papel::Advogado => "advogado",
+            Papel::Estagiario => "estagiario",
+            Papel::Financeiro => "financeiro",
+        }
+    }
+
+    pub fn pelo_nome(valor: &str) -> Option<Papel> {
+        Papel::TODOS.into_iter().find(|papel| papel.as_str() == valor)
+    }
+
+    /// Papéis que enxergam todos os casos, sem lista de acesso.
+    pub fn ve_todos_os_casos(&self) -> bool {
+        matches!(self, Papel::Admin | Papel::Socio | Papel::Financeiro)
+    }
+
+    pub fn permite(&self, permissao: Permissao) -> bool {
+        use Permissao::*;
+        match self {
+            Papel::Admin => true,
+            Papel::Socio => !matches!(permissao, GerenciarUsuarios | AdministrarBanco),
+            Papel::Advogado => matches!(
+                permissao,
+                VerCasos
+                    | EditarCasos
+                    | CompartilharCasos
+                    | VerDocumentos
+                    | EditarDocumentos
+                    | AprovarMinutas
+                    | UsarIa
+                    | VerClientes
+                    | EditarClientes
+            ),
+            Papel::Estagiario => matches!(permissao, VerCasos | VerDocumentos | EditarDocumentos | UsarIa | VerClientes),
+            Papel::Financeiro => matches!(permissao, VerCasos | VerClientes | VerRelatorios),
+        }
+    }
+}
+
+/// Ações sujeitas a permissão.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permissao {
+    VerCasos,
+    EditarCasos,
+    ExcluirCasos,
+    CompartilharCasos,
+    VerDocumentos,
+    EditarDocumentos,
+    /// Aprovar minutas geradas por IA, tirando o aviso do documento.
+    AprovarMinutas,
+    /// Perguntas, análises, resumos e minutas com IA sobre os casos visíveis.
+    UsarIa,
+    VerClientes,
+    EditarClientes,
+    VerRelatorios,
+    /// Histórico de alterações e verificação da trilha de auditoria. Só para
+    /// papéis que veem todos os casos, já que o histórico inclui casos excluídos.
+    VerAuditoria,
+    /// Exportar e anonimizar os dados de um cliente a pedido do titular (LGPD).
+    AtenderTitular,
+    /// Templates de prompt, modelos instalados, auditoria e processamentos em lote de IA.
+    GerenciarIa,
+    GerenciarUsuarios,
+    /// Limpar, inicializar e popular o banco.
+    AdministrarBanco,
+}
+
+impl UsuarioSessao {
+    pub fn pode(&self, permissao: Permissao) -> bool {
+        self.papel.permite(permissao)
+    }
+
+    /// Advogado vinculado ao usuário, para os papéis que recebem casos por `Caso.id_advogado`.
+    fn advogado_responsavel(&self) -> Option<i32> {
+        if self.papel == Papel::Advogado { self.id_advogado } else { None }
+    }
+}
+
+/// Casos que o usuário pode ver.
+#[derive(Debug, Clone)]
+pub enum EscopoCasos {
+    Todos,
+    Restrito(Vec<i32>),
+}
+
+impl EscopoCasos {
+    pub fn permite(&self, id_caso: i32) -> bool {
+        match self {
+            EscopoCasos::Todos => true,
+            EscopoCasos::Restrito(ids) => ids.contains(&id_caso),
+        }
+    }
+
+    /// Para filtros SQL no formato `($n::INTEGER[] IS NULL OR id_caso = ANY($n))`.
+    pub fn ids(&self) -> Option<Vec<i32>> {
+        match self {
+            EscopoCasos::Todos => None,
+            EscopoCasos::Restrito(ids) => Some(ids.clone()),
+        }
+    }
+}
+
+pub async fn escopo_casos(client_db: &DbClient, usuario: &UsuarioSessao) -> Result<EscopoCasos, tokio_postgres::Error> {
+    if usuario.papel.ve_todos_os_casos() {
+        return Ok(EscopoCasos::Todos);
+    }
+    let rows = client_db
+        .query(
+            "SELECT id_caso FROM Caso WHERE id_advogado = $1
+             UNION
+             SELECT id_caso FROM Caso_Acesso WHERE id_usuario = $2;",
+            &[&usuario.advogado_responsavel(), &usuario.id_usuario],
+        )
+        .await?;
+    Ok(EscopoCasos::Restrito(rows.iter().map(|r| r.get("id_caso")).collect()))
+}
+
+/// Resposta 403 de papel sem a permissão.
+pub fn sem_permissao() -> (StatusCode, Json<Value>) {
+    (StatusCode::FORBIDDEN, Json(json!({"error": "Seu perfil não tem permissão para esta ação."})))
+}
+
+/// Para rotas `api/*`: o usuário da sessão, se o papel dele permitir a ação.
+pub async fn exigir_permissao(req: &Request, permissao: Permissao) -> Result<UsuarioSessao, (StatusCode, Json<Value>)> {
+    let usuario = exigir_sessao(req).await?;
+    if !usuario.pode(permissao) {
+        return Err(sem_permissao());
+    }
+    Ok(usuario)
+}
+
+/// Para páginas SSR: sem permissão, volta para a página inicial.
+pub async fn exigir_permissao_pagina(req: &Request, permissao: Permissao) -> Result<UsuarioSessao, Response> {
+    let usuario = exigir_sessao_pagina(req).await?;
+    if !usuario.pode(permissao) {
+        return Err(Response::Redirect("/".to_string()));
+    }
+    Ok(usuario)
+}
+
+fn erro_banco(e: tokio_postgres::Error) -> (StatusCode, Json<Value>) {
+    eprintln!("Failed to check case access: {}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to check case access: {}", e)})))
+}
+
+// Caso ou documento fora do escopo responde como inexistente, para não revelar o que há no escritório
+
+/// Confere se o caso existe e está no escopo do usuário (404 caso contrário).
+pub async fn exigir_caso(client_db: &DbClient, usuario: &UsuarioSessao, id_caso: i32) -> Result<(), (StatusCode, Json<Value>)> {
+    let visivel = client_db
+        .query_opt(
+            "SELECT 1 FROM Caso c
+             WHERE c.id_caso = $1
+               AND ($2 OR c.id_advogado = $3
+                    OR EXISTS (SELECT 1 FROM Caso_Acesso a WHERE a.id_caso = c.id_caso AND a.id_usuario = $4));",
+            &[&id_caso, &usuario.papel.ve_todos_os_casos(), &usuario.advogado_responsavel(), &usuario.id_usuario],
+        )
+        .await
+        .map_err(erro_banco)?
+        .is_some();
+    if !visivel {
+        return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Caso não encontrado."}))));
+    }
+    Ok(())
+}
+
+/// Confere se todos os documentos existem e pertencem a casos do escopo do usuário.
+pub async fn exigir_documentos(client_db: &DbClient, usuario: &UsuarioSessao, ids_documento: &[i32]) -> Result<(), (StatusCode, Json<Value>)> {
+    let mut ids = ids_documento.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    let visiveis = client_db
+        .query_one(
+            "SELECT COUNT(*) AS total FROM Documento d
+             JOIN Caso c ON c.id_caso = d.id_caso
+             WHERE d.id_documento = ANY($1)
+               AND ($2 OR c.id_advogado = $3
+                    OR EXISTS (SELECT 1 FROM Caso_Acesso a WHERE a.id_caso = c.id_caso AND a.id_usuario = $4));",
+            &[&ids, &usuario.papel.ve_todos_os_casos(), &usuario.advogado_responsavel(), &usuario.id_usuario],
+        )
+        .await
+        .map_err(erro_banco)?
+        .get::<_, i64>("total");
+    if visiveis != ids.len() as i64 {
+        return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."}))));
+    }
+    Ok(())
+}
@@ -1,18 +1,33 @@
 // Import the reqwest library
 use reqwest::Client;
 
-use dotenv::from_filename;
+use std::sync::Arc;
+use tuono_app::config::{self, Config};
+use tuono_app::ia::cliente_http;
+use tuono_app::ia::modelos::validar_modelo_padrao;
 
 #[derive(Clone)]
 // Extend this struct with the feature you will need for your application
 pub struct ApplicationState {
     // This will be available to all your route handlers
     pub fetch: Client,
+    pub config: Arc<Config>,
 }
 
 pub fn main() -> ApplicationState {
-    from_filename("var.env").ok().expect("Error to load .env");
+    // Configuração inválida encerra a inicialização com a lista de problemas
+    let config = match config::iniciar().and_then(|config| config.exigir_servidor().map(|_| config)) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!("Configuration loaded: {:#?}", config);
 
-    let fetch = Client::new();
-    return ApplicationState { fetch };
-}
\ No newline at end of file
+    let fetch = cliente_http();
+
+    // Avisa no log se o modelo padrão não estiver instalado (não impede a inicialização)
+    validar_modelo_padrao(fetch.clone());
+    ApplicationState { fetch, config }
+}
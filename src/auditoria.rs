@@ -0,0 +1,492 @@
+// src/auditoria.rs
+// Trilha de auditoria das alterações de dados (tabela Auditoria). Cada registro
+// guarda quem alterou, quando, a entidade, a ação, o estado antes e depois, a
+// diferença entre os dois e o id da requisição.
+//
+// A tabela só aceita inserções (um gatilho barra UPDATE, DELETE e TRUNCATE) e os
+// registros formam uma cadeia: o hash de cada um cobre o conteúdo e o hash do
+// anterior, então alterar ou apagar um registro quebra a verificação de todos os
+// seguintes. O hash é um HMAC-SHA256 com a chave de `AUDITORIA_CHAVE_HMAC`, que
+// fica fora do banco: quem tem acesso só ao banco não consegue refazer a cadeia.
+// O banco serializa o conteúdo (`auditoria_conteudo`) e as inserções
+// (`preparar_auditoria`), para a cadeia não bifurcar; a aplicação calcula o HMAC.
+//
+// Rotas que alteram dados pegam o estado com `estado` antes da alteração e
+// chamam `registrar_alteracao` na mesma transação, depois dela.
+use std::collections::BTreeSet;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use tokio_postgres::GenericClient;
+use sha2::Sha256;
+use tuono_lib::Request;
+
+use crate::auth::UsuarioSessao;
+use crate::config;
+
+/// Hash anterior ao primeiro registro da cadeia.
+pub const HASH_INICIAL: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entidade {
+    Cliente,
+    Caso,
+    Documento,
+    Usuario,
+    Sessao,
+    Tarefa,
+    /// Template de prompt, com a versão atual.
+    Prompt,
+    /// O banco inteiro, nas operações de `api/init_clean` (id 0, sem estado).
+    Banco,
+}
+
+impl Entidade {
+    pub const TODAS: [Entidade; 8] = [
+        Entidade::Cliente,
+        Entidade::Caso,
+        Entidade::Documento,
+        Entidade::Usuario,
+        Entidade::Sessao,
+        Entidade::Tarefa,
+        Entidade::Prompt,
+        Entidade::Banco,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entidade::Cliente => "cliente",
+            Entidade::Caso => "caso",
+            Entidade::Documento => "documento",
+            Entidade::Usuario => "usuario",
+            Entidade::Sessao => "sessao",
+            Entidade::Tarefa => "tarefa",
+            Entidade::Prompt => "prompt",
+            Entidade::Banco => "banco",
+        }
+    }
+
+    pub fn pelo_nome(valor: &str) -> Option<Entidade> {
+        Entidade::TODAS.into_iter().find(|entidade| entidade.as_str() == valor)
+    }
+
+    // O arquivo do documento entra só como hash e tamanho (do conteúdo cifrado,
+    // ver `crate::cifra`); a chave de dados fica de fora. Do usuário, a senha
+    // entra só como o início do SHA-256 do hash, que muda quando ela é trocada;
+    // da sessão, o hash do token fica de fora
+    fn consulta_estado(&self) -> Option<&'static str> {
+        let consulta = match self {
+            Entidade::Cliente => {
+                "SELECT to_jsonb(c) || jsonb_build_object('cpf', pf.cpf, 'cnpj', pj.cnpj) AS estado
+                 FROM Cliente c
+                 LEFT JOIN Pessoa_Fisica pf ON pf.id_cliente = c.id_cliente
+                 LEFT JOIN Pessoa_Juridica pj ON pj.id_cliente = c.id_cliente
+                 WHERE c.id_cliente = $1;"
+            }
+            Entidade::Caso => "SELECT to_jsonb(c) AS estado FROM Caso c WHERE c.id_caso = $1;",
+            Entidade::Documento => {
+                "SELECT (to_jsonb(d) - 'arquivo' - 'chave_cifrada' - 'id_chave_mestra')
+                        || jsonb_build_object('arquivo_sha256', encode(sha256(d.arquivo), 'hex'), 'arquivo_bytes', length(d.arquivo)) AS estado
+                 FROM Documento d WHERE d.id_documento = $1;"
+            }
+            Entidade::Usuario => {
+                "SELECT (to_jsonb(u) - 'senha_hash')
+                        || jsonb_build_object('senha', left(encode(sha256(convert_to(u.senha_hash, 'UTF8')), 'hex'), 16)) AS estado
+                 FROM Usuario u WHERE u.id_usuario = $1;"
+            }
+            Entidade::Sessao => "SELECT to_jsonb(s) - 'token_hash' AS estado FROM Sessao s WHERE s.id_sessao = $1;",
+            Entidade::Tarefa => "SELECT to_jsonb(t) AS estado FROM Tarefa t WHERE t.id_tarefa = $1;",
+            Entidade::Prompt => {
+                "SELECT to_jsonb(t) || jsonb_build_object('versao_atual', (
+                            SELECT to_jsonb(v) FROM Prompt_Template_Versao v
+                            WHERE v.id_template = t.id_template ORDER BY v.versao DESC LIMIT 1
+                        )) AS estado
+                 FROM Prompt_Template t WHERE t.id_template = $1;"
+            }
+            Entidade::Banco => return None,
+        };
+        Some(consulta)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Acao {
+    Criar,
+    Alterar,
+    Excluir,
+    /// Caso compartilhado com um usuário (`Caso_Acesso`).
+    Compartilhar,
+    RemoverCompartilhamento,
+    /// Dados pessoais do cliente trocados por pseudônimos (ver `crate::lgpd`).
+    Anonimizar,
+    /// Sessão encerrada antes de expirar (logout, troca de senha, desativação).
+    Revogar,
+    /// Operações de `api/init_clean`, registradas também quando recusadas.
+    Inicializar,
+    Limpar,
+    Popular,
+}
+
+impl Acao {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Acao::Criar => "criar",
+            Acao::Alterar => "alterar",
+            Acao::Excluir => "excluir",
+            Acao::Compartilhar => "compartilhar",
+            Acao::RemoverCompartilhamento => "remover_compartilhamento",
+            Acao::Anonimizar => "anonimizar",
+            Acao::Revogar => "revogar",
+            Acao::Inicializar => "inicializar",
+            Acao::Limpar => "limpar",
+            Acao::Popular => "popular",
+        }
+    }
+}
+
+/// Quem fez a alteração e em qual requisição. Sem sessão (instalação do banco
+/// ou tentativa recusada), o registro fica sem usuário.
+#[derive(Debug, Clone)]
+pub struct Autor {
+    pub id_usuario: Option<i32>,
+    pub email: Option<String>,
+    pub id_requisicao: String,
+}
+
+impl Autor {
+    pub fn new(req: &Request, usuario: &UsuarioSessao) -> Self {
+        Self::da_requisicao(req, Some(usuario))
+    }
+
+    /// Usa o cabeçalho `X-Request-Id` quando vier de um proxy; senão gera um id.
+    pub fn da_requisicao(req: &Request, usuario: Option<&UsuarioSessao>) -> Self {
+        let id_requisicao = req
+            .headers
+            .get("x-request-id")
+            .and_then(|valor| valor.to_str().ok())
+            .map(str::trim)
+            .filter(|id| !id.is_empty() && id.len() <= 64)
+            .map(str::to_string)
+            .unwrap_or_else(gerar_id_requisicao);
+        Autor {
+            id_usuario: usuario.map(|u| u.id_usuario),
+            email: usuario.map(|u| u.email.clone()),
+            id_requisicao,
+        }
+    }
+}
+
+fn gerar_id_requisicao() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Estado atual da entidade em JSON, ou `None` se ela não existir.
+pub async fn estado<C: GenericClient>(client: &C, entidade: Entidade, id: i32) -> Result<Option<Value>, tokio_postgres::Error> {
+    let Some(consulta) = entidade.consulta_estado() else {
+        return Ok(None);
+    };
+    Ok(client.query_opt(consulta, &[&id]).await?.map(|row| row.get("estado")))
+}
+
+/// Campos que mudaram, no formato `{"campo": {"antes": ..., "depois": ...}}`.
+pub fn diferenca(antes: Option<&Value>, depois: Option<&Value>) -> Value {
+    let vazio = Map::new();
+    let antes = antes.and_then(Value::as_object).unwrap_or(&vazio);
+    let depois = depois.and_then(Value::as_object).unwrap_or(&vazio);
+    let campos: BTreeSet<&String> = antes.keys().chain(depois.keys()).collect();
+    let alterados: Map<String, Value> = campos
+        .into_iter()
+        .filter(|campo| antes.get(*campo) != depois.get(*campo))
+        .map(|campo| (campo.clone(), json!({"antes": antes.get(campo), "depois": depois.get(campo)})))
+        .collect();
+    Value::Object(alterados)
+}
+
+/// HMAC-SHA256 do conteúdo de um registro, em hexadecimal.
+pub fn calcular_hash(chave: &[u8], conteudo: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(chave).expect("HMAC aceita chaves de qualquer tamanho");
+    mac.update(conteudo.as_bytes());
+    mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Acrescenta um registro à cadeia e devolve o id dele. `client` deve ser a
+/// transação da alteração: o bloqueio de `preparar_auditoria` vale até o fim dela.
+pub async fn registrar<C: GenericClient>(
+    client: &C,
+    autor: &Autor,
+    entidade: Entidade,
+    id_entidade: i32,
+    acao: Acao,
+    antes: Option<&Value>,
+    depois: Option<&Value>,
+) -> Result<i64, String> {
+    let config = config::atual();
+    let chave = config.chave_auditoria()?;
+    let diferenca = diferenca(antes, depois);
+    let row = client
+        .query_one(
+            "SELECT r.id_auditoria, r.hash_anterior, r.data_registro, auditoria_conteudo(r) AS conteudo
+             FROM preparar_auditoria($1, $2, $3, $4, $5, $6, $7, $8, $9) r;",
+            &[
+                &autor.id_usuario,
+                &autor.email,
+                &entidade.as_str(),
+                &id_entidade,
+                &acao.as_str(),
+                &antes,
+                &depois,
+                &diferenca,
+                &autor.id_requisicao,
+            ],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let id_auditoria: i64 = row.get("id_auditoria");
+    let hash_anterior: String = row.get("hash_anterior");
+    let data_registro: DateTime<Utc> = row.get("data_registro");
+    let hash = calcular_hash(chave, row.get("conteudo"));
+    client
+        .execute(
+            "INSERT INTO Auditoria (id_auditoria, hash_anterior, data_registro, id_usuario, usuario, entidade, id_entidade,
+                                    acao, antes, depois, diferenca, id_requisicao, hash)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13);",
+            &[
+                &id_auditoria,
+                &hash_anterior,
+                &data_registro,
+                &autor.id_usuario,
+                &autor.email,
+                &entidade.as_str(),
+                &id_entidade,
+                &acao.as_str(),
+                &antes,
+                &depois,
+                &diferenca,
+                &autor.id_requisicao,
+                &hash,
+            ],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(id_auditoria)
+}
+
+/// Registra a alteração comparando `antes` com o estado atual: sem `antes` é
+/// criação, sem estado atual é exclusão. Alterações que não mudaram nada não
+/// são registradas.
+pub async fn registrar_alteracao<C: GenericClient>(
+    client: &C,
+    autor: &Autor,
+    entidade: Entidade,
+    id: i32,
+    antes: Option<Value>,
+) -> Result<Option<i64>, String> {
+    let depois = estado(client, entidade, id).await.map_err(|e| e.to_string())?;
+    let acao = match (&antes, &depois) {
+        (None, None) => return Ok(None),
+        (None, Some(_)) => Acao::Criar,
+        (Some(_), None) => Acao::Excluir,
+        (Some(a), Some(d)) if a == d => return Ok(None),
+        (Some(_), Some(_)) => Acao::Alterar,
+    };
+    registrar(client, autor, entidade, id, acao, antes.as_ref(), depois.as_ref()).await.map(Some)
+}
+
+/// Registra sessões revogadas (`crate::auth::revogar_sessao` e
+/// `revogar_sessoes_do_usuario`), com o estado de cada uma depois da revogação.
+pub async fn registrar_revogacoes<C: GenericClient>(client: &C, autor: &Autor, ids_sessao: &[i32]) -> Result<(), String> {
+    for id_sessao in ids_sessao {
+        let depois = estado(client, Entidade::Sessao, *id_sessao).await.map_err(|e| e.to_string())?;
+        registrar(client, autor, Entidade::Sessao, *id_sessao, Acao::Revogar, None, depois.as_ref()).await?;
+    }
+    Ok(())
+}
+
+/// Registros de uma entidade, do mais antigo ao mais recente.
+pub async fn historico<C: GenericClient>(client: &C, entidade: Entidade, id: i32) -> Result<Vec<Value>, tokio_postgres::Error> {
+    let rows = client
+        .query(
+            "SELECT id_auditoria, to_char(data_registro, 'YYYY-MM-DD HH24:MI:SS') AS data_registro, id_usuario, usuario,
+                    acao, antes, depois, diferenca, id_requisicao, hash
+             FROM Auditoria WHERE entidade = $1 AND id_entidade = $2
+             ORDER BY id_auditoria ASC;",
+            &[&entidade.as_str(), &id],
+        )
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|row| {
+            json!({
+                "id_auditoria": row.get::<_, i64>("id_auditoria"),
+                "data_registro": row.get::<_, Option<String>>("data_registro"),
+                "id_usuario": row.get::<_, Option<i32>>("id_usuario"),
+                "usuario": row.get::<_, Option<String>>("usuario"),
+                "acao": row.get::<_, String>("acao"),
+                "antes": row.get::<_, Option<Value>>("antes"),
+                "depois": row.get::<_, Option<Value>>("depois"),
+                "diferenca": row.get::<_, Option<Value>>("diferenca"),
+                "id_requisicao": row.get::<_, Option<String>>("id_requisicao"),
+                "hash": row.get::<_, String>("hash"),
+            })
+        })
+        .collect())
+}
+
+/// Resultado da verificação da cadeia.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verificacao {
+    pub integra: bool,
+    pub registros_verificados: i64,
+    /// Hash do último registro: guardado fora do banco, permite detectar que os
+    /// registros finais foram apagados.
+    pub hash_final: String,
+    /// Primeiro registro com problema, e qual.
+    pub id_auditoria_invalido: Option<i64>,
+    pub problema: Option<String>,
+}
+
+/// O que a verificação usa de cada registro.
+#[derive(Debug, Clone)]
+pub struct Elo {
+    pub id_auditoria: i64,
+    pub hash_anterior: String,
+    pub hash: String,
+    /// `auditoria_conteudo` do registro.
+    pub conteudo: String,
+}
+
+/// Recalcula os hashes em ordem e confere o encadeamento.
+pub async fn verificar_cadeia<C: GenericClient>(client: &C) -> Result<Verificacao, String> {
+    let config = config::atual();
+    let chave = config.chave_auditoria()?;
+    let rows = client
+        .query(
+            "SELECT id_auditoria, hash_anterior, hash, auditoria_conteudo(a) AS conteudo
+             FROM Auditoria a ORDER BY id_auditoria ASC;",
+            &[],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let elos = rows.iter().map(|row| Elo {
+        id_auditoria: row.get("id_auditoria"),
+        hash_anterior: row.get("hash_anterior"),
+        hash: row.get("hash"),
+        conteudo: row.get("conteudo"),
+    });
+    Ok(conferir_cadeia(chave, elos))
+}
+
+/// Confere registros já em ordem de id; para no primeiro com problema.
+pub fn conferir_cadeia(chave: &[u8], elos: impl IntoIterator<Item = Elo>) -> Verificacao {
+    let mut anterior = HASH_INICIAL.to_string();
+    let mut verificados = 0;
+    for elo in elos {
+        let problema = if elo.hash_anterior != anterior {
+            Some("O registro não aponta para o anterior: houve remoção ou inserção fora da ordem.")
+        } else if elo.hash != calcular_hash(chave, &elo.conteudo) {
+            Some("O conteúdo do registro não confere com o hash.")
+        } else {
+            None
+        };
+        if let Some(problema) = problema {
+            return Verificacao {
+                integra: false,
+                registros_verificados: verificados,
+                hash_final: anterior,
+                id_auditoria_invalido: Some(elo.id_auditoria),
+                problema: Some(problema.to_string()),
+            };
+        }
+        verificados += 1;
+        anterior = elo.hash;
+    }
+
+    Verificacao { integra: true, registros_verificados: verificados, hash_final: anterior, id_auditoria_invalido: None, problema: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAVE: &[u8] = b"chave de teste da trilha de auditoria";
+
+    fn cadeia(conteudos: &[&str]) -> Vec<Elo> {
+        let mut anterior = HASH_INICIAL.to_string();
+        conteudos
+            .iter()
+            .enumerate()
+            .map(|(i, conteudo)| {
+                let hash = calcular_hash(CHAVE, conteudo);
+                let elo = Elo { id_auditoria: i as i64 + 1, hash_anterior: anterior.clone(), hash: hash.clone(), conteudo: conteudo.to_string() };
+                anterior = hash;
+                elo
+            })
+            .collect()
+    }
+
+    #[test]
+    fn hash_e_hmac_sha256() {
+        // RFC 4231, caso 2
+        assert_eq!(
+            calcular_hash(b"Jefe", "what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn cadeia_integra_devolve_o_ultimo_hash() {
+        let elos = cadeia(&["a", "b", "c"]);
+        let ultimo = elos[2].hash.clone();
+        let verificacao = conferir_cadeia(CHAVE, elos);
+        assert!(verificacao.integra);
+        assert_eq!(verificacao.registros_verificados, 3);
+        assert_eq!(verificacao.hash_final, ultimo);
+    }
+
+    #[test]
+    fn conteudo_alterado_quebra_a_cadeia() {
+        let mut elos = cadeia(&["a", "b", "c"]);
+        elos[1].conteudo = "b alterado".to_string();
+        let verificacao = conferir_cadeia(CHAVE, elos);
+        assert!(!verificacao.integra);
+        assert_eq!(verificacao.id_auditoria_invalido, Some(2));
+        assert_eq!(verificacao.registros_verificados, 1);
+    }
+
+    #[test]
+    fn registro_removido_quebra_a_cadeia() {
+        let mut elos = cadeia(&["a", "b", "c"]);
+        elos.remove(1);
+        let verificacao = conferir_cadeia(CHAVE, elos);
+        assert!(!verificacao.integra);
+        assert_eq!(verificacao.id_auditoria_invalido, Some(3));
+    }
+
+    #[test]
+    fn cadeia_refeita_sem_a_chave_nao_confere() {
+        // Quem só tem acesso ao banco pode recalcular a cadeia, mas não com a chave certa
+        let elos = cadeia(&["a", "b"]);
+        let verificacao = conferir_cadeia(b"outra chave", elos);
+        assert!(!verificacao.integra);
+        assert_eq!(verificacao.id_auditoria_invalido, Some(1));
+    }
+
+    #[test]
+    fn diferenca_lista_so_os_campos_alterados() {
+        let antes = json!({"nome": "Ana", "ativo": true, "papel": "advogado"});
+        let depois = json!({"nome": "Ana", "ativo": false, "email": "ana@exemplo.com"});
+        assert_eq!(
+            diferenca(Some(&antes), Some(&depois)),
+            json!({
+                "ativo": {"antes": true, "depois": false},
+                "email": {"antes": null, "depois": "ana@exemplo.com"},
+                "papel": {"antes": "advogado", "depois": null},
+            })
+        );
+    }
+}
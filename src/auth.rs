@@ -0,0 +1,319 @@
+// src/auth.rs
+// Contas de usuário e sessões. As senhas são guardadas com Argon2 e as sessões
+// ficam na tabela Sessao; o navegador recebe só um token aleatório em cookie
+// HttpOnly, e o banco guarda o hash SHA-256 desse token.
+//
+// Toda rota chama `exigir_sessao` (API) ou `exigir_sessao_pagina` (páginas SSR)
+// antes de qualquer outra coisa, direto ou pelas versões com permissão de `acesso`.
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio_postgres::{Client as DbClient, GenericClient};
+use tuono_lib::axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use tuono_lib::axum::response::Json;
+use tuono_lib::{Request, Response};
+
+use crate::acesso::Papel;
+use crate::config::{self, RedeIp};
+use crate::connect_db;
+use crate::ia::hash_hex;
+
+/// Nome do cookie com o token da sessão.
+pub const COOKIE_SESSAO: &str = "sessao";
+/// Tamanho mínimo das senhas novas.
+pub const MIN_CARACTERES_SENHA: usize = 8;
+
+/// Usuário autenticado na requisição.
+#[derive(Debug, Clone, Serialize)]
+pub struct UsuarioSessao {
+    pub id_usuario: i32,
+    pub id_sessao: i32,
+    pub nome: String,
+    pub email: String,
+    pub papel: Papel,
+    pub id_advogado: Option<i32>,
+}
+
+/// Validade máxima de uma sessão (`SESSAO_DURACAO_HORAS`, padrão 12).
+pub fn duracao_sessao_horas() -> i32 {
+    config::atual().sessao.duracao_horas
+}
+
+/// Sessões sem uso por mais tempo que isto expiram (`SESSAO_INATIVIDADE_MINUTOS`, padrão 120).
+pub fn inatividade_sessao_minutos() -> i32 {
+    config::atual().sessao.inatividade_minutos
+}
+
+pub fn hash_senha(senha: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(senha.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash password: {}", e))
+}
+
+pub fn verificar_senha(senha: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(senha.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+/// Hash de uma senha sorteada, para que um e-mail desconhecido custe o mesmo
+/// Argon2 que uma senha errada.
+fn hash_ficticio() -> &'static str {
+    static HASH: OnceLock<String> = OnceLock::new();
+    HASH.get_or_init(|| hash_senha(&gerar_token()).expect("Failed to hash dummy password"))
+}
+
+/// Confere a senha numa thread de bloqueio (o Argon2 leva dezenas de
+/// milissegundos). Sem `hash` (usuário inexistente), confere contra um hash
+/// fictício e devolve `false`, no mesmo tempo.
+pub async fn conferir_senha(senha: &str, hash: Option<&str>) -> bool {
+    let senha = senha.to_string();
+    let hash = hash.map(str::to_string);
+    tuono_lib::tokio::task::spawn_blocking(move || match hash {
+        Some(hash) => verificar_senha(&senha, &hash),
+        None => {
+            verificar_senha(&senha, hash_ficticio());
+            false
+        }
+    })
+    .await
+    .unwrap_or(false)
+}
+
+pub fn validar_senha_nova(senha: &str) -> Result<(), String> {
+    if senha.chars().count() < MIN_CARACTERES_SENHA {
+        return Err(format!("A senha deve ter pelo menos {} caracteres.", MIN_CARACTERES_SENHA));
+    }
+    Ok(())
+}
+
+/// Cria o primeiro usuário, com papel `admin`, a partir de `USUARIO_ADMIN_EMAIL` e `USUARIO_ADMIN_SENHA`
+/// (`USUARIO_ADMIN_NOME` opcional). Sem as variáveis, não faz nada.
+pub async fn criar_usuario_inicial(client_db: &DbClient) -> Result<(), String> {
+    let config = config::atual();
+    let Some(admin) = &config.admin_inicial else {
+        println!("USUARIO_ADMIN_EMAIL/USUARIO_ADMIN_SENHA not set; no initial user created");
+        return Ok(());
+    };
+    validar_senha_nova(admin.senha.expor())?;
+    client_db
+        .execute(
+            "INSERT INTO Usuario (nome, email, senha_hash, papel) VALUES ($1, $2, $3, 'admin') ON CONFLICT (email) DO NOTHING;",
+            &[&admin.nome, &admin.email.to_lowercase(), &hash_senha(admin.senha.expor())?],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn gerar_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn token_da_requisicao(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|valor| valor.to_str().ok())
+        .flat_map(|valor| valor.split(';'))
+        .filter_map(|par| par.trim().split_once('='))
+        .find(|(nome, _)| *nome == COOKIE_SESSAO)
+        .map(|(_, token)| token.to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// `Set-Cookie` da sessão. `SESSAO_COOKIE_SEGURO=false` tira o atributo
+/// `Secure`, para acesso por HTTP fora de `localhost`.
+pub fn cookie_sessao(token: &str) -> HeaderValue {
+    let seguro = config::atual().sessao.cookie_seguro;
+    let cookie = format!(
+        "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}{}",
+        COOKIE_SESSAO,
+        token,
+        duracao_sessao_horas() as i64 * 3600,
+        if seguro { "; Secure" } else { "" }
+    );
+    HeaderValue::from_str(&cookie).unwrap_or_else(|_| HeaderValue::from_static(""))
+}
+
+/// `Set-Cookie` que apaga o cookie da sessão no navegador.
+pub fn cookie_expirado() -> HeaderValue {
+    HeaderValue::from_static("sessao=; Path=/; HttpOnly; SameSite=Strict; Max-Age=0")
+}
+
+/// Cria a sessão e devolve o token a enviar no cookie.
+pub async fn criar_sessao(client_db: &DbClient, id_usuario: i32, ip: Option<&str>, user_agent: Option<&str>) -> Result<String, tokio_postgres::Error> {
+    let token = gerar_token();
+    client_db
+        .execute(
+            "INSERT INTO Sessao (id_usuario, token_hash, expira_em, ip, user_agent)
+             VALUES ($1, $2, CURRENT_TIMESTAMP + make_interval(hours => $3), $4, $5);",
+            &[&id_usuario, &hash_hex(token.as_bytes()), &duracao_sessao_horas(), &ip, &user_agent],
+        )
+        .await?;
+    // Sessões vencidas há mais de 30 dias não servem nem para consulta
+    client_db
+        .execute("DELETE FROM Sessao WHERE expira_em < CURRENT_TIMESTAMP - INTERVAL '30 days';", &[])
+        .await?;
+    Ok(token)
+}
+
+/// IP do cliente em `X-Forwarded-For`, só com `PROXIES_CONFIAVEIS` configurada.
+///
+/// O tuono não repassa o endereço da conexão às rotas, então o cabeçalho só vale
+/// se a aplicação for acessível apenas pelo proxy. Cada proxy acrescenta à
+/// direita o endereço de quem o chamou: o cliente é o último endereço que não é
+/// de um proxy confiável (os anteriores podem ter sido forjados por ele).
+pub fn ip_da_requisicao(headers: &HeaderMap) -> Option<String> {
+    ip_encaminhado(headers, &config::atual().servidor.proxies_confiaveis)
+}
+
+fn ip_encaminhado(headers: &HeaderMap, proxies: &[RedeIp]) -> Option<String> {
+    if proxies.is_empty() {
+        return None;
+    }
+    let enderecos: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .collect();
+    for endereco in enderecos.into_iter().rev() {
+        let ip: IpAddr = endereco.parse().ok()?;
+        if !proxies.iter().any(|proxy| proxy.contem(&ip)) {
+            return Some(ip.to_string());
+        }
+    }
+    None
+}
+
+/// Usuário da sessão do cookie, se ela for válida: não revogada, dentro da
+/// validade, usada há menos de `SESSAO_INATIVIDADE_MINUTOS` e de usuário ativo.
+/// Cada uso renova o prazo de inatividade.
+pub async fn sessao_da_requisicao(client_db: &DbClient, headers: &HeaderMap) -> Result<Option<UsuarioSessao>, tokio_postgres::Error> {
+    let Some(token) = token_da_requisicao(headers) else {
+        return Ok(None);
+    };
+    let row = client_db
+        .query_opt(
+            "UPDATE Sessao s SET ultimo_acesso = CURRENT_TIMESTAMP
+             FROM Usuario u
+             WHERE s.token_hash = $1 AND u.id_usuario = s.id_usuario AND u.ativo
+               AND s.revogada_em IS NULL AND s.expira_em > CURRENT_TIMESTAMP
+               AND s.ultimo_acesso > CURRENT_TIMESTAMP - make_interval(mins => $2)
+             RETURNING s.id_sessao, u.id_usuario, u.nome, u.email, u.papel, u.id_advogado;",
+            &[&hash_hex(token.as_bytes()), &inatividade_sessao_minutos()],
+        )
+        .await?;
+    Ok(row.map(|r| UsuarioSessao {
+        id_usuario: r.get("id_usuario"),
+        id_sessao: r.get("id_sessao"),
+        nome: r.get("nome"),
+        email: r.get("email"),
+        // Papel desconhecido no banco cai no mais restrito
+        papel: Papel::pelo_nome(r.get("papel")).unwrap_or(Papel::Estagiario),
+        id_advogado: r.get("id_advogado"),
+    }))
+}
+
+/// Revoga uma sessão do usuário. Retorna `false` se ela não existir ou já estiver revogada.
+/// Quem chama registra a revogação (`crate::auditoria::registrar_revogacoes`).
+pub async fn revogar_sessao<C: GenericClient>(client_db: &C, id_usuario: i32, id_sessao: i32) -> Result<bool, tokio_postgres::Error> {
+    let alteradas = client_db
+        .execute(
+            "UPDATE Sessao SET revogada_em = CURRENT_TIMESTAMP WHERE id_sessao = $1 AND id_usuario = $2 AND revogada_em IS NULL;",
+            &[&id_sessao, &id_usuario],
+        )
+        .await?;
+    Ok(alteradas > 0)
+}
+
+/// Revoga todas as sessões do usuário, menos `exceto`, e devolve as revogadas.
+pub async fn revogar_sessoes_do_usuario<C: GenericClient>(client_db: &C, id_usuario: i32, exceto: Option<i32>) -> Result<Vec<i32>, tokio_postgres::Error> {
+    let rows = client_db
+        .query(
+            "UPDATE Sessao SET revogada_em = CURRENT_TIMESTAMP
+             WHERE id_usuario = $1 AND revogada_em IS NULL AND ($2::INTEGER IS NULL OR id_sessao <> $2)
+             RETURNING id_sessao;",
+            &[&id_usuario, &exceto],
+        )
+        .await?;
+    Ok(rows.iter().map(|row| row.get("id_sessao")).collect())
+}
+
+async fn usuario_autenticado(req: &Request) -> Result<Option<UsuarioSessao>, String> {
+    let client_db = connect_db().await.map_err(|e| format!("Database connection error: {}", e))?;
+    sessao_da_requisicao(&client_db, &req.headers)
+        .await
+        .map_err(|e| format!("Failed to validate session: {}", e))
+}
+
+/// Para rotas `api/*`: o usuário da sessão, ou a resposta 401 a devolver.
+pub async fn exigir_sessao(req: &Request) -> Result<UsuarioSessao, (StatusCode, Json<Value>)> {
+    match usuario_autenticado(req).await {
+        Ok(Some(usuario)) => Ok(usuario),
+        Ok(None) => Err((StatusCode::UNAUTHORIZED, Json(json!({"error": "Sessão inválida ou expirada. Faça login novamente."})))),
+        Err(e) => {
+            eprintln!("{}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e}))))
+        }
+    }
+}
+
+/// Para páginas SSR: o usuário da sessão, ou o redirecionamento para o login.
+pub async fn exigir_sessao_pagina(req: &Request) -> Result<UsuarioSessao, Response> {
+    match usuario_autenticado(req).await {
+        Ok(Some(usuario)) => Ok(usuario),
+        Ok(None) => {
+            let destino = urlencoding::encode(req.uri.path()).into_owned();
+            Err(Response::Redirect(format!("/login?destino={}", destino)))
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            Err(Response::Redirect("/login".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn com_encaminhamento(valores: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for valor in valores {
+            headers.append("x-forwarded-for", HeaderValue::from_str(valor).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn sem_proxies_confiaveis_o_cabecalho_e_ignorado() {
+        assert_eq!(ip_encaminhado(&com_encaminhamento(&["203.0.113.7"]), &[]), None);
+    }
+
+    #[test]
+    fn cliente_e_o_ultimo_endereco_fora_dos_proxies() {
+        let proxies = ["10.0.0.0/8".parse().unwrap()];
+        // O primeiro endereço veio do próprio cliente e pode ser forjado
+        let headers = com_encaminhamento(&["1.2.3.4, 203.0.113.7", "10.0.0.5"]);
+        assert_eq!(ip_encaminhado(&headers, &proxies), Some("203.0.113.7".to_string()));
+    }
+
+    #[test]
+    fn endereco_invalido_deixa_o_ip_desconhecido() {
+        let proxies = ["10.0.0.0/8".parse().unwrap()];
+        assert_eq!(ip_encaminhado(&com_encaminhamento(&["lixo, 10.0.0.5"]), &proxies), None);
+        assert_eq!(ip_encaminhado(&com_encaminhamento(&["10.0.0.5"]), &proxies), None);
+    }
+}
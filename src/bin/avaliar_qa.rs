@@ -0,0 +1,133 @@
+// Avaliação offline das perguntas e respostas sobre documentos.
+//
+//   cargo run --bin avaliar_qa -- avaliacao/qa_documento.json [opções]
+//   cargo run --bin avaliar_qa -- comparar anterior.json atual.json
+//
+// Opções:
+//   --modelo NOME            modelo a avaliar (padrão: o do template / LLM_MODELO_PADRAO)
+//   --template-versao N      versão do template qa_documento no banco (exige DATABASE_URL)
+//   --gravar ARQUIVO         grava as respostas do modelo para repetir a avaliação depois
+//   --reproduzir ARQUIVO     usa respostas gravadas, sem servidor de modelo
+//   --saida ARQUIVO          salva o relatório em JSON
+//   --comparar ARQUIVO       compara com o relatório de uma execução anterior
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use reqwest::Client;
+use tuono_app::{config, connect_db};
+use tuono_app::ia::avaliacao::{carregar_conjunto, carregar_relatorio, comparar_relatorios, executar_avaliacao, resumo_relatorio};
+use tuono_app::ia::fila::tempo_limite;
+use tuono_app::ia::gravacao::ProviderGravado;
+use tuono_app::ia::prompts::{carregar_template, PromptTemplate, TEMPLATE_QA_DOCUMENTO};
+use tuono_app::ia::{provider_from_env, LlmProvider};
+
+#[derive(Default)]
+struct Opcoes {
+    conjunto: Option<PathBuf>,
+    modelo: Option<String>,
+    template_versao: Option<i32>,
+    gravar: Option<PathBuf>,
+    reproduzir: Option<PathBuf>,
+    saida: Option<PathBuf>,
+    comparar: Option<PathBuf>,
+}
+
+fn ler_opcoes(args: &[String]) -> Result<Opcoes, String> {
+    let mut opcoes = Opcoes::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut valor = || args.next().cloned().ok_or_else(|| format!("Falta o valor de {}", arg));
+        match arg.as_str() {
+            "--modelo" => opcoes.modelo = Some(valor()?),
+            "--template-versao" => opcoes.template_versao = Some(valor()?.parse().map_err(|_| "--template-versao deve ser um número.".to_string())?),
+            "--gravar" => opcoes.gravar = Some(PathBuf::from(valor()?)),
+            "--reproduzir" => opcoes.reproduzir = Some(PathBuf::from(valor()?)),
+            "--saida" => opcoes.saida = Some(PathBuf::from(valor()?)),
+            "--comparar" => opcoes.comparar = Some(PathBuf::from(valor()?)),
+            outro if outro.starts_with("--") => return Err(format!("Opção desconhecida: {}", outro)),
+            outro => opcoes.conjunto = Some(PathBuf::from(outro)),
+        }
+    }
+    if opcoes.gravar.is_some() && opcoes.reproduzir.is_some() {
+        return Err("Use --gravar ou --reproduzir, não os dois.".to_string());
+    }
+    Ok(opcoes)
+}
+
+/// O template do banco quando `DATABASE_URL` está configurada; senão, o embutido.
+async fn template_qa(versao: Option<i32>) -> Result<PromptTemplate, String> {
+    if config::atual().banco.url.is_some() {
+        let client_db = connect_db().await.map_err(|e| format!("Database connection error: {}", e))?;
+        return carregar_template(&client_db, TEMPLATE_QA_DOCUMENTO, versao).await;
+    }
+    if versao.is_some() {
+        return Err("--template-versao exige DATABASE_URL.".to_string());
+    }
+    PromptTemplate::embutido(TEMPLATE_QA_DOCUMENTO).ok_or_else(|| format!("Template '{}' não encontrado.", TEMPLATE_QA_DOCUMENTO))
+}
+
+async fn avaliar(opcoes: Opcoes) -> Result<(), String> {
+    let arquivo = opcoes.conjunto.ok_or("Informe o arquivo do conjunto de avaliação.")?;
+    let conjunto = carregar_conjunto(&arquivo)?;
+    let pasta = arquivo.parent().unwrap_or(Path::new(".")).to_path_buf();
+    let template = template_qa(opcoes.template_versao).await?;
+    let modelo = template.modelo(opcoes.modelo.as_deref());
+
+    let gravado = match (&opcoes.reproduzir, &opcoes.gravar) {
+        (Some(arquivo), _) => Some(Arc::new(ProviderGravado::reproduzir(arquivo)?)),
+        (None, Some(arquivo)) => {
+            let fetch = Client::builder().timeout(tempo_limite()).build().map_err(|e| e.to_string())?;
+            Some(Arc::new(ProviderGravado::gravar(provider_from_env(fetch)?, arquivo)?))
+        }
+        (None, None) => None,
+    };
+    let llm: Arc<dyn LlmProvider> = match &gravado {
+        Some(gravado) => gravado.clone(),
+        None => {
+            let fetch = Client::builder().timeout(tempo_limite()).build().map_err(|e| e.to_string())?;
+            provider_from_env(fetch)?
+        }
+    };
+
+    println!("Evaluating {} case(s) with {} / {}", conjunto.casos.len(), llm.name(), modelo);
+    let relatorio = executar_avaliacao(llm.as_ref(), &template, &modelo, &conjunto, &pasta).await;
+
+    if opcoes.gravar.is_some() && let Some(gravado) = &gravado {
+        gravado.salvar()?;
+    }
+    if let Some(saida) = &opcoes.saida {
+        let json = serde_json::to_string_pretty(&relatorio).map_err(|e| e.to_string())?;
+        std::fs::write(saida, json).map_err(|e| format!("Falha ao salvar o relatório em {}: {}", saida.display(), e))?;
+    }
+
+    println!("\n{}", resumo_relatorio(&relatorio));
+    if let Some(anterior) = &opcoes.comparar {
+        println!("{}", comparar_relatorios(&carregar_relatorio(anterior)?, &relatorio));
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let resultado = if args.first().map(|a| a.as_str()) == Some("comparar") {
+        match (args.get(1), args.get(2)) {
+            (Some(anterior), Some(atual)) => carregar_relatorio(Path::new(anterior))
+                .and_then(|anterior| Ok(comparar_relatorios(&anterior, &carregar_relatorio(Path::new(atual))?)))
+                .map(|comparacao| println!("{}", comparacao)),
+            _ => Err("Uso: avaliar_qa comparar ANTERIOR.json ATUAL.json".to_string()),
+        }
+    } else {
+        let runtime = tuono_lib::tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to build async runtime");
+        // Configuração validada como no servidor (var.env, ver `config`)
+        config::iniciar().and_then(|_| ler_opcoes(&args)).and_then(|opcoes| runtime.block_on(avaliar(opcoes)))
+    };
+
+    if let Err(e) = resultado {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
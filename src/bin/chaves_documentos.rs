@@ -0,0 +1,49 @@
+// Chave mestra dos documentos (ver src/cifra.rs).
+//
+//   cargo run --bin chaves_documentos -- gerar
+//   cargo run --bin chaves_documentos -- rotacionar
+//
+// `gerar` imprime uma chave mestra nova. `rotacionar` recifra as chaves de dados
+// com a chave em DOCUMENTOS_CHAVE_MESTRA, abrindo as antigas com a chave em
+// DOCUMENTOS_CHAVE_MESTRA_ANTERIOR, e cifra os documentos gravados antes da
+// cifragem. O conteúdo dos arquivos já cifrados não é tocado.
+//
+// `rotacionar` lê a configuração como o servidor (ver src/config.rs); `gerar`
+// não a lê, porque roda antes de a chave existir.
+use tuono_app::cifra::{gerar_chave_mestra, rotacionar_chaves};
+use tuono_app::{config, connect_db};
+
+async fn rotacionar() -> Result<(), String> {
+    config::iniciar()?;
+    let client_db = connect_db().await.map_err(|e| format!("Database connection error: {}", e))?;
+    let relatorio = rotacionar_chaves(&client_db).await?;
+    println!(
+        "Chave mestra {}: {} chaves recifradas, {} documentos cifrados, {} já atualizados.",
+        relatorio.id_chave_mestra, relatorio.chaves_recifradas, relatorio.documentos_cifrados, relatorio.ja_atualizados
+    );
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let resultado = match args.first().map(|a| a.as_str()) {
+        Some("gerar") => {
+            println!("{}", gerar_chave_mestra());
+            Ok(())
+        }
+        Some("rotacionar") => {
+            let runtime = tuono_lib::tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build async runtime");
+            runtime.block_on(rotacionar())
+        }
+        _ => Err("Uso: chaves_documentos gerar | chaves_documentos rotacionar".to_string()),
+    };
+
+    if let Err(e) = resultado {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
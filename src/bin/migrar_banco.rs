@@ -0,0 +1,29 @@
+// Instala o banco vazio ou atualiza o esquema de um existente (ver src/migracao.rs).
+//
+//   cargo run --bin migrar_banco
+//
+// Cria as tabelas, colunas e funções que faltarem, os templates de prompt e o
+// administrador inicial (`USUARIO_ADMIN_*`), sem apagar nada; pode rodar
+// a cada atualização da aplicação.
+use tuono_app::{config, connect_db};
+use tuono_app::migracao::migrar;
+
+async fn executar() -> Result<(), String> {
+    config::iniciar()?;
+    let mut client_db = connect_db().await.map_err(|e| format!("Database connection error: {}", e))?;
+    migrar(&mut client_db).await?;
+    println!("Banco atualizado.");
+    Ok(())
+}
+
+fn main() {
+    let runtime = tuono_lib::tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build async runtime");
+
+    if let Err(e) = runtime.block_on(executar()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,307 @@
+// src/cifra.rs
+// Cifragem dos arquivos em `Documento.arquivo` (criptografia envelopada).
+//
+// Cada documento tem a própria chave de dados, sorteada a cada gravação do
+// arquivo. O arquivo é cifrado com ela em AES-256-GCM, e a chave de dados vai
+// para `Documento.chave_cifrada`, cifrada com a chave mestra. A chave mestra
+// nunca fica no banco: vem de `DOCUMENTOS_CHAVE_MESTRA` (base64 de 32 bytes) ou
+// do arquivo em `DOCUMENTOS_CHAVE_MESTRA_ARQUIVO`, lida e validada uma vez por
+// `crate::config`. `Documento.id_chave_mestra` guarda a impressão digital da
+// chave mestra usada.
+//
+// Na troca da chave mestra, a anterior vai para `DOCUMENTOS_CHAVE_MESTRA_ANTERIOR`
+// (ou `..._ANTERIOR_ARQUIVO`) e `rotacionar_chaves` recifra só as chaves de
+// dados; o conteúdo dos arquivos não é tocado.
+//
+// Documentos gravados antes da cifragem têm `chave_cifrada` nula e são lidos
+// como estão até `rotacionar_chaves` cifrá-los.
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use tokio_postgres::{GenericClient, Row};
+
+use crate::config;
+
+const TAMANHO_NONCE: usize = 12;
+
+/// Chave que cifra as chaves de dados dos documentos.
+#[derive(Clone)]
+pub struct ChaveMestra {
+    /// Impressão digital da chave (início do SHA-256), gravada em `Documento.id_chave_mestra`.
+    pub id: String,
+    cifra: Aes256Gcm,
+}
+
+impl ChaveMestra {
+    pub fn from_base64(valor: &str, origem: &str) -> Result<Self, String> {
+        let bytes = general_purpose::STANDARD
+            .decode(valor.trim())
+            .map_err(|e| format!("Chave mestra inválida em {}: {}", origem, e))?;
+        if bytes.len() != 32 {
+            return Err(format!("A chave mestra em {} deve ter 32 bytes (tem {}).", origem, bytes.len()));
+        }
+        let id = Sha256::digest(&bytes)[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        Ok(ChaveMestra { id, cifra: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&bytes)) })
+    }
+
+    // A impressão digital entra como dado associado: a chave de dados só abre
+    // com a mesma chave mestra registrada ao lado dela
+    fn embrulhar(&self, chave_dados: &[u8]) -> Result<Vec<u8>, String> {
+        cifrar_com(&self.cifra, chave_dados, self.id.as_bytes())
+    }
+
+    fn desembrulhar(&self, chave_cifrada: &[u8]) -> Result<Vec<u8>, String> {
+        decifrar_com(&self.cifra, chave_cifrada, self.id.as_bytes())
+            .map_err(|_| "Não foi possível abrir a chave do documento com a chave mestra.".to_string())
+    }
+}
+
+// Só a impressão digital aparece no `Debug` da configuração
+impl fmt::Debug for ChaveMestra {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ChaveMestra({})", self.id)
+    }
+}
+
+/// Gera uma chave mestra nova, em base64, para `DOCUMENTOS_CHAVE_MESTRA`.
+pub fn gerar_chave_mestra() -> String {
+    general_purpose::STANDARD.encode(Aes256Gcm::generate_key(OsRng))
+}
+
+// nonce || texto cifrado com a tag
+fn cifrar_com(cifra: &Aes256Gcm, dados: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let cifrado = cifra.encrypt(&nonce, Payload { msg: dados, aad }).map_err(|_| "Falha ao cifrar o arquivo.".to_string())?;
+    let mut saida = Vec::with_capacity(TAMANHO_NONCE + cifrado.len());
+    saida.extend_from_slice(&nonce);
+    saida.extend_from_slice(&cifrado);
+    Ok(saida)
+}
+
+fn decifrar_com(cifra: &Aes256Gcm, dados: &[u8], aad: &[u8]) -> Result<Vec<u8>, String> {
+    if dados.len() < TAMANHO_NONCE {
+        return Err("Conteúdo cifrado truncado.".to_string());
+    }
+    let (nonce, cifrado) = dados.split_at(TAMANHO_NONCE);
+    cifra
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: cifrado, aad })
+        .map_err(|_| "O arquivo não confere com a chave: conteúdo adulterado ou chave errada.".to_string())
+}
+
+/// Arquivo pronto para as colunas `arquivo`, `chave_cifrada` e `id_chave_mestra`.
+pub struct ArquivoCifrado {
+    pub arquivo: Vec<u8>,
+    pub chave_cifrada: Vec<u8>,
+    pub id_chave_mestra: String,
+}
+
+/// Cifra o arquivo com uma chave de dados nova.
+pub fn cifrar_arquivo(dados: &[u8]) -> Result<ArquivoCifrado, String> {
+    cifrar_com_mestra(config::atual().chave_mestra()?, dados)
+}
+
+fn cifrar_com_mestra(mestra: &ChaveMestra, dados: &[u8]) -> Result<ArquivoCifrado, String> {
+    let chave_dados = Aes256Gcm::generate_key(OsRng);
+    let arquivo = cifrar_com(&Aes256Gcm::new(&chave_dados), dados, &[])?;
+    Ok(ArquivoCifrado { arquivo, chave_cifrada: mestra.embrulhar(&chave_dados)?, id_chave_mestra: mestra.id.clone() })
+}
+
+fn chave_para<'a>(atual: &'a ChaveMestra, anterior: Option<&'a ChaveMestra>, id_chave_mestra: Option<&str>) -> Result<&'a ChaveMestra, String> {
+    if id_chave_mestra.is_none_or(|id| id == atual.id) {
+        return Ok(atual);
+    }
+    match anterior {
+        Some(anterior) if id_chave_mestra == Some(anterior.id.as_str()) => Ok(anterior),
+        _ => Err(format!(
+            "O documento foi cifrado com a chave mestra {}, que não está configurada.",
+            id_chave_mestra.unwrap_or_default()
+        )),
+    }
+}
+
+/// Conteúdo original do arquivo. Sem `chave_cifrada`, o arquivo foi gravado
+/// antes da cifragem e já está em claro.
+pub fn decifrar_arquivo(arquivo: &[u8], chave_cifrada: Option<&[u8]>, id_chave_mestra: Option<&str>) -> Result<Vec<u8>, String> {
+    let Some(chave_cifrada) = chave_cifrada else {
+        return Ok(arquivo.to_vec());
+    };
+    let config = config::atual();
+    decifrar_com_mestras(config.chave_mestra()?, config.chave_mestra_anterior(), arquivo, chave_cifrada, id_chave_mestra)
+}
+
+fn decifrar_com_mestras(
+    atual: &ChaveMestra,
+    anterior: Option<&ChaveMestra>,
+    arquivo: &[u8],
+    chave_cifrada: &[u8],
+    id_chave_mestra: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let chave_dados = chave_para(atual, anterior, id_chave_mestra)?.desembrulhar(chave_cifrada)?;
+    decifrar_com(&Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&chave_dados)), arquivo, &[])
+}
+
+/// `arquivo` decifrado de uma linha que traz também `chave_cifrada` e
+/// `id_chave_mestra`. `None` quando o documento não tem arquivo.
+pub fn arquivo_da_linha(row: &Row) -> Result<Option<Vec<u8>>, String> {
+    let Some(arquivo) = row.get::<_, Option<Vec<u8>>>("arquivo") else {
+        return Ok(None);
+    };
+    decifrar_arquivo(&arquivo, row.get("chave_cifrada"), row.get("id_chave_mestra")).map(Some)
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct RelatorioRotacao {
+    pub id_chave_mestra: String,
+    /// Chaves de dados recifradas com a chave mestra atual.
+    pub chaves_recifradas: usize,
+    /// Documentos gravados antes da cifragem e cifrados agora.
+    pub documentos_cifrados: usize,
+    pub ja_atualizados: usize,
+}
+
+/// Recifra com a chave mestra atual as chaves de dados cifradas com a anterior
+/// e cifra os documentos ainda em claro. Cada documento é atualizado num
+/// comando próprio: se a rotação parar no meio, basta rodar de novo.
+pub async fn rotacionar_chaves<C: GenericClient>(client: &C) -> Result<RelatorioRotacao, String> {
+    let config = config::atual();
+    let atual = config.chave_mestra()?;
+    let anterior = config.chave_mestra_anterior();
+    let mut relatorio = RelatorioRotacao { id_chave_mestra: atual.id.clone(), ..Default::default() };
+
+    let rows = client
+        .query(
+            "SELECT id_documento, chave_cifrada, id_chave_mestra FROM Documento WHERE arquivo IS NOT NULL ORDER BY id_documento;",
+            &[],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for row in &rows {
+        let id_documento: i32 = row.get("id_documento");
+        let chave_cifrada: Option<Vec<u8>> = row.get("chave_cifrada");
+        let id_chave: Option<String> = row.get("id_chave_mestra");
+
+        match chave_cifrada {
+            Some(_) if id_chave.as_deref() == Some(atual.id.as_str()) => relatorio.ja_atualizados += 1,
+            Some(chave_cifrada) => {
+                let nova = recifrar_chave_dados(atual, anterior, &chave_cifrada, id_chave.as_deref())
+                    .map_err(|e| format!("Documento {}: {}", id_documento, e))?;
+                // A condição evita sobrescrever um arquivo regravado durante a rotação
+                client
+                    .execute(
+                        "UPDATE Documento SET chave_cifrada = $1, id_chave_mestra = $2 WHERE id_documento = $3 AND chave_cifrada = $4;",
+                        &[&nova, &atual.id, &id_documento, &chave_cifrada],
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+                relatorio.chaves_recifradas += 1;
+            }
+            None => {
+                let Some(arquivo) = client
+                    .query_opt("SELECT arquivo FROM Documento WHERE id_documento = $1 AND chave_cifrada IS NULL;", &[&id_documento])
+                    .await
+                    .map_err(|e| e.to_string())?
+                    .and_then(|row| row.get::<_, Option<Vec<u8>>>("arquivo"))
+                else {
+                    continue;
+                };
+                let cifrado = cifrar_com_mestra(atual, &arquivo)?;
+                client
+                    .execute(
+                        "UPDATE Documento SET arquivo = $1, chave_cifrada = $2, id_chave_mestra = $3 WHERE id_documento = $4 AND chave_cifrada IS NULL;",
+                        &[&cifrado.arquivo, &cifrado.chave_cifrada, &cifrado.id_chave_mestra, &id_documento],
+                    )
+                    .await
+                    .map_err(|e| e.to_string())?;
+                relatorio.documentos_cifrados += 1;
+            }
+        }
+    }
+
+    Ok(relatorio)
+}
+
+/// Chave de dados cifrada com a anterior, recifrada com a chave mestra atual.
+fn recifrar_chave_dados(atual: &ChaveMestra, anterior: Option<&ChaveMestra>, chave_cifrada: &[u8], id_chave: Option<&str>) -> Result<Vec<u8>, String> {
+    let mestra = match anterior {
+        Some(anterior) if id_chave == Some(anterior.id.as_str()) => anterior,
+        _ => return Err(format!("cifrado com a chave mestra {}, que não é a anterior configurada.", id_chave.unwrap_or_default())),
+    };
+    atual.embrulhar(&mestra.desembrulhar(chave_cifrada)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chave() -> ChaveMestra {
+        ChaveMestra::from_base64(&gerar_chave_mestra(), "teste").unwrap()
+    }
+
+    #[test]
+    fn arquivo_volta_igual_e_nao_fica_em_claro() {
+        let mestra = chave();
+        let original = b"contrato de honorarios".to_vec();
+        let cifrado = cifrar_com_mestra(&mestra, &original).unwrap();
+        assert_eq!(cifrado.id_chave_mestra, mestra.id);
+        assert!(!cifrado.arquivo.windows(original.len()).any(|janela| janela == original.as_slice()));
+
+        let decifrado = decifrar_com_mestras(&mestra, None, &cifrado.arquivo, &cifrado.chave_cifrada, Some(&mestra.id)).unwrap();
+        assert_eq!(decifrado, original);
+    }
+
+    #[test]
+    fn arquivo_adulterado_ou_outra_chave_nao_abrem() {
+        let mestra = chave();
+        let cifrado = cifrar_com_mestra(&mestra, b"peticao inicial").unwrap();
+
+        let mut adulterado = cifrado.arquivo.clone();
+        *adulterado.last_mut().unwrap() ^= 1;
+        assert!(decifrar_com_mestras(&mestra, None, &adulterado, &cifrado.chave_cifrada, Some(&mestra.id)).is_err());
+
+        // Outra chave mestra não abre, com ou sem a impressão digital registrada
+        let outra = chave();
+        assert!(decifrar_com_mestras(&outra, None, &cifrado.arquivo, &cifrado.chave_cifrada, None).is_err());
+        assert!(decifrar_com_mestras(&outra, None, &cifrado.arquivo, &cifrado.chave_cifrada, Some(&mestra.id)).is_err());
+    }
+
+    #[test]
+    fn rotacao_recifra_so_a_chave_de_dados() {
+        let antiga = chave();
+        let nova = chave();
+        let original = b"procuracao".to_vec();
+        let cifrado = cifrar_com_mestra(&antiga, &original).unwrap();
+
+        // Antes da rotação, a anterior configurada ainda abre o documento
+        let lido = decifrar_com_mestras(&nova, Some(&antiga), &cifrado.arquivo, &cifrado.chave_cifrada, Some(&antiga.id)).unwrap();
+        assert_eq!(lido, original);
+
+        let recifrada = recifrar_chave_dados(&nova, Some(&antiga), &cifrado.chave_cifrada, Some(&antiga.id)).unwrap();
+        assert_ne!(recifrada, cifrado.chave_cifrada);
+        // O arquivo não muda e passa a abrir só com a nova
+        let lido = decifrar_com_mestras(&nova, None, &cifrado.arquivo, &recifrada, Some(&nova.id)).unwrap();
+        assert_eq!(lido, original);
+        assert!(decifrar_com_mestras(&antiga, None, &cifrado.arquivo, &recifrada, Some(&nova.id)).is_err());
+    }
+
+    #[test]
+    fn rotacao_recusa_chave_que_nao_e_a_anterior() {
+        let desconhecida = chave();
+        let nova = chave();
+        let cifrado = cifrar_com_mestra(&desconhecida, b"recibo").unwrap();
+        assert!(recifrar_chave_dados(&nova, None, &cifrado.chave_cifrada, Some(&desconhecida.id)).is_err());
+        assert!(recifrar_chave_dados(&nova, Some(&chave()), &cifrado.chave_cifrada, Some(&desconhecida.id)).is_err());
+        assert!(decifrar_com_mestras(&nova, None, &cifrado.arquivo, &cifrado.chave_cifrada, Some(&desconhecida.id)).is_err());
+    }
+
+    #[test]
+    fn chave_mestra_exige_32_bytes_em_base64() {
+        assert!(ChaveMestra::from_base64("nao e base64!", "teste").is_err());
+        assert!(ChaveMestra::from_base64(&general_purpose::STANDARD.encode([0u8; 16]), "teste").is_err());
+        assert!(ChaveMestra::from_base64(&format!("{}\n", gerar_chave_mestra()), "teste").is_ok());
+    }
+}
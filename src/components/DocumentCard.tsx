@@ -6,6 +6,9 @@ export interface Document {
   descricao: string;
   data_envio: string; // ISO 8601 string
   nome_arquivo: string; // Nome original do arquivo (com extensão)
+  versao?: number;
+  gerado_por_ia?: boolean;
+  status_minuta?: 'pendente' | 'aprovada' | null; // Minutas geradas por IA
 }
 
 interface DocumentCardProps {
@@ -37,6 +40,13 @@ export default function DocumentCard({ document, onViewDetails, onEdit, onDelete
   return (
     <div className="client-card">
       <h3 className="client-card-name">Documento: {document.nome_arquivo}</h3>
+      {document.gerado_por_ia && (
+        <p className={document.status_minuta === 'pendente' ? 'error-message' : 'client-card-detail'}>
+          {document.status_minuta === 'pendente'
+            ? `Minuta gerada por IA (v${document.versao ?? 1}) - pendente de aprovação`
+            : `Minuta gerada por IA (v${document.versao ?? 1}) - aprovada por advogado`}
+        </p>
+      )}
       <p className="client-card-detail">
         <strong>ID Doc:</strong> {document.id_documento}
       </p>
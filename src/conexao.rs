@@ -0,0 +1,204 @@
+// src/conexao.rs
+// Conexão com o PostgreSQL, com TLS (rustls) configurado pelo ambiente:
+//
+//   DATABASE_SSLMODE      disable, prefer (padrão), require ou verify-full
+//   DATABASE_SSLROOTCERT  PEM com as CAs aceitas (padrão: CAs públicas do webpki)
+//   DATABASE_SSLCERT      PEM com o certificado do cliente (opcional)
+//   DATABASE_SSLKEY       PEM com a chave privada do cliente
+//
+// Os modos seguem o `sslmode` do libpq: `prefer` tenta TLS e aceita conexão sem
+// TLS se o servidor não oferecer; `require` exige TLS mas não confere o
+// certificado; `verify-full` confere a cadeia e o nome do servidor. Sem
+// DATABASE_SSLMODE vale o `sslmode` da própria DATABASE_URL, se houver.
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::Arc;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_postgres::config::SslMode;
+use tokio_postgres::{Client, Config, NoTls};
+use tokio_postgres_rustls::MakeRustlsConnect;
+use tuono_lib::tokio;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModoSsl {
+    Disable,
+    Prefer,
+    Require,
+    VerifyFull,
+}
+
+impl ModoSsl {
+    pub fn pelo_nome(valor: &str) -> Option<ModoSsl> {
+        match valor.trim().to_lowercase().as_str() {
+            "disable" => Some(ModoSsl::Disable),
+            "prefer" => Some(ModoSsl::Prefer),
+            "require" => Some(ModoSsl::Require),
+            "verify-full" => Some(ModoSsl::VerifyFull),
+            _ => None,
+        }
+    }
+
+    fn da_config(config: &Config) -> ModoSsl {
+        match config.get_ssl_mode() {
+            SslMode::Disable => ModoSsl::Disable,
+            SslMode::Require => ModoSsl::Require,
+            _ => ModoSsl::Prefer,
+        }
+    }
+}
+
+/// Opções de TLS (`DATABASE_SSL*`), lidas pela `Config`.
+#[derive(Debug, Clone, Default)]
+pub struct OpcoesTls {
+    pub modo: Option<ModoSsl>,
+    pub ca: Option<String>,
+    pub certificado_cliente: Option<String>,
+    pub chave_cliente: Option<String>,
+}
+
+impl OpcoesTls {
+    pub fn ler(variavel: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let modo = match variavel("DATABASE_SSLMODE") {
+            Some(valor) => Some(ModoSsl::pelo_nome(&valor).ok_or_else(|| {
+                format!("DATABASE_SSLMODE inválido: '{}'. Use disable, prefer, require ou verify-full.", valor)
+            })?),
+            None => None,
+        };
+        let opcoes = OpcoesTls {
+            modo,
+            ca: variavel("DATABASE_SSLROOTCERT"),
+            certificado_cliente: variavel("DATABASE_SSLCERT"),
+            chave_cliente: variavel("DATABASE_SSLKEY"),
+        };
+        if opcoes.certificado_cliente.is_some() != opcoes.chave_cliente.is_some() {
+            return Err("DATABASE_SSLCERT e DATABASE_SSLKEY devem ser informados juntos.".to_string());
+        }
+        Ok(opcoes)
+    }
+}
+
+fn abrir(caminho: &str) -> Result<BufReader<File>, String> {
+    File::open(caminho).map(BufReader::new).map_err(|e| format!("Falha ao abrir {}: {}", caminho, e))
+}
+
+fn ler_certificados(caminho: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certificados = rustls_pemfile::certs(&mut abrir(caminho)?)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Certificado inválido em {}: {}", caminho, e))?;
+    if certificados.is_empty() {
+        return Err(format!("Nenhum certificado PEM em {}.", caminho));
+    }
+    Ok(certificados)
+}
+
+fn ler_chave(caminho: &str) -> Result<PrivateKeyDer<'static>, String> {
+    rustls_pemfile::private_key(&mut abrir(caminho)?)
+        .map_err(|e| format!("Chave inválida em {}: {}", caminho, e))?
+        .ok_or_else(|| format!("Nenhuma chave privada PEM em {}.", caminho))
+}
+
+/// Aceita qualquer certificado, para `prefer` e `require`. As assinaturas do
+/// handshake continuam sendo conferidas.
+#[derive(Debug)]
+struct SemVerificacao(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SemVerificacao {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn config_tls(modo: ModoSsl, opcoes: &OpcoesTls) -> Result<ClientConfig, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Falha ao configurar o TLS: {}", e))?;
+
+    let builder = if modo == ModoSsl::VerifyFull {
+        let mut raizes = RootCertStore::empty();
+        match &opcoes.ca {
+            Some(caminho) => {
+                for certificado in ler_certificados(caminho)? {
+                    raizes.add(certificado).map_err(|e| format!("CA inválida em {}: {}", caminho, e))?;
+                }
+            }
+            None => raizes.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+        }
+        builder.with_root_certificates(raizes)
+    } else {
+        builder.dangerous().with_custom_certificate_verifier(Arc::new(SemVerificacao(provider)))
+    };
+
+    match (&opcoes.certificado_cliente, &opcoes.chave_cliente) {
+        (Some(certificado), Some(chave)) => builder
+            .with_client_auth_cert(ler_certificados(certificado)?, ler_chave(chave)?)
+            .map_err(|e| format!("Certificado de cliente inválido: {}", e)),
+        _ => Ok(builder.with_no_client_auth()),
+    }
+}
+
+/// Conecta em `url` (formato do tokio-postgres) com o TLS de `opcoes` e deixa a
+/// conexão rodando em segundo plano.
+pub async fn conectar_com(url: &str, opcoes: &OpcoesTls) -> Result<Client, String> {
+    let mut config: Config = url.parse().map_err(|e| format!("DATABASE_URL inválida: {}", e))?;
+    let modo = opcoes.modo.unwrap_or_else(|| ModoSsl::da_config(&config));
+
+    let client = if modo == ModoSsl::Disable {
+        let (client, connection) = config.connect(NoTls).await.map_err(|e| e.to_string())?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("Database connection error: {}", e);
+            }
+        });
+        client
+    } else {
+        config.ssl_mode(if modo == ModoSsl::Prefer { SslMode::Prefer } else { SslMode::Require });
+        let tls = MakeRustlsConnect::new(config_tls(modo, opcoes)?);
+        let (client, connection) = config.connect(tls).await.map_err(|e| e.to_string())?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("Database connection error: {}", e);
+            }
+        });
+        client
+    };
+    Ok(client)
+}
+
+/// Conecta em `url` com o TLS da configuração (`crate::config`).
+pub async fn conectar(url: &str) -> Result<Client, String> {
+    conectar_com(url, &crate::config::atual().banco.tls).await
+}
@@ -0,0 +1,338 @@
+// src/ia/analise.rs
+// Resumo e extração estruturada (fatos-chave) de documentos via Ollama.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Client as DbClient;
+
+use super::{extrair_texto, modelo_analise, truncar_texto};
+use super::prompts::{carregar_template, PromptTemplate, TEMPLATE_EXTRACAO_DOCUMENTO, TEMPLATE_RESUMO_DOCUMENTO};
+use super::provider::{GenerateRequest, LlmProvider};
+use crate::cifra::arquivo_da_linha;
+
+/// Número máximo de tentativas quando a extração não passa na validação.
+const MAX_TENTATIVAS: i32 = 3;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Parte {
+    pub nome: String,
+    pub papel: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DataMencionada {
+    pub data: String, // AAAA-MM-DD
+    pub descricao: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValorMonetario {
+    pub valor: f64,
+    pub moeda: Option<String>,
+    pub descricao: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Prazo {
+    pub descricao: String,
+    pub dias: Option<i32>,
+    pub data_limite: Option<String>, // AAAA-MM-DD
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExtracaoDocumento {
+    pub partes: Vec<Parte>,
+    pub cpfs: Vec<String>,
+    pub cnpjs: Vec<String>,
+    pub datas: Vec<DataMencionada>,
+    pub valores: Vec<ValorMonetario>,
+    pub prazos: Vec<Prazo>,
+    pub juiz: Option<String>,
+    pub vara: Option<String>,
+}
+
+/// JSON Schema enviado no campo `format` do Ollama.
+pub fn schema_extracao() -> Value {
+    let texto_opcional = json!({"type": ["string", "null"]});
+    json!({
+        "type": "object",
+        "properties": {
+            "partes": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {"nome": {"type": "string"}, "papel": texto_opcional.clone()},
+                    "required": ["nome", "papel"],
+                    "additionalProperties": false
+                }
+            },
+            "cpfs": {"type": "array", "items": {"type": "string"}},
+            "cnpjs": {"type": "array", "items": {"type": "string"}},
+            "datas": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {"data": {"type": "string"}, "descricao": texto_opcional.clone()},
+                    "required": ["data", "descricao"],
+                    "additionalProperties": false
+                }
+            },
+            "valores": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {"valor": {"type": "number"}, "moeda": texto_opcional.clone(), "descricao": texto_opcional.clone()},
+                    "required": ["valor", "moeda", "descricao"],
+                    "additionalProperties": false
+                }
+            },
+            "prazos": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "descricao": {"type": "string"},
+                        "dias": {"type": ["integer", "null"]},
+                        "data_limite": texto_opcional.clone()
+                    },
+                    "required": ["descricao", "dias", "data_limite"],
+                    "additionalProperties": false
+                }
+            },
+            "juiz": texto_opcional.clone(),
+            "vara": texto_opcional.clone()
+        },
+        "required": ["partes", "cpfs", "cnpjs", "datas", "valores", "prazos", "juiz", "vara"],
+        "additionalProperties": false
+    })
+}
+
+fn somente_digitos(valor: &str) -> String {
+    valor.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Valida a resposta do modelo contra o schema e regras de formato.
+pub fn validar_extracao(resposta: &str) -> Result<ExtracaoDocumento, String> {
+    let extracao: ExtracaoDocumento = serde_json::from_str(resposta)
+        .map_err(|e| format!("A resposta não corresponde ao schema: {}", e))?;
+
+    for cpf in &extracao.cpfs {
+        if somente_digitos(cpf).len() != 11 {
+            return Err(format!("CPF inválido: '{}'. Um CPF tem 11 dígitos.", cpf));
+        }
+    }
+    for cnpj in &extracao.cnpjs {
+        if somente_digitos(cnpj).len() != 14 {
+            return Err(format!("CNPJ inválido: '{}'. Um CNPJ tem 14 dígitos.", cnpj));
+        }
+    }
+    for data in &extracao.datas {
+        if NaiveDate::parse_from_str(&data.data, "%Y-%m-%d").is_err() {
+            return Err(format!("Data inválida: '{}'. Use o formato AAAA-MM-DD.", data.data));
+        }
+    }
+    for prazo in &extracao.prazos {
+        if let Some(limite) = &prazo.data_limite && NaiveDate::parse_from_str(limite, "%Y-%m-%d").is_err() {
+            return Err(format!("Data limite inválida: '{}'. Use o formato AAAA-MM-DD.", limite));
+        }
+        if let Some(dias) = prazo.dias && dias <= 0 {
+            return Err(format!("Prazo em dias inválido: {}.", dias));
+        }
+    }
+
+    Ok(extracao)
+}
+
+fn valores_documento(texto: &str) -> HashMap<&'static str, String> {
+    HashMap::from([("documento", truncar_texto(texto).to_string())])
+}
+
+/// Gera o resumo do documento com o template `resumo_documento`.
+pub async fn resumir(llm: &dyn LlmProvider, template: &PromptTemplate, modelo: &str, texto: &str) -> Result<String, String> {
+    let (system, prompt) = template.render(&valores_documento(texto))?;
+    let resposta = llm.generate(&GenerateRequest {
+        model: modelo.to_string(),
+        prompt,
+        system,
+        options: template.opcoes(),
+        ..Default::default()
+    }).await?;
+    Ok(resposta.text)
+}
+
+/// Extrai os fatos-chave em JSON, repetindo a chamada com o erro de validação
+/// até `MAX_TENTATIVAS` vezes. Retorna a extração e o número de tentativas.
+pub async fn extrair_fatos(llm: &dyn LlmProvider, template: &PromptTemplate, modelo: &str, texto: &str) -> Result<(ExtracaoDocumento, i32), String> {
+    let schema = schema_extracao();
+    let (system, prompt_base) = template.render(&valores_documento(texto))?;
+
+    let mut ultimo_erro = String::new();
+    for tentativa in 1..=MAX_TENTATIVAS {
+        let prompt = if ultimo_erro.is_empty() {
+            prompt_base.clone()
+        } else {
+            format!("{}\n\nA resposta anterior foi rejeitada: {}. Corrija e responda novamente.", prompt_base, ultimo_erro)
+        };
+
+        let resposta = llm.generate(&GenerateRequest {
+            model: modelo.to_string(),
+            prompt,
+            system: system.clone(),
+            format: Some(schema.clone()),
+            options: template.opcoes(),
+        }).await?;
+        match validar_extracao(&resposta.text) {
+            Ok(extracao) => return Ok((extracao, tentativa)),
+            Err(e) => {
+                eprintln!("Extraction attempt {} failed validation: {}", tentativa, e);
+                ultimo_erro = e;
+            }
+        }
+    }
+
+    Err(format!("Extração inválida após {} tentativas: {}", MAX_TENTATIVAS, ultimo_erro))
+}
+
+/// Modelo da análise: o solicitado, o padrão do template de resumo ou o da instalação.
+pub async fn modelo_para_analise(client_db: &DbClient, solicitado: Option<String>) -> String {
+    match carregar_template(client_db, TEMPLATE_RESUMO_DOCUMENTO, None).await {
+        Ok(template) => template.modelo(solicitado.as_deref()),
+        Err(e) => {
+            eprintln!("{}", e);
+            solicitado.unwrap_or_else(modelo_analise)
+        }
+    }
+}
+
+/// Registra uma análise pendente para o documento e devolve seu id.
+pub async fn registrar_analise(client_db: &DbClient, id_documento: i32, modelo: &str) -> Result<i32, tokio_postgres::Error> {
+    let row = client_db
+        .query_one(
+            "INSERT INTO Documento_Analise (id_documento, modelo, status) VALUES ($1, $2, 'pendente') RETURNING id_analise;",
+            &[&id_documento, &modelo],
+        )
+        .await?;
+    Ok(row.get("id_analise"))
+}
+
+/// Executa a análise registrada em `id_analise` e grava o resultado (ou o erro).
+pub async fn executar_analise(llm: &dyn LlmProvider, client_db: &DbClient, id_analise: i32) -> Result<(), String> {
+    let row = client_db
+        .query_one(
+            "SELECT a.modelo, d.arquivo, d.chave_cifrada, d.id_chave_mestra FROM Documento_Analise a JOIN Documento d ON a.id_documento = d.id_documento WHERE a.id_analise = $1;",
+            &[&id_analise],
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch document for analysis: {}", e))?;
+
+    let modelo: String = row.get("modelo");
+    let arquivo = arquivo_da_linha(&row);
+
+    let resultado = async {
+        let arquivo = arquivo?.ok_or_else(|| "Documento sem conteúdo.".to_string())?;
+        let texto = extrair_texto(&arquivo)?;
+        let template_resumo = carregar_template(client_db, TEMPLATE_RESUMO_DOCUMENTO, None).await?;
+        let template_extracao = carregar_template(client_db, TEMPLATE_EXTRACAO_DOCUMENTO, None).await?;
+        let versoes_prompt = json!({
+            TEMPLATE_RESUMO_DOCUMENTO: template_resumo.id_versao,
+            TEMPLATE_EXTRACAO_DOCUMENTO: template_extracao.id_versao,
+        });
+        let resumo = resumir(llm, &template_resumo, &modelo, &texto).await?;
+        let (extracao, tentativas) = extrair_fatos(llm, &template_extracao, &modelo, &texto).await?;
+        Ok::<_, String>((resumo, extracao, tentativas, versoes_prompt))
+    }
+    .await;
+
+    match resultado {
+        Ok((resumo, extracao, tentativas, versoes_prompt)) => {
+            let extracao_json = serde_json::to_value(&extracao).map_err(|e| e.to_string())?;
+            client_db
+                .execute(
+                    "UPDATE Documento_Analise SET status = 'concluida', resumo = $1, extracao = $2, tentativas = $3, versoes_prompt = $4, erro = NULL, data_analise = CURRENT_TIMESTAMP WHERE id_analise = $5;",
+                    &[&resumo, &extracao_json, &tentativas, &versoes_prompt, &id_analise],
+                )
+                .await
+                .map_err(|e| format!("Failed to store analysis: {}", e))?;
+            Ok(())
+        }
+        Err(e) => {
+            if let Err(db_err) = client_db
+                .execute(
+                    "UPDATE Documento_Analise SET status = 'erro', erro = $1, data_analise = CURRENT_TIMESTAMP WHERE id_analise = $2;",
+                    &[&e, &id_analise],
+                )
+                .await
+            {
+                eprintln!("Failed to store analysis error: {}", db_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Agenda a análise de um documento recém-enviado em segundo plano.
+pub fn agendar_analise(llm: Arc<dyn LlmProvider>, id_documento: i32, modelo: Option<String>) {
+    tuono_lib::tokio::spawn(async move {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for document analysis: {}", e);
+                return;
+            }
+        };
+
+        let modelo = modelo_para_analise(&client_db, modelo).await;
+        let id_analise = match registrar_analise(&client_db, id_documento, &modelo).await {
+            Ok(id) => id,
+            Err(e) => {
+                eprintln!("Failed to register document analysis: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = executar_analise(llm.as_ref(), &client_db, id_analise).await {
+            eprintln!("Document analysis {} failed: {}", id_analise, e);
+        }
+    });
+}
+
+/// Última análise de um documento, no formato devolvido pela API.
+pub async fn ultima_analise(client_db: &DbClient, id_documento: i32) -> Result<Option<Value>, tokio_postgres::Error> {
+    let row = client_db
+        .query_opt(
+            "SELECT id_analise, modelo, status, resumo, extracao, versoes_prompt, erro, tentativas, data_analise FROM Documento_Analise WHERE id_documento = $1 ORDER BY data_analise DESC, id_analise DESC LIMIT 1;",
+            &[&id_documento],
+        )
+        .await?;
+
+    Ok(row.map(|r| analise_json(&r)))
+}
+
+pub fn analise_json(row: &tokio_postgres::Row) -> Value {
+    let data_analise: Option<chrono::NaiveDateTime> = row.get("data_analise");
+    json!({
+        "id_analise": row.get::<_, i32>("id_analise"),
+        "modelo": row.get::<_, String>("modelo"),
+        "status": row.get::<_, String>("status"),
+        "resumo": row.get::<_, Option<String>>("resumo"),
+        "extracao": row.get::<_, Option<Value>>("extracao"),
+        "versoes_prompt": row.get::<_, Option<Value>>("versoes_prompt"),
+        "erro": row.get::<_, Option<String>>("erro"),
+        "tentativas": row.get::<_, Option<i32>>("tentativas"),
+        "data_analise": data_analise.map(|d| d.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ia::openai::conferir_schema_estrito;
+
+    #[test]
+    fn schema_de_extracao_vale_no_modo_estrito() {
+        assert_eq!(conferir_schema_estrito(&schema_extracao(), "extracao"), Ok(()));
+    }
+}
@@ -0,0 +1,303 @@
+// src/ia/auditoria.rs
+// Registro de todas as chamadas ao LLM (tabela Llm_Chamada) e agregados de uso.
+use std::sync::Arc;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use tokio_postgres::Client as DbClient;
+
+use super::provider::{ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+use super::redacao::Redator;
+use super::hash_hex;
+
+/// Quem fez a chamada e sobre quais documentos ou casos.
+#[derive(Debug, Clone, Default)]
+pub struct ContextoAuditoria {
+    /// Funcionalidade que originou a chamada (ex.: `qa_documento`, `analise_documento`).
+    pub origem: String,
+    pub usuario: Option<String>,
+    pub ids_documento: Vec<i32>,
+    /// Casos de chamadas que não partem de documentos (ex.: `classificacao_caso`).
+    pub ids_caso: Vec<i32>,
+    pub id_versao_prompt: Option<i32>,
+}
+
+impl ContextoAuditoria {
+    pub fn new(origem: &str, ids_documento: Vec<i32>) -> Self {
+        ContextoAuditoria { origem: origem.to_string(), ids_documento, ..Default::default() }
+    }
+
+    /// Registra o usuário que fez a chamada.
+    pub fn por(self, usuario: &str) -> Self {
+        ContextoAuditoria { usuario: Some(usuario.to_string()), ..self }
+    }
+
+    /// Registra o caso de que a chamada trata.
+    pub fn do_caso(self, id_caso: i32) -> Self {
+        ContextoAuditoria { ids_caso: vec![id_caso], ..self }
+    }
+}
+
+/// `LLM_AUDIT_PROMPT=completo` grava o prompt inteiro; o padrão (`hash`) grava só o SHA-256.
+fn gravar_prompt_completo() -> bool {
+    crate::config::atual().llm.auditar_prompt_completo
+}
+
+fn ns_para_ms(ns: Option<i64>) -> Option<i64> {
+    ns.map(|n| n / 1_000_000)
+}
+
+struct Registro<'a> {
+    operacao: &'static str,
+    modelo: &'a str,
+    entrada: String,
+    /// `false` grava só o hash mesmo com `LLM_AUDIT_PROMPT=completo`.
+    gravar_entrada: bool,
+    resposta: Option<&'a LlmResponse>,
+    /// Texto da resposta com os mesmos marcadores da entrada.
+    texto_resposta: Option<String>,
+    erro: Option<&'a LlmError>,
+    latencia_ms: i64,
+}
+
+/// Redator com as categorias de `LLM_REDACAO`: o registro guarda o texto como
+/// o provedor o recebeu (ver `redacao::redigir`), nunca os dados pessoais.
+fn redator_configurado() -> Redator {
+    Redator::new(&crate::config::atual().llm.redacao)
+}
+
+fn entrada_generate(request: &GenerateRequest, redator: &mut Redator) -> String {
+    let redigida = redator.redigir_generate(request);
+    match &redigida.system {
+        Some(system) => format!("system: {}\n\nuser: {}", system, redigida.prompt),
+        None => redigida.prompt,
+    }
+}
+
+fn entrada_chat(request: &ChatRequest, redator: &mut Redator) -> String {
+    let redigida = redator.redigir_chat(request);
+    redigida.messages.iter().map(|m| format!("{}: {}", m.role, m.content)).collect::<Vec<_>>().join("\n\n")
+}
+
+/// Provedor que repassa as chamadas ao provedor real e registra cada uma.
+/// Falhas ao gravar o registro não interrompem a chamada.
+pub struct ProviderAuditado {
+    interno: Arc<dyn LlmProvider>,
+    contexto: ContextoAuditoria,
+}
+
+/// Envolve o provedor para que toda chamada feita por ele seja auditada.
+pub fn auditar(interno: Arc<dyn LlmProvider>, contexto: ContextoAuditoria) -> Arc<dyn LlmProvider> {
+    Arc::new(ProviderAuditado { interno, contexto })
+}
+
+impl ProviderAuditado {
+    async fn registrar(&self, registro: Registro<'_>) {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for LLM audit log: {}", e);
+                return;
+            }
+        };
+
+        let hash_prompt = hash_hex(registro.entrada.as_bytes());
+        let prompt = if registro.gravar_entrada && gravar_prompt_completo() { Some(registro.entrada.as_str()) } else { None };
+        let resposta = registro.resposta;
+        let erro = registro.erro.map(|e| e.to_string());
+
+        if let Err(e) = client_db
+            .execute(
+                "INSERT INTO Llm_Chamada (origem, usuario, provedor, operacao, modelo, ids_documento, ids_caso, id_versao_prompt, hash_prompt, prompt, resposta,
+                                          prompt_tokens, completion_tokens, total_duration_ms, load_duration_ms, prompt_eval_duration_ms, eval_duration_ms,
+                                          latencia_ms, sucesso, erro)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20);",
+                &[
+                    &self.contexto.origem,
+                    &self.contexto.usuario,
+                    &self.interno.name(),
+                    &registro.operacao,
+                    &registro.modelo,
+                    &self.contexto.ids_documento,
+                    &self.contexto.ids_caso,
+                    &self.contexto.id_versao_prompt,
+                    &hash_prompt,
+                    &prompt,
+                    &registro.texto_resposta,
+                    &resposta.and_then(|r| r.prompt_tokens),
+                    &resposta.and_then(|r| r.completion_tokens),
+                    &ns_para_ms(resposta.and_then(|r| r.total_duration_ns)),
+                    &ns_para_ms(resposta.and_then(|r| r.load_duration_ns)),
+                    &ns_para_ms(resposta.and_then(|r| r.prompt_eval_duration_ns)),
+                    &ns_para_ms(resposta.and_then(|r| r.eval_duration_ns)),
+                    &registro.latencia_ms,
+                    &erro.is_none(),
+                    &erro,
+                ],
+            )
+            .await
+        {
+            eprintln!("Failed to write LLM audit log: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ProviderAuditado {
+    fn name(&self) -> &'static str {
+        self.interno.name()
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.interno.list_models().await
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let inicio = Instant::now();
+        let resultado = self.interno.generate(request).await;
+        let mut redator = redator_configurado();
+        let entrada = entrada_generate(request, &mut redator);
+        self.registrar(Registro {
+            operacao: "generate",
+            modelo: &request.model,
+            entrada,
+            gravar_entrada: true,
+            resposta: resultado.as_ref().ok(),
+            texto_resposta: resultado.as_ref().ok().map(|r| redator.redigir(&r.text)),
+            erro: resultado.as_ref().err(),
+            latencia_ms: inicio.elapsed().as_millis() as i64,
+        }).await;
+        resultado
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let inicio = Instant::now();
+        let resultado = self.interno.chat(request).await;
+        let mut redator = redator_configurado();
+        let entrada = entrada_chat(request, &mut redator);
+        self.registrar(Registro {
+            operacao: "chat",
+            modelo: &request.model,
+            entrada,
+            gravar_entrada: true,
+            resposta: resultado.as_ref().ok(),
+            texto_resposta: resultado.as_ref().ok().map(|r| redator.redigir(&r.text)),
+            erro: resultado.as_ref().err(),
+            latencia_ms: inicio.elapsed().as_millis() as i64,
+        }).await;
+        resultado
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let inicio = Instant::now();
+        let resultado = self.interno.embeddings(model, input).await;
+        // Os textos já estão no banco (descrições, andamentos, resumos) e podem
+        // ser de vários casos: a chamada guarda só o hash
+        self.registrar(Registro {
+            operacao: "embeddings",
+            modelo: model,
+            entrada: input.join("\n\n"),
+            gravar_entrada: false,
+            resposta: None,
+            texto_resposta: None,
+            erro: resultado.as_ref().err(),
+            latencia_ms: inicio.elapsed().as_millis() as i64,
+        }).await;
+        resultado
+    }
+}
+
+/// Uso agregado por modelo, usuário ou origem.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsoIa {
+    pub chave: String,
+    pub chamadas: i64,
+    pub erros: i64,
+    pub prompt_tokens: i64,
+    pub completion_tokens: i64,
+    pub latencia_media_ms: f64,
+}
+
+/// Agrupamentos aceitos por `uso_agregado`.
+pub const AGRUPAMENTOS_USO: [&str; 3] = ["modelo", "usuario", "origem"];
+
+pub async fn uso_agregado(client_db: &DbClient, agrupar: &str, inicio: Option<NaiveDate>, fim: Option<NaiveDate>) -> Result<Vec<UsoIa>, String> {
+    let coluna = match agrupar {
+        "modelo" => "modelo",
+        "usuario" => "COALESCE(usuario, 'anônimo')",
+        "origem" => "origem",
+        outro => return Err(format!("Agrupamento inválido: '{}'. Use {}.", outro, AGRUPAMENTOS_USO.join(", "))),
+    };
+
+    let query = format!(
+        "SELECT {coluna} AS chave,
+                COUNT(*) AS chamadas,
+                COUNT(*) FILTER (WHERE NOT sucesso) AS erros,
+                COALESCE(SUM(prompt_tokens), 0)::BIGINT AS prompt_tokens,
+                COALESCE(SUM(completion_tokens), 0)::BIGINT AS completion_tokens,
+                COALESCE(AVG(latencia_ms), 0)::DOUBLE PRECISION AS latencia_media_ms
+         FROM Llm_Chamada
+         WHERE ($1::DATE IS NULL OR data_chamada >= $1::DATE)
+           AND ($2::DATE IS NULL OR data_chamada < $2::DATE + 1)
+         GROUP BY 1
+         ORDER BY chamadas DESC;"
+    );
+
+    let rows = client_db
+        .query(&query, &[&inicio, &fim])
+        .await
+        .map_err(|e| format!("Failed to aggregate LLM usage: {}", e))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| UsoIa {
+            chave: row.get("chave"),
+            chamadas: row.get("chamadas"),
+            erros: row.get("erros"),
+            prompt_tokens: row.get("prompt_tokens"),
+            completion_tokens: row.get("completion_tokens"),
+            latencia_media_ms: row.get("latencia_media_ms"),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ia::provider::ChatMessage;
+    use crate::ia::redacao::Categoria;
+
+    #[test]
+    fn prompt_registrado_sai_sem_os_dados_pessoais() {
+        let request = ChatRequest {
+            model: "modelo".to_string(),
+            messages: vec![
+                ChatMessage::system("Responda em português."),
+                ChatMessage::user("O autor, CPF 123.456.789-09, pede revisão do contrato."),
+            ],
+            ..Default::default()
+        };
+        let mut redator = Redator::new(&Categoria::TODAS);
+        let entrada = entrada_chat(&request, &mut redator);
+        assert!(!entrada.contains("123.456.789-09"));
+        assert!(entrada.contains("CPF [CPF_1]"));
+
+        // A resposta restaurada volta a ser gravada com o mesmo marcador
+        assert_eq!(redator.redigir("O CPF 123.456.789-09 confere."), "O CPF [CPF_1] confere.");
+    }
+
+    #[test]
+    fn prompt_de_geracao_registrado_sai_sem_os_dados_pessoais() {
+        let request = GenerateRequest {
+            model: "modelo".to_string(),
+            prompt: "Resuma: CPF 123.456.789-09.".to_string(),
+            system: Some("Contato: contato@exemplo.com".to_string()),
+            ..Default::default()
+        };
+        let entrada = entrada_generate(&request, &mut Redator::new(&Categoria::TODAS));
+        assert!(!entrada.contains("123.456.789-09"));
+        assert!(!entrada.contains("contato@exemplo.com"));
+    }
+}
@@ -0,0 +1,405 @@
+// src/ia/avaliacao.rs
+// Avaliação offline de perguntas e respostas sobre documentos. Um conjunto
+// "golden" (documento, pergunta, fatos esperados) é respondido pelo mesmo
+// caminho de `POST /api/ollama` (ver `qa`) e cada resposta recebe notas de
+// cobertura dos fatos, validade das citações e acerto da recusa. O binário
+// `avaliar_qa` executa o conjunto e compara relatórios de execuções diferentes.
+use std::path::Path;
+use std::time::Instant;
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use super::prompts::PromptTemplate;
+use super::provider::LlmProvider;
+use super::qa::{mensagens_qa, responder_pergunta};
+use super::{extrair_texto, normalizar_texto};
+
+/// Trechos entre aspas mais curtos que isto não são tratados como citação.
+const MIN_CARACTERES_CITACAO: usize = 12;
+
+/// Expressões que indicam que o modelo se recusou a responder (texto normalizado).
+const EXPRESSOES_RECUSA: [&str; 10] = [
+    "nao pode responder",
+    "nao posso responder",
+    "nao e possivel responder",
+    "nao consigo responder",
+    "nao consta",
+    "nao ha informac",
+    "nao menciona",
+    "nao encontrei",
+    "nao esta no documento",
+    "nao informa",
+];
+
+#[derive(Debug, Deserialize)]
+pub struct ConjuntoAvaliacao {
+    pub nome: String,
+    pub casos: Vec<CasoAvaliacao>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CasoAvaliacao {
+    pub id: String,
+    /// Caminho de um PDF ou arquivo de texto, relativo ao arquivo do conjunto.
+    pub documento: Option<String>,
+    /// Texto do documento, no lugar de `documento`.
+    pub texto: Option<String>,
+    #[serde(default)]
+    pub dados_caso: String,
+    pub pergunta: String,
+    #[serde(default)]
+    pub fatos_esperados: Vec<FatoEsperado>,
+    /// A resposta certa é dizer que o documento não traz a informação.
+    #[serde(default)]
+    pub deve_recusar: bool,
+}
+
+/// Um fato esperado na resposta: um texto ou uma lista de formas equivalentes
+/// (ex.: `["R$ 15.000,00", "quinze mil reais"]`).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FatoEsperado {
+    Texto(String),
+    Alternativas(Vec<String>),
+}
+
+impl FatoEsperado {
+    fn formas(&self) -> Vec<&str> {
+        match self {
+            FatoEsperado::Texto(texto) => vec![texto.as_str()],
+            FatoEsperado::Alternativas(formas) => formas.iter().map(|f| f.as_str()).collect(),
+        }
+    }
+
+    fn descricao(&self) -> String {
+        self.formas().join(" | ")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Citacao {
+    pub trecho: String,
+    /// O trecho aparece literalmente no documento.
+    pub valida: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultadoCaso {
+    pub id: String,
+    pub pergunta: String,
+    pub resposta: Option<String>,
+    pub erro: Option<String>,
+    pub fatos_encontrados: Vec<String>,
+    pub fatos_ausentes: Vec<String>,
+    /// Fração dos fatos esperados presentes na resposta (`None` sem fatos esperados).
+    pub cobertura_fatos: Option<f64>,
+    pub citacoes: Vec<Citacao>,
+    /// Fração das citações encontradas no documento (`None` sem citações).
+    pub validade_citacoes: Option<f64>,
+    pub recusou: bool,
+    pub deve_recusar: bool,
+    pub recusa_correta: bool,
+    pub latencia_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metricas {
+    pub casos: usize,
+    pub erros: usize,
+    pub cobertura_fatos: Option<f64>,
+    pub validade_citacoes: Option<f64>,
+    pub acerto_recusa: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelatorioAvaliacao {
+    pub conjunto: String,
+    pub provedor: String,
+    pub modelo: String,
+    pub template: String,
+    pub template_versao: i32,
+    pub data: String,
+    pub metricas: Metricas,
+    pub casos: Vec<ResultadoCaso>,
+}
+
+pub fn carregar_conjunto(arquivo: &Path) -> Result<ConjuntoAvaliacao, String> {
+    let conteudo = std::fs::read_to_string(arquivo).map_err(|e| format!("Falha ao ler o conjunto {}: {}", arquivo.display(), e))?;
+    serde_json::from_str(&conteudo).map_err(|e| format!("Conjunto de avaliação inválido ({}): {}", arquivo.display(), e))
+}
+
+pub fn carregar_relatorio(arquivo: &Path) -> Result<RelatorioAvaliacao, String> {
+    let conteudo = std::fs::read_to_string(arquivo).map_err(|e| format!("Falha ao ler o relatório {}: {}", arquivo.display(), e))?;
+    serde_json::from_str(&conteudo).map_err(|e| format!("Relatório de avaliação inválido ({}): {}", arquivo.display(), e))
+}
+
+/// Texto do documento do caso: `texto`, ou o arquivo em `documento` (PDF ou texto).
+fn texto_do_documento(caso: &CasoAvaliacao, pasta: &Path) -> Result<String, String> {
+    if let Some(texto) = &caso.texto {
+        return Ok(texto.clone());
+    }
+    let documento = caso.documento.as_ref().ok_or_else(|| format!("Caso '{}' sem 'texto' nem 'documento'.", caso.id))?;
+    let caminho = pasta.join(documento);
+    let bytes = std::fs::read(&caminho).map_err(|e| format!("Falha ao ler {}: {}", caminho.display(), e))?;
+    if documento.to_lowercase().ends_with(".pdf") {
+        extrair_texto(&bytes)
+    } else {
+        String::from_utf8(bytes).map_err(|e| format!("{} não é texto UTF-8: {}", caminho.display(), e))
+    }
+}
+
+/// Trechos entre aspas na resposta.
+pub fn citacoes_da_resposta(resposta: &str) -> Vec<String> {
+    let mut citacoes = Vec::new();
+    for (abre, fecha) in [('"', '"'), ('“', '”'), ('«', '»')] {
+        let mut resto = resposta;
+        while let Some(inicio) = resto.find(abre) {
+            let depois = &resto[inicio + abre.len_utf8()..];
+            let Some(fim) = depois.find(fecha) else { break };
+            let trecho = depois[..fim].trim();
+            if trecho.chars().count() >= MIN_CARACTERES_CITACAO {
+                citacoes.push(trecho.to_string());
+            }
+            resto = &depois[fim + fecha.len_utf8()..];
+        }
+    }
+    citacoes
+}
+
+/// A citação vale se cada parte dela (separadas por reticências) estiver no documento.
+fn citacao_valida(citacao: &str, documento_normalizado: &str) -> bool {
+    citacao
+        .split(['…'])
+        .flat_map(|parte| parte.split("..."))
+        .map(|parte| normalizar_texto(parte.trim_matches(|c: char| c.is_whitespace() || c == '.' || c == ',' || c == ';')))
+        .filter(|parte| !parte.is_empty())
+        .all(|parte| documento_normalizado.contains(&parte))
+}
+
+pub fn resposta_e_recusa(resposta: &str) -> bool {
+    let resposta = normalizar_texto(resposta);
+    EXPRESSOES_RECUSA.iter().any(|expressao| resposta.contains(expressao))
+}
+
+pub fn avaliar_resposta(caso: &CasoAvaliacao, documento: &str, resposta: &str, latencia_ms: u64) -> ResultadoCaso {
+    let resposta_normalizada = normalizar_texto(resposta);
+    let (encontrados, ausentes): (Vec<&FatoEsperado>, Vec<&FatoEsperado>) = caso
+        .fatos_esperados
+        .iter()
+        .partition(|fato| fato.formas().iter().any(|forma| resposta_normalizada.contains(&normalizar_texto(forma))));
+    let cobertura_fatos = if caso.fatos_esperados.is_empty() {
+        None
+    } else {
+        Some(encontrados.len() as f64 / caso.fatos_esperados.len() as f64)
+    };
+
+    let documento_normalizado = normalizar_texto(documento);
+    let citacoes: Vec<Citacao> = citacoes_da_resposta(resposta)
+        .into_iter()
+        .map(|trecho| Citacao { valida: citacao_valida(&trecho, &documento_normalizado), trecho })
+        .collect();
+    let validade_citacoes = if citacoes.is_empty() {
+        None
+    } else {
+        Some(citacoes.iter().filter(|c| c.valida).count() as f64 / citacoes.len() as f64)
+    };
+
+    let recusou = resposta_e_recusa(resposta);
+    ResultadoCaso {
+        id: caso.id.clone(),
+        pergunta: caso.pergunta.clone(),
+        resposta: Some(resposta.to_string()),
+        erro: None,
+        fatos_encontrados: encontrados.iter().map(|f| f.descricao()).collect(),
+        fatos_ausentes: ausentes.iter().map(|f| f.descricao()).collect(),
+        cobertura_fatos,
+        citacoes,
+        validade_citacoes,
+        recusou,
+        deve_recusar: caso.deve_recusar,
+        recusa_correta: recusou == caso.deve_recusar,
+        latencia_ms,
+    }
+}
+
+fn media(valores: impl Iterator<Item = f64>) -> Option<f64> {
+    let valores: Vec<f64> = valores.collect();
+    if valores.is_empty() { None } else { Some(valores.iter().sum::<f64>() / valores.len() as f64) }
+}
+
+/// Médias das notas. Casos com erro contam só em `erros`.
+pub fn calcular_metricas(casos: &[ResultadoCaso]) -> Metricas {
+    let respondidos: Vec<&ResultadoCaso> = casos.iter().filter(|c| c.erro.is_none()).collect();
+    Metricas {
+        casos: casos.len(),
+        erros: casos.len() - respondidos.len(),
+        cobertura_fatos: media(respondidos.iter().filter_map(|c| c.cobertura_fatos)),
+        validade_citacoes: media(respondidos.iter().filter_map(|c| c.validade_citacoes)),
+        acerto_recusa: media(respondidos.iter().map(|c| if c.recusa_correta { 1.0 } else { 0.0 })),
+    }
+}
+
+/// Responde cada caso do conjunto com `llm` e avalia as respostas.
+/// `pasta` é a pasta do arquivo do conjunto, para os caminhos dos documentos.
+pub async fn executar_avaliacao(
+    llm: &dyn LlmProvider,
+    template: &PromptTemplate,
+    modelo: &str,
+    conjunto: &ConjuntoAvaliacao,
+    pasta: &Path,
+) -> RelatorioAvaliacao {
+    let mut casos = Vec::new();
+    for caso in &conjunto.casos {
+        let inicio = Instant::now();
+        let resultado = async {
+            let documento = texto_do_documento(caso, pasta)?;
+            let messages = mensagens_qa(template, documento.clone(), caso.pergunta.clone(), caso.dados_caso.clone())?;
+            let resposta = responder_pergunta(llm, template, modelo.to_string(), messages).await?;
+            Ok::<_, String>((documento, resposta.text))
+        }
+        .await;
+        let latencia_ms = inicio.elapsed().as_millis() as u64;
+
+        casos.push(match resultado {
+            Ok((documento, resposta)) => avaliar_resposta(caso, &documento, &resposta, latencia_ms),
+            Err(e) => {
+                eprintln!("Evaluation case '{}' failed: {}", caso.id, e);
+                ResultadoCaso {
+                    id: caso.id.clone(),
+                    pergunta: caso.pergunta.clone(),
+                    resposta: None,
+                    erro: Some(e),
+                    fatos_encontrados: Vec::new(),
+                    fatos_ausentes: caso.fatos_esperados.iter().map(|f| f.descricao()).collect(),
+                    cobertura_fatos: None,
+                    citacoes: Vec::new(),
+                    validade_citacoes: None,
+                    recusou: false,
+                    deve_recusar: caso.deve_recusar,
+                    recusa_correta: false,
+                    latencia_ms,
+                }
+            }
+        });
+    }
+
+    RelatorioAvaliacao {
+        conjunto: conjunto.nome.clone(),
+        provedor: llm.name().to_string(),
+        modelo: modelo.to_string(),
+        template: template.nome.clone(),
+        template_versao: template.versao,
+        data: Local::now().to_rfc3339(),
+        metricas: calcular_metricas(&casos),
+        casos,
+    }
+}
+
+fn percentual(valor: Option<f64>) -> String {
+    valor.map(|v| format!("{:.1}%", v * 100.0)).unwrap_or_else(|| "-".to_string())
+}
+
+fn diferenca(anterior: Option<f64>, atual: Option<f64>) -> String {
+    match (anterior, atual) {
+        (Some(a), Some(b)) => format!("{:+.1} p.p.", (b - a) * 100.0),
+        _ => "-".to_string(),
+    }
+}
+
+/// Resumo de um relatório em Markdown.
+pub fn resumo_relatorio(relatorio: &RelatorioAvaliacao) -> String {
+    let m = &relatorio.metricas;
+    let mut saida = format!(
+        "## {} ({} / {}, template {} v{})\n\n| Métrica | Valor |\n|---|---|\n| Casos | {} |\n| Erros | {} |\n| Cobertura de fatos | {} |\n| Validade das citações | {} |\n| Acerto da recusa | {} |\n",
+        relatorio.conjunto,
+        relatorio.provedor,
+        relatorio.modelo,
+        relatorio.template,
+        relatorio.template_versao,
+        m.casos,
+        m.erros,
+        percentual(m.cobertura_fatos),
+        percentual(m.validade_citacoes),
+        percentual(m.acerto_recusa),
+    );
+    let falhas: Vec<&ResultadoCaso> = relatorio
+        .casos
+        .iter()
+        .filter(|c| c.erro.is_some() || !c.fatos_ausentes.is_empty() || !c.recusa_correta || c.citacoes.iter().any(|ct| !ct.valida))
+        .collect();
+    if !falhas.is_empty() {
+        saida.push_str("\n### Casos com problemas\n\n");
+        for caso in falhas {
+            let mut problemas = Vec::new();
+            if let Some(erro) = &caso.erro {
+                problemas.push(format!("erro: {}", erro));
+            }
+            if !caso.fatos_ausentes.is_empty() {
+                problemas.push(format!("fatos ausentes: {}", caso.fatos_ausentes.join("; ")));
+            }
+            if caso.erro.is_none() && !caso.recusa_correta {
+                problemas.push(if caso.deve_recusar { "deveria recusar".to_string() } else { "recusou sem motivo".to_string() });
+            }
+            let invalidas = caso.citacoes.iter().filter(|c| !c.valida).count();
+            if invalidas > 0 {
+                problemas.push(format!("{} citação(ões) fora do documento", invalidas));
+            }
+            saida.push_str(&format!("- `{}`: {}\n", caso.id, problemas.join(", ")));
+        }
+    }
+    saida
+}
+
+/// Comparação entre duas execuções em Markdown: métricas e casos que pioraram ou melhoraram.
+pub fn comparar_relatorios(anterior: &RelatorioAvaliacao, atual: &RelatorioAvaliacao) -> String {
+    let (a, b) = (&anterior.metricas, &atual.metricas);
+    let mut saida = format!(
+        "## Comparação: {} ({} v{}) → {} ({} v{})\n\n| Métrica | Anterior | Atual | Diferença |\n|---|---|---|---|\n",
+        anterior.modelo, anterior.template, anterior.template_versao, atual.modelo, atual.template, atual.template_versao,
+    );
+    saida.push_str(&format!("| Erros | {} | {} | {:+} |\n", a.erros, b.erros, b.erros as i64 - a.erros as i64));
+    for (nome, va, vb) in [
+        ("Cobertura de fatos", a.cobertura_fatos, b.cobertura_fatos),
+        ("Validade das citações", a.validade_citacoes, b.validade_citacoes),
+        ("Acerto da recusa", a.acerto_recusa, b.acerto_recusa),
+    ] {
+        saida.push_str(&format!("| {} | {} | {} | {} |\n", nome, percentual(va), percentual(vb), diferenca(va, vb)));
+    }
+
+    let mut piores = Vec::new();
+    let mut melhores = Vec::new();
+    for caso in &atual.casos {
+        let Some(antes) = anterior.casos.iter().find(|c| c.id == caso.id) else { continue };
+        let mut mudancas: Vec<(bool, String)> = Vec::new();
+        if let (Some(x), Some(y)) = (antes.cobertura_fatos, caso.cobertura_fatos) && (y - x).abs() > f64::EPSILON {
+            mudancas.push((y > x, format!("cobertura {} → {}", percentual(Some(x)), percentual(Some(y)))));
+        }
+        if antes.recusa_correta != caso.recusa_correta {
+            mudancas.push((caso.recusa_correta, format!("recusa {}", if caso.recusa_correta { "corrigida" } else { "incorreta" })));
+        }
+        if let (Some(x), Some(y)) = (antes.validade_citacoes, caso.validade_citacoes) && (y - x).abs() > f64::EPSILON {
+            mudancas.push((y > x, format!("citações {} → {}", percentual(Some(x)), percentual(Some(y)))));
+        }
+        if antes.erro.is_some() != caso.erro.is_some() {
+            mudancas.push((caso.erro.is_none(), if caso.erro.is_some() { "passou a falhar".to_string() } else { "deixou de falhar".to_string() }));
+        }
+        for (melhorou, descricao) in mudancas {
+            let linha = format!("- `{}`: {}\n", caso.id, descricao);
+            if melhorou { melhores.push(linha) } else { piores.push(linha) }
+        }
+    }
+    if !piores.is_empty() {
+        saida.push_str("\n### Pioraram\n\n");
+        saida.push_str(&piores.concat());
+    }
+    if !melhores.is_empty() {
+        saida.push_str("\n### Melhoraram\n\n");
+        saida.push_str(&melhores.concat());
+    }
+    let novos: Vec<&str> = atual.casos.iter().filter(|c| !anterior.casos.iter().any(|a| a.id == c.id)).map(|c| c.id.as_str()).collect();
+    if !novos.is_empty() {
+        saida.push_str(&format!("\nCasos sem execução anterior: {}\n", novos.join(", ")));
+    }
+    saida
+}
@@ -0,0 +1,388 @@
+// src/ia/classificacao.rs
+// Sugestão de Categoria_caso para casos sem categoria: pelo LLM, com regras de
+// palavras-chave quando o modelo falha. As sugestões ficam pendentes até a
+// confirmação de uma pessoa.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Client as DbClient;
+
+use super::{extrair_texto, normalizar_texto, truncar_texto};
+use super::auditoria::{auditar, ContextoAuditoria};
+use super::prompts::{carregar_template, PromptTemplate, TEMPLATE_CLASSIFICACAO_CASO};
+use super::provider::{GenerateRequest, LlmProvider};
+use crate::cifra::arquivo_da_linha;
+
+/// Confiança máxima atribuída a uma sugestão por palavras-chave.
+const CONFIANCA_MAXIMA_REGRA: f32 = 0.6;
+/// Documentos iniciais do caso (os mais antigos) usados na classificação.
+const DOCUMENTOS_INICIAIS: i64 = 2;
+
+/// Palavras-chave por área, comparadas com a parte da descrição da categoria
+/// antes de " - " (ex.: "Trabalhista - Rescisão Indireta").
+const PALAVRAS_POR_AREA: [(&str, &[&str]); 10] = [
+    ("civel", &["cobranca", "indenizacao", "danos morais", "contrato", "inadimplemento", "responsabilidade civil"]),
+    ("criminal", &["crime", "denuncia", "inquerito", "furto", "roubo", "habeas corpus", "codigo penal", "reu preso"]),
+    ("trabalhista", &["reclamacao trabalhista", "clt", "rescisao", "horas extras", "fgts", "verbas rescisorias", "empregador", "ctps"]),
+    ("familia", &["divorcio", "pensao alimenticia", "alimentos", "guarda", "uniao estavel", "partilha", "conjuge"]),
+    ("tributario", &["tributo", "imposto", "icms", "iptu", "irpf", "execucao fiscal", "restituicao", "receita federal"]),
+    ("ambiental", &["licenciamento", "ambiental", "ibama", "desmatamento", "area de preservacao"]),
+    ("consumidor", &["consumidor", "cdc", "fornecedor", "vicio do produto", "defeito", "negativacao", "procon"]),
+    ("administrativo", &["concurso publico", "servidor publico", "licitacao", "ato administrativo", "administracao publica"]),
+    ("previdenciario", &["inss", "aposentadoria", "auxilio-doenca", "beneficio previdenciario", "bpc"]),
+    ("empresarial", &["recuperacao judicial", "falencia", "contrato social", "socio", "sociedade empresaria"]),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OrigemSugestao {
+    Regra,
+    Llm,
+}
+
+impl OrigemSugestao {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrigemSugestao::Regra => "regra",
+            OrigemSugestao::Llm => "llm",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CategoriaAtiva {
+    pub id_categoria_caso: i32,
+    pub descricao: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SugestaoCategoria {
+    pub id_categoria_caso: i32,
+    pub categoria: String,
+    pub confianca: f32,
+    pub origem: OrigemSugestao,
+    pub justificativa: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RespostaClassificacao {
+    id_categoria_caso: i32,
+    confianca: f32,
+    justificativa: String,
+}
+
+pub async fn categorias_ativas(client_db: &DbClient) -> Result<Vec<CategoriaAtiva>, tokio_postgres::Error> {
+    let rows = client_db
+        .query("SELECT id_categoria_caso, descricao FROM Categoria_caso WHERE ativo = TRUE ORDER BY id_categoria_caso;", &[])
+        .await?;
+    Ok(rows
+        .iter()
+        .map(|r| CategoriaAtiva { id_categoria_caso: r.get("id_categoria_caso"), descricao: r.get("descricao") })
+        .collect())
+}
+
+/// Descrição do caso e texto dos documentos iniciais (o resumo da análise,
+/// quando existe, ou o texto extraído do PDF).
+pub async fn textos_do_caso(client_db: &DbClient, id_caso: i32) -> Result<Option<(String, String)>, tokio_postgres::Error> {
+    let Some(caso) = client_db.query_opt("SELECT descricao FROM Caso WHERE id_caso = $1;", &[&id_caso]).await? else {
+        return Ok(None);
+    };
+    let descricao: Option<String> = caso.get("descricao");
+
+    let rows = client_db
+        .query(
+            "SELECT d.id_documento, d.nome_arquivo, d.arquivo, d.chave_cifrada, d.id_chave_mestra,
+                    (SELECT a.resumo FROM Documento_Analise a
+                     WHERE a.id_documento = d.id_documento AND a.status = 'concluida'
+                     ORDER BY a.data_analise DESC LIMIT 1) AS resumo
+             FROM Documento d
+             WHERE d.id_caso = $1 AND NOT d.gerado_por_ia
+             ORDER BY d.data_envio ASC NULLS LAST, d.id_documento ASC
+             LIMIT $2;",
+            &[&id_caso, &DOCUMENTOS_INICIAIS],
+        )
+        .await?;
+
+    let mut textos: Vec<String> = Vec::new();
+    for row in rows {
+        let nome_arquivo: Option<String> = row.get("nome_arquivo");
+        let resumo: Option<String> = row.get("resumo");
+        let texto = match resumo {
+            Some(resumo) => Some(resumo),
+            None => arquivo_da_linha(&row).ok().flatten().and_then(|arquivo| extrair_texto(&arquivo).ok()),
+        };
+        if let Some(texto) = texto {
+            textos.push(format!("### {}\n{}", nome_arquivo.unwrap_or_default(), texto));
+        }
+    }
+
+    Ok(Some((descricao.unwrap_or_default(), truncar_texto(&textos.join("\n\n")).to_string())))
+}
+
+/// Classificação por palavras-chave da área e da própria descrição da categoria.
+pub fn classificar_por_regras(texto: &str, categorias: &[CategoriaAtiva]) -> Option<SugestaoCategoria> {
+    let texto = normalizar_texto(texto);
+    let mut pontuacoes: Vec<(&CategoriaAtiva, usize, Vec<String>)> = categorias
+        .iter()
+        .map(|categoria| {
+            let descricao = normalizar_texto(&categoria.descricao);
+            let area = descricao.split(" - ").next().unwrap_or("").trim().to_string();
+            let mut palavras: Vec<String> = PALAVRAS_POR_AREA
+                .iter()
+                .filter(|(chave, _)| area.contains(chave))
+                .flat_map(|(_, palavras)| palavras.iter().map(|p| p.to_string()))
+                .collect();
+            palavras.extend(descricao.split(|c: char| !c.is_alphanumeric()).filter(|p| p.len() >= 5).map(|p| p.to_string()));
+            palavras.sort();
+            palavras.dedup();
+            let encontradas: Vec<String> = palavras.into_iter().filter(|p| texto.contains(p.as_str())).collect();
+            (categoria, encontradas.len(), encontradas)
+        })
+        .filter(|(_, pontos, _)| *pontos > 0)
+        .collect();
+    pontuacoes.sort_by_key(|p| std::cmp::Reverse(p.1));
+
+    let (categoria, pontos, encontradas) = pontuacoes.first()?;
+    let segundo = pontuacoes.get(1).map(|p| p.1).unwrap_or(0);
+    let confianca = (*pontos as f32 / (*pontos + segundo + 1) as f32).min(CONFIANCA_MAXIMA_REGRA);
+    Some(SugestaoCategoria {
+        id_categoria_caso: categoria.id_categoria_caso,
+        categoria: categoria.descricao.clone(),
+        confianca,
+        origem: OrigemSugestao::Regra,
+        justificativa: format!("Palavras-chave encontradas: {}", encontradas.join(", ")),
+    })
+}
+
+fn schema_classificacao(categorias: &[CategoriaAtiva]) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "id_categoria_caso": {"type": "integer", "enum": categorias.iter().map(|c| c.id_categoria_caso).collect::<Vec<_>>()},
+            "confianca": {"type": "number", "minimum": 0, "maximum": 1},
+            "justificativa": {"type": "string"}
+        },
+        "required": ["id_categoria_caso", "confianca", "justificativa"],
+        "additionalProperties": false
+    })
+}
+
+/// Pede a categoria ao modelo. Ids fora da lista de categorias ativas são recusados.
+pub async fn classificar_por_llm(
+    llm: &dyn LlmProvider,
+    template: &PromptTemplate,
+    modelo: &str,
+    descricao: &str,
+    documentos: &str,
+    categorias: &[CategoriaAtiva],
+) -> Result<SugestaoCategoria, String> {
+    let lista = categorias.iter().map(|c| format!("{}: {}", c.id_categoria_caso, c.descricao)).collect::<Vec<_>>().join("\n");
+    let valores = HashMap::from([
+        ("categorias", lista),
+        ("descricao", if descricao.trim().is_empty() { "Não informada.".to_string() } else { descricao.to_string() }),
+        ("documento", if documentos.trim().is_empty() { "Nenhum documento.".to_string() } else { documentos.to_string() }),
+    ]);
+    let (system, prompt) = template.render(&valores)?;
+
+    let resposta = llm.generate(&GenerateRequest {
+        model: modelo.to_string(),
+        prompt,
+        system,
+        format: Some(schema_classificacao(categorias)),
+        options: template.opcoes(),
+    }).await?;
+    let dados: RespostaClassificacao = serde_json::from_str(&resposta.text)
+        .map_err(|e| format!("A resposta de classificação não corresponde ao schema: {}", e))?;
+
+    let categoria = categorias
+        .iter()
+        .find(|c| c.id_categoria_caso == dados.id_categoria_caso)
+        .ok_or_else(|| format!("O modelo sugeriu uma categoria inexistente ou inativa: {}", dados.id_categoria_caso))?;
+    Ok(SugestaoCategoria {
+        id_categoria_caso: categoria.id_categoria_caso,
+        categoria: categoria.descricao.clone(),
+        confianca: dados.confianca.clamp(0.0, 1.0),
+        origem: OrigemSugestao::Llm,
+        justificativa: dados.justificativa,
+    })
+}
+
+/// Classifica o caso e grava a sugestão pendente, substituindo outra ainda não
+/// decidida. Retorna `None` quando nem o modelo nem as regras chegam a uma categoria.
+pub async fn sugerir_categoria(llm: &dyn LlmProvider, client_db: &DbClient, id_caso: i32, modelo: Option<&str>) -> Result<Option<SugestaoCategoria>, String> {
+    let categorias = categorias_ativas(client_db).await.map_err(|e| format!("Failed to fetch case categories: {}", e))?;
+    if categorias.is_empty() {
+        return Ok(None);
+    }
+    let (descricao, documentos) = textos_do_caso(client_db, id_caso)
+        .await
+        .map_err(|e| format!("Failed to fetch case texts: {}", e))?
+        .ok_or_else(|| format!("Caso {} não encontrado.", id_caso))?;
+    if descricao.trim().is_empty() && documentos.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let template = carregar_template(client_db, TEMPLATE_CLASSIFICACAO_CASO, None).await?;
+    let modelo = template.modelo(modelo);
+    let sugestao = match classificar_por_llm(llm, &template, &modelo, &descricao, &documentos, &categorias).await {
+        Ok(sugestao) => Some(sugestao),
+        Err(e) => {
+            // Sem o modelo, as palavras-chave ainda dão uma sugestão
+            eprintln!("LLM case classification failed for case {}: {}", id_caso, e);
+            classificar_por_regras(&format!("{}\n{}", descricao, documentos), &categorias)
+        }
+    };
+    let Some(sugestao) = sugestao else {
+        return Ok(None);
+    };
+
+    client_db
+        .execute("DELETE FROM Categoria_Sugerida WHERE id_caso = $1 AND status = 'pendente';", &[&id_caso])
+        .await
+        .map_err(|e| format!("Failed to clear pending category suggestions: {}", e))?;
+
+    let (modelo_usado, id_versao_prompt) = match sugestao.origem {
+        OrigemSugestao::Llm => (Some(modelo.as_str()), template.id_versao),
+        OrigemSugestao::Regra => (None, None),
+    };
+    client_db
+        .execute(
+            "INSERT INTO Categoria_Sugerida (id_caso, id_categoria_caso, confianca, origem, justificativa, modelo, id_versao_prompt, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, 'pendente');",
+            &[&id_caso, &sugestao.id_categoria_caso, &sugestao.confianca, &sugestao.origem.as_str(), &sugestao.justificativa, &modelo_usado, &id_versao_prompt],
+        )
+        .await
+        .map_err(|e| format!("Failed to store category suggestion: {}", e))?;
+
+    Ok(Some(sugestao))
+}
+
+/// Agenda a sugestão de categoria de um caso recém-criado sem categoria.
+pub fn agendar_sugestao(llm: Arc<dyn LlmProvider>, id_caso: i32) {
+    tuono_lib::tokio::spawn(async move {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for case classification: {}", e);
+                return;
+            }
+        };
+
+        match sugerir_categoria(llm.as_ref(), &client_db, id_caso, None).await {
+            Ok(Some(sugestao)) => println!("Category suggestion for case {}: {} ({:.2})", id_caso, sugestao.categoria, sugestao.confianca),
+            Ok(None) => {}
+            Err(e) => eprintln!("Case classification failed for case {}: {}", id_caso, e),
+        }
+    });
+}
+
+/// Andamento do preenchimento em lote (um por processo).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Preenchimento {
+    pub em_execucao: bool,
+    pub total: usize,
+    pub processados: usize,
+    pub sugestoes: usize,
+    pub erros: usize,
+    pub inicio: Option<DateTime<Local>>,
+    pub fim: Option<DateTime<Local>>,
+}
+
+static PREENCHIMENTO: OnceLock<Mutex<Preenchimento>> = OnceLock::new();
+
+fn preenchimento() -> &'static Mutex<Preenchimento> {
+    PREENCHIMENTO.get_or_init(|| Mutex::new(Preenchimento::default()))
+}
+
+pub fn situacao_preenchimento() -> Preenchimento {
+    preenchimento().lock().map(|p| p.clone()).unwrap_or_default()
+}
+
+fn atualizar(f: impl FnOnce(&mut Preenchimento)) {
+    if let Ok(mut p) = preenchimento().lock() {
+        f(&mut p);
+    }
+}
+
+/// Inicia, em segundo plano, sugestões para todos os casos sem categoria e sem
+/// sugestão pendente; cada chamada é auditada com `contexto` e o caso. Retorna
+/// `false` se já houver um preenchimento em execução.
+pub fn iniciar_preenchimento(llm: Arc<dyn LlmProvider>, contexto: ContextoAuditoria, modelo: Option<String>) -> bool {
+    {
+        let Ok(mut p) = preenchimento().lock() else { return false };
+        if p.em_execucao {
+            return false;
+        }
+        *p = Preenchimento { em_execucao: true, inicio: Some(Local::now()), ..Default::default() };
+    }
+
+    tuono_lib::tokio::spawn(async move {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for category backfill: {}", e);
+                atualizar(|p| {
+                    p.em_execucao = false;
+                    p.fim = Some(Local::now());
+                });
+                return;
+            }
+        };
+
+        let ids: Vec<i32> = match client_db
+            .query(
+                "SELECT c.id_caso FROM Caso c
+                 WHERE c.id_categoria_caso IS NULL
+                   AND NOT EXISTS (SELECT 1 FROM Categoria_Sugerida s WHERE s.id_caso = c.id_caso AND s.status = 'pendente')
+                 ORDER BY c.id_caso;",
+                &[],
+            )
+            .await
+        {
+            Ok(rows) => rows.iter().map(|r| r.get("id_caso")).collect(),
+            Err(e) => {
+                eprintln!("Failed to list uncategorized cases: {}", e);
+                Vec::new()
+            }
+        };
+        atualizar(|p| p.total = ids.len());
+
+        for id_caso in ids {
+            let auditado = auditar(llm.clone(), contexto.clone().do_caso(id_caso));
+            let resultado = sugerir_categoria(auditado.as_ref(), &client_db, id_caso, modelo.as_deref()).await;
+            atualizar(|p| {
+                p.processados += 1;
+                match &resultado {
+                    Ok(Some(_)) => p.sugestoes += 1,
+                    Ok(None) => {}
+                    Err(_) => p.erros += 1,
+                }
+            });
+            if let Err(e) = resultado {
+                eprintln!("Category backfill failed for case {}: {}", id_caso, e);
+            }
+        }
+
+        atualizar(|p| {
+            p.em_execucao = false;
+            p.fim = Some(Local::now());
+        });
+        let p = situacao_preenchimento();
+        println!("Category backfill finished: {} suggestion(s) for {} case(s), {} error(s)", p.sugestoes, p.total, p.erros);
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ia::openai::conferir_schema_estrito;
+
+    #[test]
+    fn schema_de_classificacao_vale_no_modo_estrito() {
+        let categorias = vec![CategoriaAtiva { id_categoria_caso: 1, descricao: "Trabalhista".to_string() }];
+        assert_eq!(conferir_schema_estrito(&schema_classificacao(&categorias), "classificacao"), Ok(()));
+    }
+}
@@ -0,0 +1,301 @@
+// src/ia/fila.rs
+// Fila em processo para as chamadas ao LLM: limite de execuções simultâneas por
+// modelo, tamanho máximo de espera, tempo limite e cancelamento.
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::future::{select, Either};
+use serde::Serialize;
+use tuono_lib::tokio::sync::{Notify, Semaphore};
+
+use super::provider::{ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+/// Tempo máximo de uma chamada (espera na fila + geração), em `LLM_TIMEOUT_SEGUNDOS`.
+pub fn tempo_limite() -> Duration {
+    crate::config::atual().llm.fila.tempo_limite
+}
+
+/// Execuções simultâneas permitidas para o modelo: `LLM_CONCORRENCIA_MODELOS`
+/// (`modelo=n,outro=m`) ou, na falta dele, `LLM_CONCORRENCIA`.
+fn concorrencia_do_modelo(modelo: &str) -> usize {
+    let fila = &crate::config::atual().llm.fila;
+    fila.concorrencia_modelos.iter().find(|(nome, _)| nome == modelo).map_or(fila.concorrencia, |(_, n)| *n)
+}
+
+struct EstadoModelo {
+    limite: usize,
+    semaforo: Arc<Semaphore>,
+    aguardando: VecDeque<u64>,
+    em_execucao: usize,
+}
+
+struct Requisicao {
+    modelo: String,
+    ticket: u64,
+    /// Só quem enfileirou consulta a posição e cancela.
+    id_usuario: i32,
+    cancelar: Arc<Notify>,
+}
+
+/// Identifica uma chamada na fila: o `id_requisicao` gerado pelo frontend e o
+/// usuário que a fez.
+#[derive(Debug, Clone)]
+pub struct Identificacao {
+    pub id_usuario: i32,
+    pub id_requisicao: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SituacaoModelo {
+    pub modelo: String,
+    pub limite: usize,
+    pub em_execucao: usize,
+    pub aguardando: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SituacaoRequisicao {
+    pub id_requisicao: String,
+    pub modelo: String,
+    pub status: &'static str, // "aguardando" ou "executando"
+    /// Posição na fila (1 = próxima a executar); `None` quando já está executando.
+    pub posicao: Option<usize>,
+}
+
+pub struct Fila {
+    max_fila: usize,
+    proximo_ticket: AtomicU64,
+    modelos: Mutex<HashMap<String, EstadoModelo>>,
+    requisicoes: Mutex<HashMap<String, Requisicao>>,
+}
+
+static FILA: OnceLock<Fila> = OnceLock::new();
+
+/// Fila única do processo.
+pub fn fila() -> &'static Fila {
+    FILA.get_or_init(|| Fila {
+        max_fila: crate::config::atual().llm.fila.fila_maxima,
+        proximo_ticket: AtomicU64::new(1),
+        modelos: Mutex::new(HashMap::new()),
+        requisicoes: Mutex::new(HashMap::new()),
+    })
+}
+
+/// Desfaz o registro do ticket quando a chamada termina ou é abandonada
+/// (por exemplo, quando o cliente HTTP desconecta e o handler é descartado).
+struct Ticket<'a> {
+    fila: &'a Fila,
+    modelo: String,
+    ticket: u64,
+    id_requisicao: Option<String>,
+    executando: bool,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut modelos) = self.fila.modelos.lock()
+            && let Some(estado) = modelos.get_mut(&self.modelo)
+        {
+            estado.aguardando.retain(|t| *t != self.ticket);
+            if self.executando {
+                estado.em_execucao = estado.em_execucao.saturating_sub(1);
+            }
+        }
+        if let Some(id) = &self.id_requisicao
+            && let Ok(mut requisicoes) = self.fila.requisicoes.lock()
+            && requisicoes.get(id).map(|r| r.ticket) == Some(self.ticket)
+        {
+            requisicoes.remove(id);
+        }
+    }
+}
+
+impl Fila {
+    /// Executa `chamada` quando houver vaga para o modelo. Falha se a fila
+    /// estiver cheia, se o tempo limite estourar ou se a requisição for cancelada.
+    pub async fn executar<T, F>(&self, modelo: &str, identificacao: Option<&Identificacao>, chamada: F) -> Result<T, LlmError>
+    where
+        T: Send,
+        F: Future<Output = Result<T, LlmError>> + Send,
+    {
+        let ticket = self.proximo_ticket.fetch_add(1, Ordering::Relaxed);
+        let cancelar = Arc::new(Notify::new());
+
+        let semaforo = {
+            let mut modelos = self.modelos.lock().map_err(|_| LlmError::Ocupado("Fila de IA indisponível.".to_string()))?;
+            let estado = modelos.entry(modelo.to_string()).or_insert_with(|| {
+                let limite = concorrencia_do_modelo(modelo);
+                EstadoModelo { limite, semaforo: Arc::new(Semaphore::new(limite)), aguardando: VecDeque::new(), em_execucao: 0 }
+            });
+            if estado.aguardando.len() >= self.max_fila {
+                return Err(LlmError::Ocupado(format!(
+                    "Há {} requisições aguardando o modelo '{}'. Tente novamente em instantes.",
+                    estado.aguardando.len(),
+                    modelo
+                )));
+            }
+            estado.aguardando.push_back(ticket);
+            estado.semaforo.clone()
+        };
+
+        let mut guarda = Ticket {
+            fila: self,
+            modelo: modelo.to_string(),
+            ticket,
+            id_requisicao: identificacao.map(|i| i.id_requisicao.clone()),
+            executando: false,
+        };
+        if let Some(identificacao) = identificacao
+            && let Ok(mut requisicoes) = self.requisicoes.lock()
+        {
+            requisicoes.insert(
+                identificacao.id_requisicao.clone(),
+                Requisicao { modelo: modelo.to_string(), ticket, id_usuario: identificacao.id_usuario, cancelar: cancelar.clone() },
+            );
+        }
+
+        let limite = tempo_limite();
+        let execucao = async {
+            let _vaga = semaforo
+                .acquire_owned()
+                .await
+                .map_err(|_| LlmError::Ocupado("Fila de IA encerrada.".to_string()))?;
+            if let Ok(mut modelos) = self.modelos.lock()
+                && let Some(estado) = modelos.get_mut(modelo)
+            {
+                estado.aguardando.retain(|t| *t != ticket);
+                estado.em_execucao += 1;
+            }
+            guarda.executando = true;
+            chamada.await
+        };
+
+        let cancelamento = cancelar.notified();
+        let corrida = select(Box::pin(tuono_lib::tokio::time::timeout(limite, execucao)), Box::pin(cancelamento)).await;
+        match corrida {
+            Either::Left((Ok(resultado), _)) => resultado,
+            Either::Left((Err(_), _)) => Err(LlmError::Tempo(format!("A chamada ao modelo '{}' excedeu {} segundos.", modelo, limite.as_secs()))),
+            Either::Right(_) => Err(LlmError::Cancelado),
+        }
+    }
+
+    /// Cancela uma requisição aguardando ou em execução. Retorna `false` se ela
+    /// não existir ou tiver sido enfileirada por outro usuário.
+    pub fn cancelar(&self, id_usuario: i32, id_requisicao: &str) -> bool {
+        let cancelar = self
+            .requisicoes
+            .lock()
+            .ok()
+            .and_then(|r| r.get(id_requisicao).filter(|r| r.id_usuario == id_usuario).map(|r| r.cancelar.clone()));
+        match cancelar {
+            Some(cancelar) => {
+                cancelar.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn situacao(&self) -> Vec<SituacaoModelo> {
+        let modelos = match self.modelos.lock() {
+            Ok(modelos) => modelos,
+            Err(_) => return Vec::new(),
+        };
+        let mut situacao: Vec<SituacaoModelo> = modelos
+            .iter()
+            .map(|(modelo, estado)| SituacaoModelo {
+                modelo: modelo.clone(),
+                limite: estado.limite,
+                em_execucao: estado.em_execucao,
+                aguardando: estado.aguardando.len(),
+            })
+            .collect();
+        situacao.sort_by(|a, b| a.modelo.cmp(&b.modelo));
+        situacao
+    }
+
+    /// Posição de uma requisição do usuário; `None` se ela não existir ou for de outro usuário.
+    pub fn situacao_requisicao(&self, id_usuario: i32, id_requisicao: &str) -> Option<SituacaoRequisicao> {
+        let (modelo, ticket) = {
+            let requisicoes = self.requisicoes.lock().ok()?;
+            let requisicao = requisicoes.get(id_requisicao).filter(|r| r.id_usuario == id_usuario)?;
+            (requisicao.modelo.clone(), requisicao.ticket)
+        };
+        let modelos = self.modelos.lock().ok()?;
+        let posicao = modelos.get(&modelo).and_then(|estado| estado.aguardando.iter().position(|t| *t == ticket)).map(|p| p + 1);
+        Some(SituacaoRequisicao {
+            id_requisicao: id_requisicao.to_string(),
+            modelo,
+            status: if posicao.is_some() { "aguardando" } else { "executando" },
+            posicao,
+        })
+    }
+}
+
+/// Provedor que passa toda geração pela fila. A identificação permite ao
+/// usuário consultar a posição e cancelar pela API `/api/fila_ia`.
+pub struct ProviderEnfileirado {
+    interno: Arc<dyn LlmProvider>,
+    identificacao: Option<Identificacao>,
+}
+
+pub fn enfileirar(interno: Arc<dyn LlmProvider>, identificacao: Option<Identificacao>) -> Arc<dyn LlmProvider> {
+    Arc::new(ProviderEnfileirado { interno, identificacao })
+}
+
+#[async_trait]
+impl LlmProvider for ProviderEnfileirado {
+    fn name(&self) -> &'static str {
+        self.interno.name()
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.interno.list_models().await
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        fila().executar(&request.model, self.identificacao.as_ref(), self.interno.generate(request)).await
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        fila().executar(&request.model, self.identificacao.as_ref(), self.interno.chat(request)).await
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        fila().executar(model, self.identificacao.as_ref(), self.interno.embeddings(model, input)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fila_com_requisicao(id_usuario: i32, id_requisicao: &str) -> Fila {
+        let fila = Fila {
+            max_fila: 1,
+            proximo_ticket: AtomicU64::new(2),
+            modelos: Mutex::new(HashMap::new()),
+            requisicoes: Mutex::new(HashMap::new()),
+        };
+        fila.requisicoes.lock().unwrap().insert(
+            id_requisicao.to_string(),
+            Requisicao { modelo: "modelo".to_string(), ticket: 1, id_usuario, cancelar: Arc::new(Notify::new()) },
+        );
+        fila
+    }
+
+    #[test]
+    fn outro_usuario_nao_ve_nem_cancela_a_requisicao() {
+        let fila = fila_com_requisicao(7, "abc");
+        assert!(fila.situacao_requisicao(8, "abc").is_none());
+        assert!(!fila.cancelar(8, "abc"));
+
+        let situacao = fila.situacao_requisicao(7, "abc").unwrap();
+        assert_eq!(situacao.modelo, "modelo");
+        assert!(fila.cancelar(7, "abc"));
+    }
+}
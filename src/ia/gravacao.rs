@@ -0,0 +1,143 @@
+// src/ia/gravacao.rs
+// Respostas de um modelo real gravadas em arquivo JSON, para repetir uma
+// avaliação sem servidor de modelo. A chave de cada resposta é o hash do
+// modelo, das mensagens e do schema pedido.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::hash_hex;
+use super::provider::{ChatMessage, ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RespostaGravada {
+    pub modelo: String,
+    pub texto: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ArquivoGravacoes {
+    respostas: BTreeMap<String, RespostaGravada>,
+}
+
+/// Com um provedor interno, grava cada resposta; sem ele, só reproduz as gravadas.
+pub struct ProviderGravado {
+    interno: Option<Arc<dyn LlmProvider>>,
+    arquivo: PathBuf,
+    gravacoes: Mutex<ArquivoGravacoes>,
+}
+
+fn ler_arquivo(arquivo: &Path) -> Result<ArquivoGravacoes, String> {
+    let conteudo = std::fs::read_to_string(arquivo)
+        .map_err(|e| format!("Falha ao ler as respostas gravadas em {}: {}", arquivo.display(), e))?;
+    serde_json::from_str(&conteudo).map_err(|e| format!("Arquivo de respostas gravadas inválido ({}): {}", arquivo.display(), e))
+}
+
+fn chave(modelo: &str, messages: &[ChatMessage], format: &Option<Value>) -> String {
+    let mut entrada = String::from(modelo);
+    for m in messages {
+        entrada.push('\0');
+        entrada.push_str(&m.role);
+        entrada.push('\0');
+        entrada.push_str(&m.content);
+    }
+    if let Some(format) = format {
+        entrada.push('\0');
+        entrada.push_str(&format.to_string());
+    }
+    hash_hex(entrada.as_bytes())
+}
+
+impl ProviderGravado {
+    pub fn reproduzir(arquivo: &Path) -> Result<Self, String> {
+        Ok(ProviderGravado { interno: None, arquivo: arquivo.to_path_buf(), gravacoes: Mutex::new(ler_arquivo(arquivo)?) })
+    }
+
+    /// Grava as respostas de `interno`, acrescentando às que já existirem no arquivo.
+    pub fn gravar(interno: Arc<dyn LlmProvider>, arquivo: &Path) -> Result<Self, String> {
+        let gravacoes = if arquivo.exists() { ler_arquivo(arquivo)? } else { ArquivoGravacoes::default() };
+        Ok(ProviderGravado { interno: Some(interno), arquivo: arquivo.to_path_buf(), gravacoes: Mutex::new(gravacoes) })
+    }
+
+    pub fn salvar(&self) -> Result<(), String> {
+        let gravacoes = self.gravacoes.lock().map_err(|_| "Falha ao acessar as respostas gravadas.".to_string())?;
+        let conteudo = serde_json::to_string_pretty(&*gravacoes).map_err(|e| e.to_string())?;
+        std::fs::write(&self.arquivo, conteudo)
+            .map_err(|e| format!("Falha ao salvar as respostas gravadas em {}: {}", self.arquivo.display(), e))
+    }
+
+    fn gravada(&self, chave: &str, modelo: &str) -> Result<LlmResponse, LlmError> {
+        let gravacoes = self.gravacoes.lock().map_err(|_| LlmError::Resposta("recorded responses are unavailable".to_string()))?;
+        match gravacoes.respostas.get(chave) {
+            Some(gravada) => Ok(LlmResponse { text: gravada.texto.clone(), model: gravada.modelo.clone(), ..Default::default() }),
+            None => Err(LlmError::Resposta(format!("no recorded response for model '{}' and this prompt ({})", modelo, chave))),
+        }
+    }
+
+    fn registrar(&self, chave: String, resposta: &LlmResponse) {
+        if let Ok(mut gravacoes) = self.gravacoes.lock() {
+            gravacoes.respostas.insert(chave, RespostaGravada { modelo: resposta.model.clone(), texto: resposta.text.clone() });
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ProviderGravado {
+    fn name(&self) -> &'static str {
+        match &self.interno {
+            Some(interno) => interno.name(),
+            None => "gravado",
+        }
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        if let Some(interno) = &self.interno {
+            return interno.list_models().await;
+        }
+        let gravacoes = self.gravacoes.lock().map_err(|_| LlmError::Resposta("recorded responses are unavailable".to_string()))?;
+        let mut modelos: Vec<String> = gravacoes.respostas.values().map(|r| r.modelo.clone()).collect();
+        modelos.sort();
+        modelos.dedup();
+        Ok(modelos)
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(ChatMessage::system(system.clone()));
+        }
+        messages.push(ChatMessage::user(request.prompt.clone()));
+        let chave = chave(&request.model, &messages, &request.format);
+        match &self.interno {
+            Some(interno) => {
+                let resposta = interno.generate(request).await?;
+                self.registrar(chave, &resposta);
+                Ok(resposta)
+            }
+            None => self.gravada(&chave, &request.model),
+        }
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let chave = chave(&request.model, &request.messages, &request.format);
+        match &self.interno {
+            Some(interno) => {
+                let resposta = interno.chat(request).await?;
+                self.registrar(chave, &resposta);
+                Ok(resposta)
+            }
+            None => self.gravada(&chave, &request.model),
+        }
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        match &self.interno {
+            Some(interno) => interno.embeddings(model, input).await,
+            None => Err(LlmError::Configuracao("embeddings are not recorded".to_string())),
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// src/ia/mock.rs
+// Provedor determinístico para desenvolvimento e testes, sem servidor de modelo.
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+
+use super::provider::{ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+/// Dimensão dos vetores devolvidos por `embeddings`.
+pub const MOCK_EMBEDDING_DIM: usize = 64;
+
+pub struct MockProvider;
+
+// SHA-256 e não `DefaultHasher`: as respostas gravadas não podem mudar com a versão do Rust
+fn hash_de(texto: &str) -> u64 {
+    let digest = Sha256::digest(texto.as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 tem 32 bytes"))
+}
+
+/// Menor valor válido para o JSON Schema: objetos com as propriedades
+/// obrigatórias, listas vazias, `null` quando permitido.
+fn exemplo_do_schema(schema: &Value) -> Value {
+    let tipos: Vec<&str> = match &schema["type"] {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+        _ => vec![],
+    };
+
+    if tipos.contains(&"null") {
+        return Value::Null;
+    }
+    match tipos.first().copied() {
+        Some("object") => {
+            let mut obj = Map::new();
+            if let Some(props) = schema["properties"].as_object() {
+                for (nome, prop) in props {
+                    obj.insert(nome.clone(), exemplo_do_schema(prop));
+                }
+            }
+            Value::Object(obj)
+        }
+        Some("array") => json!([]),
+        Some("string") => json!(""),
+        Some("integer") | Some("number") => json!(0),
+        Some("boolean") => json!(false),
+        _ => Value::Null,
+    }
+}
+
+fn resposta(model: &str, entrada: &str, format: &Option<Value>) -> LlmResponse {
+    let text = match format {
+        Some(schema) => exemplo_do_schema(schema).to_string(),
+        None => format!("[mock:{}] resposta {:016x}", model, hash_de(entrada)),
+    };
+    LlmResponse {
+        prompt_tokens: Some(entrada.split_whitespace().count() as i64),
+        completion_tokens: Some(text.split_whitespace().count() as i64),
+        text,
+        model: model.to_string(),
+        total_duration_ns: Some(0),
+        ..Default::default()
+    }
+}
+
+/// Vetor determinístico por "bag of words" com hashing, normalizado.
+pub fn embedding_deterministico(texto: &str) -> Vec<f32> {
+    let mut vetor = vec![0f32; MOCK_EMBEDDING_DIM];
+    for palavra in texto.to_lowercase().split(|c: char| !c.is_alphanumeric()).filter(|p| !p.is_empty()) {
+        vetor[(hash_de(palavra) % MOCK_EMBEDDING_DIM as u64) as usize] += 1.0;
+    }
+    let norma = vetor.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norma > 0.0 {
+        vetor.iter_mut().for_each(|v| *v /= norma);
+    }
+    vetor
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    fn name(&self) -> &'static str {
+        "mock"
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        Ok(vec!["mock".to_string()])
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let entrada = format!("{}{}", request.system.clone().unwrap_or_default(), request.prompt);
+        Ok(resposta(&request.model, &entrada, &request.format))
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let entrada: String = request.messages.iter().map(|m| m.content.as_str()).collect::<Vec<_>>().join("\n");
+        Ok(resposta(&request.model, &entrada, &request.format))
+    }
+
+    async fn embeddings(&self, _model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        Ok(input.iter().map(|t| embedding_deterministico(t)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hash_nao_depende_da_versao_do_rust() {
+        // Primeiros 8 bytes do SHA-256 de "abc"
+        assert_eq!(hash_de("abc"), 0xba7816bf8f01cfea);
+    }
+}
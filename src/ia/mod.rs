@@ -0,0 +1,138 @@
+// src/ia/mod.rs
+// Funções compartilhadas pelas rotas de IA (provedores de LLM, análise de documentos).
+pub mod analise;
+pub mod auditoria;
+pub mod avaliacao;
+pub mod classificacao;
+pub mod fila;
+pub mod gravacao;
+pub mod injecao;
+pub mod mock;
+pub mod modelos;
+pub mod ollama;
+pub mod openai;
+pub mod peticao;
+pub mod prazos;
+pub mod prompts;
+pub mod provider;
+pub mod qa;
+pub mod redacao;
+pub mod resumo_longo;
+pub mod saude;
+pub mod similares;
+
+pub use provider::{provider_from_env, provider_para_requisicao, LlmError, LlmProvider};
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use pdf_extract::extract_text_from_mem;
+use sha2::{Digest, Sha256};
+
+/// Cliente HTTP do processo, o mesmo que vai no `ApplicationState`. A macro `api`
+/// importa `ApplicationState` em cada handler que recebe o estado, então só um
+/// handler por arquivo pode recebê-lo; os outros usam este.
+pub fn cliente_http() -> reqwest::Client {
+    static CLIENTE: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENTE
+        .get_or_init(|| {
+            // Sem tempo limite, uma geração travada no servidor do modelo prende a requisição para sempre
+            reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(10))
+                .timeout(fila::tempo_limite())
+                .build()
+                .expect("Failed to build HTTP client")
+        })
+        .clone()
+}
+
+/// Modelo usado nas análises automáticas quando nenhum outro é informado.
+pub const MODELO_ANALISE_PADRAO: &str = "qwen2.5:7b";
+
+/// Modelo padrão configurado em `LLM_MODELO_PADRAO` (ou no antigo
+/// `OLLAMA_ANALYSIS_MODEL`), ou `MODELO_ANALISE_PADRAO`.
+pub fn modelo_analise() -> String {
+    crate::config::atual().llm.modelo_padrao.clone()
+}
+
+/// Limite de caracteres do documento enviados ao modelo.
+pub const MAX_CARACTERES_DOCUMENTO: usize = 24_000;
+
+/// Corta o texto em `MAX_CARACTERES_DOCUMENTO` caracteres.
+pub fn truncar_texto(texto: &str) -> &str {
+    match texto.char_indices().nth(MAX_CARACTERES_DOCUMENTO) {
+        Some((idx, _)) => &texto[..idx],
+        None => texto,
+    }
+}
+
+/// SHA-256 em hexadecimal, usado como chave de cache e nos registros de auditoria.
+pub fn hash_hex(dados: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(dados))
+}
+
+/// Minúsculas, sem acentos e com espaços simples, para comparar palavras-chave e respostas.
+pub fn normalizar_texto(texto: &str) -> String {
+    texto
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' => 'a',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            'ç' => 'c',
+            outro => outro,
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Extrai o texto de um PDF armazenado em `Documento.arquivo`.
+pub fn extrair_texto(arquivo: &[u8]) -> Result<String, String> {
+    extract_text_from_mem(arquivo).map_err(|e| format!("Failed to extract text from PDF: {:?}", e))
+}
+
+/// Dados estruturados do caso em texto, para a variável `{{dados_caso}}` dos templates.
+pub async fn dados_do_caso(client_db: &tokio_postgres::Client, id_caso: i32) -> Result<String, tokio_postgres::Error> {
+    let row = client_db
+        .query_opt(
+            "SELECT c.numero_processo, c.descricao, c.data_abertura, c.data_fechamento,
+                    cl.nome AS cliente_nome, adv.nome AS advogado_nome, adv.oab AS advogado_oab,
+                    s.descricao AS status_descricao, vj.nome_vara, cc.descricao AS categoria_descricao
+             FROM Caso c
+             INNER JOIN Cliente cl ON c.id_cliente = cl.id_cliente
+             INNER JOIN Advogado adv ON c.id_advogado = adv.id_advogado
+             INNER JOIN Status s ON c.id_status = s.id_status
+             LEFT JOIN Vara_Judicial vj ON c.id_vara_judicial = vj.id_vara_judicial
+             LEFT JOIN Categoria_caso cc ON c.id_categoria_caso = cc.id_categoria_caso
+             WHERE c.id_caso = $1;",
+            &[&id_caso],
+        )
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(String::new());
+    };
+
+    let nao_informado = || "Não informado".to_string();
+    let data_abertura: chrono::NaiveDate = row.get("data_abertura");
+    let data_fechamento: Option<chrono::NaiveDate> = row.get("data_fechamento");
+
+    Ok(format!(
+        "Número do processo: {}\nCliente: {}\nAdvogado: {} (OAB {})\nStatus: {}\nVara: {}\nCategoria: {}\nAbertura: {}\nFechamento: {}\nDescrição: {}",
+        row.get::<_, Option<String>>("numero_processo").unwrap_or_else(nao_informado),
+        row.get::<_, String>("cliente_nome"),
+        row.get::<_, String>("advogado_nome"),
+        row.get::<_, String>("advogado_oab"),
+        row.get::<_, String>("status_descricao"),
+        row.get::<_, Option<String>>("nome_vara").unwrap_or_else(nao_informado),
+        row.get::<_, Option<String>>("categoria_descricao").unwrap_or_else(nao_informado),
+        data_abertura,
+        data_fechamento.map(|d| d.to_string()).unwrap_or_else(nao_informado),
+        row.get::<_, Option<String>>("descricao").unwrap_or_else(nao_informado),
+    ))
+}
@@ -0,0 +1,107 @@
+// src/ia/modelos.rs
+// Modelos liberados para uso (`LLM_MODELOS_PERMITIDOS`) e verificação do
+// modelo padrão na inicialização.
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::modelo_analise;
+use super::saude;
+use super::provider::{provider_from_env, ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+/// Modelos de `LLM_MODELOS_PERMITIDOS` (separados por vírgula). `None` libera
+/// todos os modelos instalados. O modelo padrão é sempre permitido.
+pub fn modelos_permitidos() -> Option<Vec<String>> {
+    crate::config::atual().llm.modelos_permitidos.clone()
+}
+
+/// Compara ignorando a tag `:latest`, que o Ollama acrescenta aos nomes sem tag.
+pub fn mesmo_modelo(a: &str, b: &str) -> bool {
+    a.trim_end_matches(":latest") == b.trim_end_matches(":latest")
+}
+
+pub fn modelo_permitido(modelo: &str) -> bool {
+    match modelos_permitidos() {
+        Some(permitidos) => permitidos.iter().any(|p| mesmo_modelo(p, modelo)),
+        None => true,
+    }
+}
+
+fn verificar(modelo: &str) -> Result<(), LlmError> {
+    if modelo_permitido(modelo) { Ok(()) } else { Err(LlmError::ModeloNaoPermitido(modelo.to_string())) }
+}
+
+/// Provedor que recusa modelos fora da lista e só lista os permitidos.
+/// Embeddings não passam pela lista: o modelo é escolhido pelo servidor, não pelo usuário.
+pub struct ProviderRestrito {
+    interno: Arc<dyn LlmProvider>,
+}
+
+/// Envolve o provedor com a lista de modelos; sem lista, devolve o próprio provedor.
+pub fn restringir(interno: Arc<dyn LlmProvider>) -> Arc<dyn LlmProvider> {
+    if modelos_permitidos().is_none() {
+        interno
+    } else {
+        Arc::new(ProviderRestrito { interno })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ProviderRestrito {
+    fn name(&self) -> &'static str {
+        self.interno.name()
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let modelos = self.interno.list_models().await?;
+        Ok(modelos.into_iter().filter(|m| modelo_permitido(m)).collect())
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        verificar(&request.model)?;
+        self.interno.generate(request).await
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        verificar(&request.model)?;
+        self.interno.chat(request).await
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        self.interno.embeddings(model, input).await
+    }
+}
+
+/// Confere, em segundo plano, se o modelo padrão está instalado no provedor e
+/// inicia o monitoramento de `saude`. Sem o modelo, o serviço fica marcado como
+/// "degradado" em `/api/saude_ia` até ele ser baixado (`POST /api/modelos_ia`);
+/// com o provedor fora do ar, como "indisponivel". A aplicação sobe nos dois casos.
+pub fn validar_modelo_padrao(fetch: Client) {
+    saude::iniciar_monitoramento(fetch.clone());
+    tuono_lib::tokio::spawn(async move {
+        let padrao = modelo_analise();
+        let llm = match provider_from_env(fetch) {
+            Ok(llm) => llm,
+            Err(e) => {
+                eprintln!("Default model check skipped: {}", e);
+                saude::marcar_indisponivel(&e);
+                return;
+            }
+        };
+        match saude::listar_modelos(llm.as_ref()).await.map(|situacao| situacao.modelos) {
+            Ok(instalados) if instalados.iter().any(|m| mesmo_modelo(m, &padrao)) => {
+                println!("Default LLM model '{}' is available ({})", padrao, llm.name());
+            }
+            Ok(instalados) => {
+                eprintln!(
+                    "Default LLM model '{}' is not installed ({}). Installed: [{}]. Pull it with POST /api/modelos_ia or set LLM_MODELO_PADRAO.",
+                    padrao,
+                    llm.name(),
+                    instalados.join(", ")
+                );
+            }
+            Err(e) => eprintln!("Could not validate default LLM model '{}': {}", padrao, e),
+        }
+    });
+}
@@ -0,0 +1,210 @@
+// src/ia/ollama.rs
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::provider::{normalizar_url, ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse, OpcoesGeracao};
+
+/// Tempo máximo de um `/api/pull`: modelos de vários GB em conexões lentas.
+const TEMPO_LIMITE_DOWNLOAD: Duration = Duration::from_secs(6 * 3600);
+
+#[derive(Debug, Deserialize)]
+struct OllamaTagsResponse {
+    models: Vec<OllamaModelInternal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaModelInternal {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatMessage {
+    content: String,
+}
+
+/// Campos comuns de `/api/generate` e `/api/chat` (sem streaming).
+#[derive(Debug, Deserialize)]
+struct OllamaRawResponse {
+    model: Option<String>,
+    response: Option<String>,
+    message: Option<OllamaChatMessage>,
+    prompt_eval_count: Option<i64>,
+    eval_count: Option<i64>,
+    total_duration: Option<i64>,
+    load_duration: Option<i64>,
+    prompt_eval_duration: Option<i64>,
+    eval_duration: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+pub struct OllamaProvider {
+    fetch: Client,
+    base_url: String,
+}
+
+impl OllamaProvider {
+    pub fn new(fetch: Client, base_url: &str) -> Self {
+        OllamaProvider { fetch, base_url: normalizar_url(base_url) }
+    }
+
+    fn options(options: &OpcoesGeracao, format: &Option<Value>) -> Value {
+        let mut opts = json!({});
+        if let Some(temperature) = options.temperature {
+            opts["temperature"] = json!(temperature);
+        } else if format.is_some() {
+            // Saída estruturada fica mais estável sem amostragem
+            opts["temperature"] = json!(0);
+        }
+        if let Some(num_ctx) = options.num_ctx {
+            opts["num_ctx"] = json!(num_ctx);
+        }
+        opts
+    }
+
+    async fn post(&self, path: &str, body: &Value) -> Result<reqwest::Response, LlmError> {
+        self.enviar(self.fetch.post(format!("{}{}", self.base_url, path)).json(body)).await
+    }
+
+    async fn enviar(&self, request: RequestBuilder) -> Result<reqwest::Response, LlmError> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| LlmError::Conexao(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let corpo = response.text().await.unwrap_or_else(|_| "No response body".to_string());
+            return Err(LlmError::Api { status, corpo });
+        }
+        Ok(response)
+    }
+
+    /// Baixa um modelo (`/api/pull`). A resposta é o fluxo NDJSON de progresso do Ollama.
+    /// O tempo limite do cliente (`LLM_TIMEOUT_SEGUNDOS`) vale até o fim do corpo e
+    /// cortaria o download de um modelo grande; aqui vale `TEMPO_LIMITE_DOWNLOAD`.
+    pub async fn puxar_modelo(&self, nome: &str) -> Result<reqwest::Response, LlmError> {
+        let request = self.fetch.post(format!("{}api/pull", self.base_url))
+            .json(&json!({"model": nome, "stream": true}))
+            .timeout(TEMPO_LIMITE_DOWNLOAD);
+        self.enviar(request).await
+    }
+
+    /// Remove um modelo instalado (`/api/delete`).
+    pub async fn remover_modelo(&self, nome: &str) -> Result<(), LlmError> {
+        let response = self.fetch.delete(format!("{}api/delete", self.base_url))
+            .json(&json!({"model": nome}))
+            .send()
+            .await
+            .map_err(|e| LlmError::Conexao(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let corpo = response.text().await.unwrap_or_else(|_| "No response body".to_string());
+            return Err(LlmError::Api { status, corpo });
+        }
+        Ok(())
+    }
+
+    /// Detalhes de um modelo instalado (`/api/show`): parâmetros, template, licença, etc.
+    pub async fn detalhes_modelo(&self, nome: &str) -> Result<Value, LlmError> {
+        self.post("api/show", &json!({"model": nome})).await?
+            .json()
+            .await
+            .map_err(|e| LlmError::Resposta(e.to_string()))
+    }
+
+    fn to_response(raw: OllamaRawResponse, model: &str) -> LlmResponse {
+        let text = raw.response
+            .or_else(|| raw.message.map(|m| m.content))
+            .unwrap_or_default();
+        LlmResponse {
+            text,
+            model: raw.model.unwrap_or_else(|| model.to_string()),
+            prompt_tokens: raw.prompt_eval_count,
+            completion_tokens: raw.eval_count,
+            total_duration_ns: raw.total_duration,
+            load_duration_ns: raw.load_duration,
+            prompt_eval_duration_ns: raw.prompt_eval_duration,
+            eval_duration_ns: raw.eval_duration,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> &'static str {
+        "ollama"
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let response = self.fetch.get(format!("{}api/tags", self.base_url))
+            .send()
+            .await
+            .map_err(|e| LlmError::Conexao(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let corpo = response.text().await.unwrap_or_else(|_| "No response body".to_string());
+            return Err(LlmError::Api { status, corpo });
+        }
+
+        let tags: OllamaTagsResponse = response.json().await.map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(tags.models.into_iter().map(|m| m.name).collect())
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let mut body = json!({
+            "model": request.model,
+            "prompt": request.prompt,
+            "stream": false,
+            "options": Self::options(&request.options, &request.format),
+        });
+        if let Some(system) = &request.system {
+            body["system"] = json!(system);
+        }
+        if let Some(schema) = &request.format {
+            body["format"] = schema.clone();
+        }
+
+        let raw: OllamaRawResponse = self.post("api/generate", &body).await?
+            .json()
+            .await
+            .map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(Self::to_response(raw, &request.model))
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": false,
+            "options": Self::options(&request.options, &request.format),
+        });
+        if let Some(schema) = &request.format {
+            body["format"] = schema.clone();
+        }
+
+        let raw: OllamaRawResponse = self.post("api/chat", &body).await?
+            .json()
+            .await
+            .map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(Self::to_response(raw, &request.model))
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let body = json!({"model": model, "input": input});
+        let data: OllamaEmbedResponse = self.post("api/embed", &body).await?
+            .json()
+            .await
+            .map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(data.embeddings)
+    }
+}
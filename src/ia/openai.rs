@@ -0,0 +1,205 @@
+// src/ia/openai.rs
+// Servidores compatíveis com a API da OpenAI (llama.cpp server, vLLM, LM Studio).
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::provider::{normalizar_url, ChatMessage, ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+#[derive(Debug, Deserialize)]
+struct ModelsResponse {
+    data: Vec<ModelEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEntry {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionResponse {
+    model: Option<String>,
+    choices: Vec<ChatChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoiceMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    prompt_tokens: Option<i64>,
+    completion_tokens: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingsResponse {
+    data: Vec<EmbeddingEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingEntry {
+    embedding: Vec<f32>,
+}
+
+pub struct OpenAiProvider {
+    fetch: Client,
+    /// URL base incluindo o prefixo da API, ex.: `http://localhost:8080/v1/`.
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl OpenAiProvider {
+    pub fn new(fetch: Client, base_url: &str, api_key: Option<String>) -> Self {
+        OpenAiProvider { fetch, base_url: normalizar_url(base_url), api_key }
+    }
+
+    fn autenticar(&self, builder: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(key) => builder.bearer_auth(key),
+            None => builder,
+        }
+    }
+
+    async fn enviar(&self, builder: RequestBuilder) -> Result<reqwest::Response, LlmError> {
+        let response = self.autenticar(builder)
+            .send()
+            .await
+            .map_err(|e| LlmError::Conexao(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let corpo = response.text().await.unwrap_or_else(|_| "No response body".to_string());
+            return Err(LlmError::Api { status, corpo });
+        }
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        let response = self.enviar(self.fetch.get(format!("{}models", self.base_url))).await?;
+        let models: ModelsResponse = response.json().await.map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(models.data.into_iter().map(|m| m.id).collect())
+    }
+
+    /// Nem todos os servidores expõem `/completions`; a geração usa o chat.
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(ChatMessage::system(system.clone()));
+        }
+        messages.push(ChatMessage::user(request.prompt.clone()));
+
+        self.chat(&ChatRequest {
+            model: request.model.clone(),
+            messages,
+            format: request.format.clone(),
+            options: request.options.clone(),
+        })
+        .await
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let mut body = json!({
+            "model": request.model,
+            "messages": request.messages,
+            "stream": false,
+        });
+        if let Some(temperature) = request.options.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(schema) = &request.format {
+            body["response_format"] = json!({
+                "type": "json_schema",
+                "json_schema": {"name": "resposta", "schema": schema, "strict": true}
+            });
+        }
+
+        let response = self.enviar(self.fetch.post(format!("{}chat/completions", self.base_url)).json(&body)).await?;
+        let data: ChatCompletionResponse = response.json().await.map_err(|e| LlmError::Resposta(e.to_string()))?;
+
+        let text = data.choices
+            .into_iter()
+            .next()
+            .and_then(|c| c.message.content)
+            .ok_or_else(|| LlmError::Resposta("Resposta sem 'choices'.".to_string()))?;
+
+        Ok(LlmResponse {
+            text,
+            model: data.model.unwrap_or_else(|| request.model.clone()),
+            prompt_tokens: data.usage.as_ref().and_then(|u| u.prompt_tokens),
+            completion_tokens: data.usage.as_ref().and_then(|u| u.completion_tokens),
+            ..Default::default()
+        })
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        let body: Value = json!({"model": model, "input": input});
+        let response = self.enviar(self.fetch.post(format!("{}embeddings", self.base_url)).json(&body)).await?;
+        let data: EmbeddingsResponse = response.json().await.map_err(|e| LlmError::Resposta(e.to_string()))?;
+        Ok(data.data.into_iter().map(|e| e.embedding).collect())
+    }
+}
+
+/// Regras do `strict: true`: todo objeto com `additionalProperties: false` e
+/// todas as propriedades em `required` (opcionais viram `["tipo", "null"]`).
+#[cfg(test)]
+pub(crate) fn conferir_schema_estrito(schema: &Value, caminho: &str) -> Result<(), String> {
+    if let Some(propriedades) = schema["properties"].as_object() {
+        if schema["additionalProperties"] != json!(false) {
+            return Err(format!("{}: falta \"additionalProperties\": false", caminho));
+        }
+        let obrigatorias: Vec<&str> = schema["required"].as_array().map(|r| r.iter().filter_map(|v| v.as_str()).collect()).unwrap_or_default();
+        for (nome, propriedade) in propriedades {
+            if !obrigatorias.contains(&nome.as_str()) {
+                return Err(format!("{}.{}: fora de \"required\"", caminho, nome));
+            }
+            conferir_schema_estrito(propriedade, &format!("{}.{}", caminho, nome))?;
+        }
+    }
+    if schema.get("items").is_some() {
+        conferir_schema_estrito(&schema["items"], &format!("{}[]", caminho))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conferencia_recusa_objeto_sem_additional_properties() {
+        let schema = json!({"type": "object", "properties": {"a": {"type": "string"}}, "required": ["a"]});
+        assert!(conferir_schema_estrito(&schema, "schema").is_err());
+    }
+
+    #[test]
+    fn conferencia_recusa_propriedade_opcional_aninhada() {
+        let schema = json!({
+            "type": "object",
+            "properties": {"itens": {"type": "array", "items": {
+                "type": "object",
+                "properties": {"a": {"type": "string"}, "b": {"type": "string"}},
+                "required": ["a"],
+                "additionalProperties": false
+            }}},
+            "required": ["itens"],
+            "additionalProperties": false
+        });
+        assert_eq!(conferir_schema_estrito(&schema, "schema"), Err("schema.itens[].b: fora de \"required\"".to_string()));
+    }
+}
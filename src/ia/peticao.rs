@@ -0,0 +1,334 @@
+// src/ia/peticao.rs
+// Minutas de peças (petição inicial, contestação, recurso) geradas a partir dos
+// dados do caso e de documentos selecionados. A minuta é gravada como um novo
+// Documento DOCX do caso, marcado como gerado por IA até a aprovação de um advogado.
+use std::io::{Cursor, Write};
+
+use serde::Serialize;
+use tokio_postgres::{Client as DbClient, GenericClient};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use super::{extrair_texto, truncar_texto};
+use crate::cifra::{arquivo_da_linha, cifrar_arquivo};
+
+pub const STATUS_PENDENTE: &str = "pendente";
+pub const STATUS_APROVADA: &str = "aprovada";
+
+/// Aviso gravado no topo do DOCX enquanto a minuta não é aprovada.
+const AVISO_MINUTA_IA: &str = "MINUTA GERADA POR INTELIGÊNCIA ARTIFICIAL — PENDENTE DE REVISÃO E APROVAÇÃO POR ADVOGADO. NÃO PROTOCOLAR.";
+
+/// Qualificação completa das partes e dados do processo, para a variável
+/// `{{dados_caso}}` dos templates de minuta. `None` se o caso não existir.
+pub async fn qualificacao_do_caso(client_db: &DbClient, id_caso: i32) -> Result<Option<String>, tokio_postgres::Error> {
+    let row = client_db
+        .query_opt(
+            "SELECT c.numero_processo, c.descricao, c.data_abertura,
+                    cl.nome AS cliente_nome, cl.email AS cliente_email, cl.telefone AS cliente_telefone, cl.endereco AS cliente_endereco,
+                    pf.cpf, pj.cnpj,
+                    adv.nome AS advogado_nome, adv.oab AS advogado_oab, adv.email AS advogado_email, adv.telefone AS advogado_telefone,
+                    vj.nome_vara, vj.cidade AS vara_cidade, vj.estado AS vara_estado,
+                    cc.descricao AS categoria_descricao
+             FROM Caso c
+             INNER JOIN Cliente cl ON c.id_cliente = cl.id_cliente
+             LEFT JOIN Pessoa_Fisica pf ON pf.id_cliente = cl.id_cliente
+             LEFT JOIN Pessoa_Juridica pj ON pj.id_cliente = cl.id_cliente
+             INNER JOIN Advogado adv ON c.id_advogado = adv.id_advogado
+             LEFT JOIN Vara_Judicial vj ON c.id_vara_judicial = vj.id_vara_judicial
+             LEFT JOIN Categoria_caso cc ON c.id_categoria_caso = cc.id_categoria_caso
+             WHERE c.id_caso = $1;",
+            &[&id_caso],
+        )
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let campo = |nome: &str| row.get::<_, Option<String>>(nome).filter(|v| !v.trim().is_empty()).unwrap_or_else(|| "[PREENCHER]".to_string());
+    let documento_cliente = match (row.get::<_, Option<String>>("cpf"), row.get::<_, Option<String>>("cnpj")) {
+        (Some(cpf), _) => format!("CPF {}", cpf),
+        (None, Some(cnpj)) => format!("CNPJ {}", cnpj),
+        (None, None) => "CPF/CNPJ [PREENCHER]".to_string(),
+    };
+    let data_abertura: chrono::NaiveDate = row.get("data_abertura");
+
+    Ok(Some(format!(
+        "Cliente: {}, {}, endereço: {}, e-mail: {}, telefone: {}\nAdvogado: {}, OAB {}, e-mail: {}, telefone: {}\nJuízo: {} - {}/{}\nNúmero do processo: {}\nCategoria: {}\nAbertura do caso: {}\nDescrição do caso: {}",
+        row.get::<_, String>("cliente_nome"),
+        documento_cliente,
+        campo("cliente_endereco"),
+        campo("cliente_email"),
+        campo("cliente_telefone"),
+        row.get::<_, String>("advogado_nome"),
+        row.get::<_, String>("advogado_oab"),
+        campo("advogado_email"),
+        campo("advogado_telefone"),
+        campo("nome_vara"),
+        campo("vara_cidade"),
+        campo("vara_estado"),
+        campo("numero_processo"),
+        campo("categoria_descricao"),
+        data_abertura,
+        campo("descricao"),
+    )))
+}
+
+/// Texto dos documentos de apoio, todos do caso informado, separados pelo nome
+/// do arquivo e cortado em `MAX_CARACTERES_DOCUMENTO`.
+pub async fn textos_de_apoio(client_db: &DbClient, id_caso: i32, ids_documento: &[i32]) -> Result<String, String> {
+    if ids_documento.is_empty() {
+        return Ok("Nenhum documento selecionado.".to_string());
+    }
+
+    let rows = client_db
+        .query(
+            "SELECT id_documento, nome_arquivo, arquivo, chave_cifrada, id_chave_mestra FROM Documento WHERE id_caso = $1 AND id_documento = ANY($2) ORDER BY id_documento;",
+            &[&id_caso, &ids_documento],
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch supporting documents: {}", e))?;
+
+    if rows.len() != ids_documento.len() {
+        return Err("Todos os documentos de apoio precisam existir e pertencer ao caso.".to_string());
+    }
+
+    let mut textos: Vec<String> = Vec::new();
+    for row in rows {
+        let nome_arquivo: String = row.get("nome_arquivo");
+        let arquivo = arquivo_da_linha(&row).map_err(|e| format!("Documento '{}': {}", nome_arquivo, e))?;
+        let arquivo = arquivo.ok_or_else(|| format!("Documento '{}' não tem arquivo.", nome_arquivo))?;
+        let texto = extrair_texto(&arquivo).map_err(|e| format!("Documento '{}': {}", nome_arquivo, e))?;
+        textos.push(format!("### {}\n{}", nome_arquivo, texto));
+    }
+    Ok(truncar_texto(&textos.join("\n\n")).to_string())
+}
+
+// Partes mínimas de um DOCX (WordprocessingML): tipos de conteúdo, relação
+// com o documento principal e o próprio documento
+const DOCX_TIPOS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+const DOCX_RELACOES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+/// Texto escapado para XML, sem os caracteres de controle que o formato não aceita.
+fn escapar_xml(texto: &str) -> String {
+    let mut saida = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        match c {
+            '&' => saida.push_str("&amp;"),
+            '<' => saida.push_str("&lt;"),
+            '>' => saida.push_str("&gt;"),
+            '"' => saida.push_str("&quot;"),
+            '\t' => saida.push(c),
+            c if c < ' ' => {}
+            c => saida.push(c),
+        }
+    }
+    saida
+}
+
+/// Parágrafo com um único trecho; `formatacao` vai para as propriedades do trecho (`w:rPr`).
+fn paragrafo(texto: &str, centralizado: bool, formatacao: &str) -> String {
+    format!(
+        r#"<w:p>{}<w:r><w:rPr>{}</w:rPr><w:t xml:space="preserve">{}</w:t></w:r></w:p>"#,
+        if centralizado { r#"<w:pPr><w:jc w:val="center"/></w:pPr>"# } else { "" },
+        formatacao,
+        escapar_xml(texto)
+    )
+}
+
+/// Monta o DOCX da minuta. Linhas iniciadas por `#` viram títulos em negrito e
+/// os marcadores `**` de Markdown são removidos. Com `rascunho`, o documento
+/// começa com o aviso de minuta gerada por IA.
+pub fn gerar_docx(titulo: &str, texto: &str, rascunho: bool) -> Result<Vec<u8>, String> {
+    let mut corpo = String::new();
+    if rascunho {
+        corpo.push_str(&paragrafo(AVISO_MINUTA_IA, true, r#"<w:b/><w:color w:val="C00000"/>"#));
+    }
+    corpo.push_str(&paragrafo(titulo, true, r#"<w:b/><w:sz w:val="28"/>"#));
+
+    for linha in texto.lines() {
+        let linha = linha.trim_end().replace("**", "");
+        if linha.trim().is_empty() {
+            continue;
+        }
+        match linha.trim_start().strip_prefix('#') {
+            Some(titulo) => corpo.push_str(&paragrafo(titulo.trim_start_matches('#').trim(), false, "<w:b/>")),
+            None => corpo.push_str(&paragrafo(&linha, false, "")),
+        }
+    }
+
+    let documento = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>{}</w:body></w:document>"#,
+        corpo
+    );
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for (nome, conteudo) in [("[Content_Types].xml", DOCX_TIPOS), ("_rels/.rels", DOCX_RELACOES), ("word/document.xml", documento.as_str())] {
+        zip.start_file(nome, opcoes).map_err(|e| format!("Failed to build DOCX: {}", e))?;
+        zip.write_all(conteudo.as_bytes()).map_err(|e| format!("Failed to build DOCX: {}", e))?;
+    }
+    let saida = zip.finish().map_err(|e| format!("Failed to build DOCX: {}", e))?;
+    Ok(saida.into_inner())
+}
+
+/// Título da peça no DOCX; templates próprios usam o nome em maiúsculas.
+pub fn titulo_da_peca(tipo: &str) -> String {
+    match tipo {
+        super::prompts::TEMPLATE_PETICAO_INICIAL => "PETIÇÃO INICIAL".to_string(),
+        super::prompts::TEMPLATE_CONTESTACAO => "CONTESTAÇÃO".to_string(),
+        super::prompts::TEMPLATE_RECURSO => "RECURSO".to_string(),
+        outro => outro.replace('_', " ").to_uppercase(),
+    }
+}
+
+pub struct NovaMinuta<'a> {
+    pub id_caso: i32,
+    pub tipo: &'a str,
+    pub modelo: &'a str,
+    pub id_versao_prompt: Option<i32>,
+    pub ids_documento: &'a [i32],
+    pub instrucoes: Option<&'a str>,
+    pub texto: &'a str,
+    /// Minuta da qual esta é uma nova versão.
+    pub id_documento_anterior: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MinutaSalva {
+    pub id_documento: i32,
+    pub id_documento_original: i32,
+    pub versao: i32,
+    pub nome_arquivo: String,
+}
+
+/// Grava a minuta como Documento do caso (versão seguinte à da minuta anterior,
+/// quando informada) e registra os metadados em Minuta_Ia.
+pub async fn salvar_minuta<C: GenericClient>(client_db: &C, nova: &NovaMinuta<'_>) -> Result<MinutaSalva, String> {
+    let (id_original, versao) = match nova.id_documento_anterior {
+        Some(id_anterior) => {
+            let row = client_db
+                .query_opt(
+                    "SELECT COALESCE(d.id_documento_original, d.id_documento) AS id_original
+                     FROM Documento d INNER JOIN Minuta_Ia m ON m.id_documento = d.id_documento
+                     WHERE d.id_documento = $1 AND d.id_caso = $2;",
+                    &[&id_anterior, &nova.id_caso],
+                )
+                .await
+                .map_err(|e| format!("Failed to fetch previous draft: {}", e))?
+                .ok_or_else(|| "Minuta anterior não encontrada neste caso.".to_string())?;
+            let id_original: i32 = row.get("id_original");
+            let versao: i32 = client_db
+                .query_one(
+                    "SELECT MAX(versao) + 1 AS versao FROM Documento WHERE id_documento = $1 OR id_documento_original = $1;",
+                    &[&id_original],
+                )
+                .await
+                .map_err(|e| format!("Failed to compute draft version: {}", e))?
+                .get("versao");
+            (Some(id_original), versao)
+        }
+        None => (None, 1),
+    };
+
+    let titulo = titulo_da_peca(nova.tipo);
+    let arquivo = cifrar_arquivo(&gerar_docx(&titulo, nova.texto, true)?)?;
+    let nome_arquivo = format!("minuta_{}_caso{}_v{}.docx", nova.tipo, nova.id_caso, versao);
+    let descricao = format!("Minuta de {} gerada por IA (v{}) - pendente de aprovação", titulo.to_lowercase(), versao);
+
+    let row = client_db
+        .query_one(
+            "INSERT INTO Documento (id_caso, descricao, data_envio, nome_arquivo, arquivo, chave_cifrada, id_chave_mestra, versao, id_documento_original, gerado_por_ia)
+             VALUES ($1, $2, CURRENT_DATE, $3, $4, $5, $6, $7, $8, TRUE) RETURNING id_documento;",
+            &[&nova.id_caso, &descricao, &nome_arquivo, &arquivo.arquivo, &arquivo.chave_cifrada, &arquivo.id_chave_mestra, &versao, &id_original],
+        )
+        .await
+        .map_err(|e| format!("Failed to insert draft document: {}", e))?;
+    let id_documento: i32 = row.get("id_documento");
+
+    client_db
+        .execute(
+            "INSERT INTO Minuta_Ia (id_documento, tipo, modelo, id_versao_prompt, documentos_base, instrucoes, texto, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8);",
+            &[&id_documento, &nova.tipo, &nova.modelo, &nova.id_versao_prompt, &nova.ids_documento, &nova.instrucoes, &nova.texto, &STATUS_PENDENTE],
+        )
+        .await
+        .map_err(|e| format!("Failed to insert draft metadata: {}", e))?;
+
+    Ok(MinutaSalva { id_documento, id_documento_original: id_original.unwrap_or(id_documento), versao, nome_arquivo })
+}
+
+/// Aprova a minuta: regrava o DOCX sem o aviso de IA e registra o advogado.
+/// Retorna `false` se o documento não for uma minuta pendente.
+pub async fn aprovar_minuta<C: GenericClient>(client_db: &C, id_documento: i32, id_advogado: i32) -> Result<bool, String> {
+    let row = client_db
+        .query_opt(
+            "SELECT m.tipo, m.texto FROM Minuta_Ia m WHERE m.id_documento = $1 AND m.status = $2;",
+            &[&id_documento, &STATUS_PENDENTE],
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch draft: {}", e))?;
+    let Some(row) = row else {
+        return Ok(false);
+    };
+
+    let tipo: String = row.get("tipo");
+    let texto: String = row.get("texto");
+    let titulo = titulo_da_peca(&tipo);
+    let arquivo = cifrar_arquivo(&gerar_docx(&titulo, &texto, false)?)?;
+
+    client_db
+        .execute(
+            "UPDATE Documento SET arquivo = $1, chave_cifrada = $2, id_chave_mestra = $3, descricao = REPLACE(descricao, 'pendente de aprovação', 'aprovada')
+             WHERE id_documento = $4;",
+            &[&arquivo.arquivo, &arquivo.chave_cifrada, &arquivo.id_chave_mestra, &id_documento],
+        )
+        .await
+        .map_err(|e| format!("Failed to update approved draft: {}", e))?;
+    client_db
+        .execute(
+            "UPDATE Minuta_Ia SET status = $1, id_advogado_aprovador = $2, data_aprovacao = CURRENT_TIMESTAMP WHERE id_documento = $3;",
+            &[&STATUS_APROVADA, &id_advogado, &id_documento],
+        )
+        .await
+        .map_err(|e| format!("Failed to record draft approval: {}", e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn documento_do_docx(docx: &[u8]) -> String {
+        let mut arquivo = zip::ZipArchive::new(Cursor::new(docx)).unwrap();
+        for parte in ["[Content_Types].xml", "_rels/.rels"] {
+            assert!(arquivo.by_name(parte).is_ok(), "{} ausente", parte);
+        }
+        let mut documento = String::new();
+        arquivo.by_name("word/document.xml").unwrap().read_to_string(&mut documento).unwrap();
+        documento
+    }
+
+    #[test]
+    fn docx_traz_aviso_titulo_e_texto_escapado() {
+        let docx = gerar_docx("PETIÇÃO INICIAL", "## DOS FATOS\n\nO **autor** & a ré <acordaram>\u{0} o valor.", true).unwrap();
+        let documento = documento_do_docx(&docx);
+        assert!(documento.contains(AVISO_MINUTA_IA));
+        assert!(documento.contains("PETIÇÃO INICIAL"));
+        assert!(documento.contains(r#"<w:b/></w:rPr><w:t xml:space="preserve">DOS FATOS</w:t>"#));
+        assert!(documento.contains("O autor &amp; a ré &lt;acordaram&gt; o valor."));
+        assert_eq!(documento.matches("<w:p>").count(), 4);
+    }
+
+    #[test]
+    fn docx_aprovado_sem_aviso() {
+        let documento = documento_do_docx(&gerar_docx("CONTESTAÇÃO", "Texto.", false).unwrap());
+        assert!(!documento.contains(AVISO_MINUTA_IA));
+    }
+}
@@ -0,0 +1,395 @@
+// src/ia/prazos.rs
+// Detecção de prazos em intimações/decisões e sugestão de tarefas.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_postgres::Client as DbClient;
+
+use super::{extrair_texto, truncar_texto};
+use super::prompts::{carregar_template, PromptTemplate, TEMPLATE_PRAZOS_DOCUMENTO};
+use super::provider::{GenerateRequest, LlmProvider};
+use crate::cifra::arquivo_da_linha;
+
+/// Prazo do art. 218, § 3º, do CPC quando o juiz não fixa outro.
+const PRAZO_PADRAO_DIAS: i32 = 5;
+
+/// Palavras que indicam que o documento é uma intimação ou decisão.
+const MARCADORES_INTIMACAO: [&str; 8] = [
+    "intimação", "intime-se", "intimem-se", "decisão", "despacho", "sentença", "cite-se", "manifestação",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OrigemPrazo {
+    Regra,
+    Llm,
+}
+
+impl OrigemPrazo {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrigemPrazo::Regra => "regra",
+            OrigemPrazo::Llm => "llm",
+        }
+    }
+}
+
+/// Prazo encontrado no texto, com a posição (em bytes) do trecho de origem.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrazoDetectado {
+    pub descricao: String,
+    pub dias: i32,
+    pub dias_uteis: bool,
+    pub trecho: String,
+    pub inicio: usize,
+    pub fim: usize,
+    pub origem: OrigemPrazo,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrazoLlm {
+    descricao: String,
+    dias: i32,
+    dias_uteis: bool,
+    trecho: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RespostaPrazosLlm {
+    prazos: Vec<PrazoLlm>,
+}
+
+pub fn parece_intimacao(texto: &str) -> bool {
+    let texto = texto.to_lowercase();
+    MARCADORES_INTIMACAO.iter().any(|m| texto.contains(m))
+}
+
+fn numero_por_extenso(palavra: &str) -> Option<i32> {
+    match palavra.to_lowercase().as_str() {
+        "um" => Some(1),
+        "dois" => Some(2),
+        "três" | "tres" => Some(3),
+        "cinco" => Some(5),
+        "dez" => Some(10),
+        "quinze" => Some(15),
+        "vinte" => Some(20),
+        "trinta" => Some(30),
+        _ => None,
+    }
+}
+
+/// Detecção por padrões: "prazo de 15 (quinze) dias", "prazo de quinze dias úteis",
+/// "intime-se para manifestação" (sem prazo explícito, aplica o prazo padrão).
+pub fn detectar_por_regras(texto: &str) -> Vec<PrazoDetectado> {
+    let numerico = Regex::new(r"(?i)prazos?\s+(?:comum\s+)?de\s+(\d{1,3})\s*(?:\([^)]{0,30}\)\s*)?dias(\s+úteis|\s+uteis|\s+corridos)?").unwrap();
+    let extenso = Regex::new(r"(?i)prazos?\s+(?:comum\s+)?de\s+(um|dois|três|tres|cinco|dez|quinze|vinte|trinta)\s+dias(\s+úteis|\s+uteis|\s+corridos)?").unwrap();
+    let manifestacao = Regex::new(r"(?i)intime[m]?-se[^.;]{0,160}?para\s+(?:que\s+)?(?:se\s+)?manifest[a-zçã]*[^.;]*").unwrap();
+
+    let mut prazos: Vec<PrazoDetectado> = Vec::new();
+
+    for caps in numerico.captures_iter(texto).chain(extenso.captures_iter(texto)) {
+        let m = caps.get(0).unwrap();
+        let dias = match caps[1].parse::<i32>().ok().or_else(|| numero_por_extenso(&caps[1])) {
+            Some(d) if d > 0 => d,
+            _ => continue,
+        };
+        let corridos = caps.get(2).map(|c| c.as_str().to_lowercase().contains("corridos")).unwrap_or(false);
+        prazos.push(PrazoDetectado {
+            descricao: format!("Prazo de {} dias{}", dias, if corridos { " corridos" } else { " úteis" }),
+            dias,
+            dias_uteis: !corridos,
+            trecho: m.as_str().to_string(),
+            inicio: m.start(),
+            fim: m.end(),
+            origem: OrigemPrazo::Regra,
+        });
+    }
+
+    for m in manifestacao.find_iter(texto) {
+        // Só aplica o prazo padrão quando a frase não traz um prazo explícito
+        if prazos.iter().any(|p| p.inicio < m.end() && m.start() < p.fim) {
+            continue;
+        }
+        prazos.push(PrazoDetectado {
+            descricao: "Manifestação (prazo não fixado, art. 218, § 3º, CPC)".to_string(),
+            dias: PRAZO_PADRAO_DIAS,
+            dias_uteis: true,
+            trecho: m.as_str().to_string(),
+            inicio: m.start(),
+            fim: m.end(),
+            origem: OrigemPrazo::Regra,
+        });
+    }
+
+    prazos.sort_by_key(|p| p.inicio);
+    prazos
+}
+
+fn schema_prazos() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "prazos": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "descricao": {"type": "string"},
+                        "dias": {"type": "integer"},
+                        "dias_uteis": {"type": "boolean"},
+                        "trecho": {"type": "string"}
+                    },
+                    "required": ["descricao", "dias", "dias_uteis", "trecho"],
+                    "additionalProperties": false
+                }
+            }
+        },
+        "required": ["prazos"],
+        "additionalProperties": false
+    })
+}
+
+/// Pede ao modelo os prazos do documento. Só são mantidos os prazos cujo
+/// trecho aparece literalmente no texto, para que a sugestão sempre aponte
+/// para a origem.
+pub async fn detectar_por_llm(llm: &dyn LlmProvider, template: &PromptTemplate, modelo: &str, texto: &str) -> Result<Vec<PrazoDetectado>, String> {
+    let (system, prompt) = template.render(&HashMap::from([("documento", truncar_texto(texto).to_string())]))?;
+
+    let resposta = llm.generate(&GenerateRequest {
+        model: modelo.to_string(),
+        prompt,
+        system,
+        format: Some(schema_prazos()),
+        options: template.opcoes(),
+    }).await?;
+    let dados: RespostaPrazosLlm = serde_json::from_str(&resposta.text)
+        .map_err(|e| format!("A resposta de prazos não corresponde ao schema: {}", e))?;
+
+    Ok(dados.prazos.into_iter().filter_map(|p| {
+        let trecho = p.trecho.trim();
+        if p.dias <= 0 || trecho.is_empty() {
+            return None;
+        }
+        let inicio = texto.find(trecho)?;
+        Some(PrazoDetectado {
+            descricao: p.descricao,
+            dias: p.dias,
+            dias_uteis: p.dias_uteis,
+            trecho: trecho.to_string(),
+            inicio,
+            fim: inicio + trecho.len(),
+            origem: OrigemPrazo::Llm,
+        })
+    }).collect())
+}
+
+/// Une as detecções, descartando sugestões do modelo que repetem um prazo
+/// já encontrado pelas regras no mesmo trecho.
+pub fn combinar(regras: Vec<PrazoDetectado>, llm: Vec<PrazoDetectado>) -> Vec<PrazoDetectado> {
+    let mut prazos = regras;
+    for p in llm {
+        let duplicado = prazos.iter().any(|r| r.dias == p.dias && r.inicio < p.fim && p.inicio < r.fim);
+        if !duplicado {
+            prazos.push(p);
+        }
+    }
+    prazos.sort_by_key(|p| p.inicio);
+    prazos
+}
+
+/// Data de vencimento contada a partir do dia seguinte à data base.
+/// Em dias úteis, sábados e domingos são ignorados (feriados não são considerados).
+pub fn calcular_vencimento(data_base: NaiveDate, dias: i32, dias_uteis: bool) -> NaiveDate {
+    if !dias_uteis {
+        return data_base + Duration::days(dias as i64);
+    }
+
+    let mut data = data_base;
+    let mut restantes = dias;
+    while restantes > 0 {
+        data += Duration::days(1);
+        if !matches!(data.weekday(), Weekday::Sat | Weekday::Sun) {
+            restantes -= 1;
+        }
+    }
+    data
+}
+
+/// Detecta os prazos do documento e grava as sugestões de tarefa pendentes.
+/// Retorna o número de sugestões criadas.
+pub async fn sugerir_tarefas(llm: &dyn LlmProvider, client_db: &DbClient, id_documento: i32, modelo: Option<&str>) -> Result<usize, String> {
+    let row = client_db
+        .query_one(
+            "SELECT id_caso, arquivo, chave_cifrada, id_chave_mestra, data_envio FROM Documento WHERE id_documento = $1;",
+            &[&id_documento],
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch document for deadline detection: {}", e))?;
+
+    let id_caso: i32 = row.get("id_caso");
+    let arquivo = arquivo_da_linha(&row)?;
+    let data_envio: Option<NaiveDate> = row.get("data_envio");
+
+    let arquivo = arquivo.ok_or_else(|| "Documento sem conteúdo.".to_string())?;
+    let texto = extrair_texto(&arquivo)?;
+    if !parece_intimacao(&texto) {
+        return Ok(0);
+    }
+
+    let template = carregar_template(client_db, TEMPLATE_PRAZOS_DOCUMENTO, None).await?;
+    let modelo = template.modelo(modelo);
+
+    let regras = detectar_por_regras(&texto);
+    let prazos_llm = match detectar_por_llm(llm, &template, &modelo, &texto).await {
+        Ok(prazos) => prazos,
+        Err(e) => {
+            // As regras continuam valendo mesmo sem o modelo
+            eprintln!("LLM deadline detection failed for document {}: {}", id_documento, e);
+            Vec::new()
+        }
+    };
+    let prazos = combinar(regras, prazos_llm);
+
+    let data_base = data_envio.unwrap_or_else(|| chrono::Local::now().date_naive());
+
+    // Reprocessar o documento substitui as sugestões ainda não decididas
+    client_db
+        .execute("DELETE FROM Tarefa_Sugerida WHERE id_documento = $1 AND status = 'pendente';", &[&id_documento])
+        .await
+        .map_err(|e| format!("Failed to clear pending suggestions: {}", e))?;
+
+    for prazo in &prazos {
+        let vencimento = calcular_vencimento(data_base, prazo.dias, prazo.dias_uteis);
+        let inicio = prazo.inicio as i32;
+        let fim = prazo.fim as i32;
+        let id_versao_prompt = if prazo.origem == OrigemPrazo::Llm { template.id_versao } else { None };
+        client_db
+            .execute(
+                "INSERT INTO Tarefa_Sugerida (id_caso, id_documento, descricao, prazo_dias, dias_uteis, data_base, data_vencimento, origem, trecho, inicio_trecho, fim_trecho, id_versao_prompt, status)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, 'pendente');",
+                &[&id_caso, &id_documento, &prazo.descricao, &prazo.dias, &prazo.dias_uteis, &data_base, &vencimento, &prazo.origem.as_str(), &prazo.trecho, &inicio, &fim, &id_versao_prompt],
+            )
+            .await
+            .map_err(|e| format!("Failed to store task suggestion: {}", e))?;
+    }
+
+    Ok(prazos.len())
+}
+
+/// Agenda a detecção de prazos de um documento recém-enviado em segundo plano.
+pub fn agendar_sugestoes(llm: Arc<dyn LlmProvider>, id_documento: i32, modelo: Option<String>) {
+    tuono_lib::tokio::spawn(async move {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for deadline detection: {}", e);
+                return;
+            }
+        };
+
+        match sugerir_tarefas(llm.as_ref(), &client_db, id_documento, modelo.as_deref()).await {
+            Ok(total) if total > 0 => println!("{} task suggestion(s) created for document {}", total, id_documento),
+            Ok(_) => {}
+            Err(e) => eprintln!("Deadline detection failed for document {}: {}", id_documento, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ia::openai::conferir_schema_estrito;
+
+    #[test]
+    fn schema_de_prazos_vale_no_modo_estrito() {
+        assert_eq!(conferir_schema_estrito(&schema_prazos(), "prazos"), Ok(()));
+    }
+
+    fn data(ano: i32, mes: u32, dia: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(ano, mes, dia).unwrap()
+    }
+
+    #[test]
+    fn prazo_numerico_com_extenso_entre_parenteses() {
+        let texto = "Intime-se o réu para contestar, no prazo de 15 (quinze) dias úteis.";
+        let prazos = detectar_por_regras(texto);
+        assert_eq!(prazos.len(), 1);
+        assert_eq!(prazos[0].dias, 15);
+        assert!(prazos[0].dias_uteis);
+        assert_eq!(&texto[prazos[0].inicio..prazos[0].fim], prazos[0].trecho);
+        assert_eq!(prazos[0].trecho, "prazo de 15 (quinze) dias úteis");
+    }
+
+    #[test]
+    fn prazo_por_extenso_e_dias_corridos() {
+        let prazos = detectar_por_regras("Concedo o prazo comum de DEZ dias corridos às partes.");
+        assert_eq!(prazos.len(), 1);
+        assert_eq!(prazos[0].dias, 10);
+        assert!(!prazos[0].dias_uteis);
+        assert_eq!(prazos[0].descricao, "Prazo de 10 dias corridos");
+
+        let prazos = detectar_por_regras("no prazo de cinco dias");
+        assert_eq!((prazos[0].dias, prazos[0].dias_uteis), (5, true));
+    }
+
+    #[test]
+    fn varios_prazos_em_ordem_e_prazo_zero_ignorado() {
+        let texto = "Prazo de 0 dias. Apresente réplica no prazo de 15 dias. Após, prazos de 5 dias para as partes.";
+        let dias: Vec<i32> = detectar_por_regras(texto).iter().map(|p| p.dias).collect();
+        assert_eq!(dias, vec![15, 5]);
+    }
+
+    #[test]
+    fn manifestacao_sem_prazo_usa_o_padrao() {
+        let prazos = detectar_por_regras("Intimem-se as partes para que se manifestem sobre o laudo.");
+        assert_eq!(prazos.len(), 1);
+        assert_eq!(prazos[0].dias, PRAZO_PADRAO_DIAS);
+        assert!(prazos[0].dias_uteis);
+        assert!(prazos[0].trecho.starts_with("Intimem-se"));
+    }
+
+    #[test]
+    fn manifestacao_com_prazo_explicito_nao_repete() {
+        let prazos = detectar_por_regras("Intime-se o autor para manifestação no prazo de 10 (dez) dias.");
+        assert_eq!(prazos.len(), 1);
+        assert_eq!(prazos[0].dias, 10);
+    }
+
+    #[test]
+    fn texto_sem_prazo() {
+        assert!(detectar_por_regras("Junte-se aos autos. Cumpra-se.").is_empty());
+        assert!(!parece_intimacao("Recibo de pagamento de honorários."));
+        assert!(parece_intimacao("DESPACHO: cite-se o réu."));
+    }
+
+    #[test]
+    fn vencimento_pula_fim_de_semana_so_em_dias_uteis() {
+        // 03/05/2024 é uma sexta-feira
+        assert_eq!(calcular_vencimento(data(2024, 5, 3), 1, true), data(2024, 5, 6));
+        assert_eq!(calcular_vencimento(data(2024, 5, 3), 5, true), data(2024, 5, 10));
+        assert_eq!(calcular_vencimento(data(2024, 5, 3), 5, false), data(2024, 5, 8));
+        assert_eq!(calcular_vencimento(data(2024, 5, 4), 1, true), data(2024, 5, 6));
+    }
+
+    #[test]
+    fn combinar_descarta_sugestao_repetida_do_modelo() {
+        let regras = detectar_por_regras("Manifeste-se no prazo de 15 dias.");
+        let prazo = |dias, inicio, fim| PrazoDetectado {
+            descricao: String::new(),
+            dias,
+            dias_uteis: true,
+            trecho: String::new(),
+            inicio,
+            fim,
+            origem: OrigemPrazo::Llm,
+        };
+        let (inicio, fim) = (regras[0].inicio, regras[0].fim);
+        let llm = vec![prazo(15, inicio + 1, fim + 1), prazo(30, inicio + 1, fim), prazo(15, fim + 10, fim + 20)];
+        let combinados = combinar(regras, llm);
+        let origens: Vec<(i32, &str)> = combinados.iter().map(|p| (p.dias, p.origem.as_str())).collect();
+        assert_eq!(origens, vec![(15, "regra"), (30, "llm"), (15, "llm")]);
+    }
+}
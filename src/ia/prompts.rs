@@ -0,0 +1,291 @@
+// src/ia/prompts.rs
+// Templates de prompt versionados (tabelas Prompt_Template e Prompt_Template_Versao).
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tokio_postgres::{Client as DbClient, GenericClient, Row};
+
+use super::injecao::{escapar_documento, REGRAS_DOCUMENTO_NAO_CONFIAVEL};
+use super::provider::OpcoesGeracao;
+
+pub const TEMPLATE_QA_DOCUMENTO: &str = "qa_documento";
+pub const TEMPLATE_RESUMO_DOCUMENTO: &str = "resumo_documento";
+pub const TEMPLATE_EXTRACAO_DOCUMENTO: &str = "extracao_documento";
+pub const TEMPLATE_PRAZOS_DOCUMENTO: &str = "prazos_documento";
+pub const TEMPLATE_RESUMO_PARTE: &str = "resumo_parte";
+pub const TEMPLATE_RESUMO_CONSOLIDADO: &str = "resumo_consolidado";
+pub const TEMPLATE_PETICAO_INICIAL: &str = "peticao_inicial";
+pub const TEMPLATE_CONTESTACAO: &str = "contestacao";
+pub const TEMPLATE_RECURSO: &str = "recurso";
+pub const TEMPLATE_CLASSIFICACAO_CASO: &str = "classificacao_caso";
+
+/// Variáveis com texto de terceiros: os valores são escapados e o template
+/// recebe as regras de sistema contra prompt injection.
+const VARIAVEIS_NAO_CONFIAVEIS: [&str; 2] = ["documento", "resumos"];
+
+/// System prompt comum aos templates de minutas de peças.
+const SYSTEM_MINUTA: &str = "Você redige minutas de peças processuais para revisão por um advogado. Use apenas os dados informados; quando faltar uma informação, escreva [PREENCHER] em vez de inventá-la. Não cite jurisprudência que não esteja nos documentos.";
+
+/// Templates criados pela migração (ver `migracao`): (nome, descrição, system, conteúdo).
+pub const TEMPLATES_PADRAO: [(&str, &str, Option<&str>, &str); 10] = [
+    (
+        TEMPLATE_QA_DOCUMENTO,
+        "Perguntas e respostas sobre um documento",
+        None,
+        "Com base no documento abaixo, responda à pergunta do usuário. Se a informação não estiver no documento, diga que não pode responder.\n\n<documento>\n{{documento}}\n</documento>\n\nPergunta do Usuário: {{pergunta}}",
+    ),
+    (
+        TEMPLATE_RESUMO_DOCUMENTO,
+        "Resumo automático de documentos enviados",
+        None,
+        "Resuma o seguinte documento jurídico em português, em no máximo 10 linhas, destacando o objeto, as partes e as decisões ou pedidos principais.\n\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_EXTRACAO_DOCUMENTO,
+        "Extração de fatos-chave em JSON",
+        None,
+        "Extraia do documento jurídico abaixo: partes (nome e papel), CPFs, CNPJs, datas (AAAA-MM-DD), valores monetários, prazos, juiz e vara. Use listas vazias ou null quando a informação não existir. Responda somente com JSON.\n\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_PRAZOS_DOCUMENTO,
+        "Detecção de prazos em intimações e decisões",
+        None,
+        "Liste os prazos processuais que o documento abaixo impõe à parte (ex.: \"prazo de 15 dias\", \"intime-se para manifestação\"). Para cada prazo, informe uma descrição curta da providência, o número de dias, se são dias úteis e o trecho copiado literalmente do documento. Se não houver prazos, devolva uma lista vazia.\n\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_RESUMO_PARTE,
+        "Resumo de um trecho de documento longo (etapa map)",
+        None,
+        "O texto abaixo é a parte {{parte}} de {{total_partes}} de um processo judicial. Resuma em português os fatos, pedidos, decisões, datas e valores mencionados nesta parte, sem omitir nomes das partes nem prazos.\n\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_RESUMO_CONSOLIDADO,
+        "Consolidação de resumos parciais (etapa reduce)",
+        None,
+        "Os textos abaixo são resumos, em ordem, de partes consecutivas de um mesmo processo judicial. Consolide-os em um único resumo em português, cronológico e sem repetições, preservando partes, pedidos, decisões, datas, valores e prazos.\n\n<documento>\n{{resumos}}\n</documento>",
+    ),
+    (
+        TEMPLATE_PETICAO_INICIAL,
+        "Minuta de petição inicial a partir dos dados do caso",
+        Some(SYSTEM_MINUTA),
+        "Redija a minuta de uma PETIÇÃO INICIAL em português, com endereçamento ao juízo, qualificação das partes, dos fatos, do direito, dos pedidos, valor da causa, requerimentos finais, local, data e assinatura do advogado.\n\nDados do caso:\n{{dados_caso}}\n\nOrientações do advogado: {{instrucoes}}\n\nDocumentos de apoio:\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_CONTESTACAO,
+        "Minuta de contestação a partir dos dados do caso",
+        Some(SYSTEM_MINUTA),
+        "Redija a minuta de uma CONTESTAÇÃO em português, com endereçamento ao juízo, qualificação do réu, síntese da inicial, preliminares cabíveis, mérito com impugnação específica dos fatos, pedidos, provas a produzir, local, data e assinatura do advogado.\n\nDados do caso:\n{{dados_caso}}\n\nOrientações do advogado: {{instrucoes}}\n\nDocumentos de apoio (inclua a petição inicial da parte contrária):\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_RECURSO,
+        "Minuta de recurso a partir dos dados do caso",
+        Some(SYSTEM_MINUTA),
+        "Redija a minuta de um RECURSO em português (apelação, salvo se as orientações indicarem outro), com petição de interposição e razões recursais: tempestividade, cabimento, síntese da decisão recorrida, razões para a reforma, pedido, local, data e assinatura do advogado.\n\nDados do caso:\n{{dados_caso}}\n\nOrientações do advogado: {{instrucoes}}\n\nDocumentos de apoio (inclua a decisão recorrida):\n<documento>\n{{documento}}\n</documento>",
+    ),
+    (
+        TEMPLATE_CLASSIFICACAO_CASO,
+        "Sugestão de categoria do caso",
+        None,
+        "Classifique o caso jurídico abaixo em exatamente uma das categorias da lista, pelo id. Informe a confiança entre 0 e 1 e uma justificativa curta baseada nos fatos.\n\nCategorias (id: descrição):\n{{categorias}}\n\nDescrição do caso: {{descricao}}\n\nDocumentos iniciais:\n<documento>\n{{documento}}\n</documento>",
+    ),
+];
+
+/// Uma versão de template pronta para uso.
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTemplate {
+    pub id_template: Option<i32>,
+    pub nome: String,
+    /// `None` quando o template embutido é usado por falta de registro no banco.
+    pub id_versao: Option<i32>,
+    pub versao: i32,
+    pub system_prompt: Option<String>,
+    pub conteudo: String,
+    pub variaveis: Vec<String>,
+    pub modelo_padrao: Option<String>,
+    pub temperature: Option<f64>,
+    pub num_ctx: Option<i32>,
+}
+
+impl PromptTemplate {
+    fn from_row(row: &Row) -> Self {
+        PromptTemplate {
+            id_template: Some(row.get("id_template")),
+            nome: row.get("nome"),
+            id_versao: Some(row.get("id_versao")),
+            versao: row.get("versao"),
+            system_prompt: row.get("system_prompt"),
+            conteudo: row.get("conteudo"),
+            variaveis: row.get("variaveis"),
+            modelo_padrao: row.get("modelo_padrao"),
+            temperature: row.get("temperature"),
+            num_ctx: row.get("num_ctx"),
+        }
+    }
+
+    /// Template de `TEMPLATES_PADRAO`, sem registro no banco.
+    pub fn embutido(nome: &str) -> Option<Self> {
+        TEMPLATES_PADRAO.iter().find(|t| t.0 == nome).map(|(nome, _, system, conteudo)| PromptTemplate {
+            id_template: None,
+            nome: nome.to_string(),
+            id_versao: None,
+            versao: 0,
+            system_prompt: system.map(|s| s.to_string()),
+            conteudo: conteudo.to_string(),
+            variaveis: variaveis_do_texto(&format!("{}{}", system.unwrap_or(""), conteudo)),
+            modelo_padrao: None,
+            temperature: None,
+            num_ctx: None,
+        })
+    }
+
+    /// Substitui `{{variavel}}` pelos valores informados. Toda variável usada
+    /// no template precisa estar em `valores`. Templates com texto de
+    /// documento sempre recebem `REGRAS_DOCUMENTO_NAO_CONFIAVEL` no system.
+    pub fn render(&self, valores: &HashMap<&str, String>) -> Result<(Option<String>, String), String> {
+        let faltando: Vec<&String> = self.variaveis.iter().filter(|v| !valores.contains_key(v.as_str())).collect();
+        if !faltando.is_empty() {
+            return Err(format!(
+                "Template '{}' v{} exige variáveis não informadas: {}",
+                self.nome,
+                self.versao,
+                faltando.iter().map(|v| v.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+        }
+
+        let valores: Vec<(&str, String)> = valores
+            .iter()
+            .map(|(nome, valor)| {
+                if VARIAVEIS_NAO_CONFIAVEIS.contains(nome) {
+                    (*nome, escapar_documento(valor))
+                } else {
+                    (*nome, valor.clone())
+                }
+            })
+            .collect();
+        let substituir = |texto: &str| {
+            let mut saida = texto.to_string();
+            for (nome, valor) in &valores {
+                saida = saida.replace(&format!("{{{{{}}}}}", nome), valor);
+            }
+            saida
+        };
+
+        let nao_confiavel = self.variaveis.iter().any(|v| VARIAVEIS_NAO_CONFIAVEIS.contains(&v.as_str()));
+        let system = match (nao_confiavel, self.system_prompt.as_deref()) {
+            (true, Some(system)) => Some(format!("{}\n\n{}", REGRAS_DOCUMENTO_NAO_CONFIAVEL, substituir(system))),
+            (true, None) => Some(REGRAS_DOCUMENTO_NAO_CONFIAVEL.to_string()),
+            (false, system) => system.map(substituir),
+        };
+
+        Ok((system, substituir(&self.conteudo)))
+    }
+
+    pub fn opcoes(&self) -> OpcoesGeracao {
+        OpcoesGeracao {
+            temperature: self.temperature,
+            num_ctx: self.num_ctx.map(|n| n as i64),
+        }
+    }
+
+    /// Modelo solicitado, ou o padrão do template, ou o padrão da instalação.
+    pub fn modelo(&self, solicitado: Option<&str>) -> String {
+        solicitado
+            .filter(|m| !m.trim().is_empty())
+            .map(|m| m.to_string())
+            .or_else(|| self.modelo_padrao.clone())
+            .unwrap_or_else(super::modelo_analise)
+    }
+}
+
+/// Nomes de variáveis `{{nome}}` presentes no texto, sem repetição.
+pub fn variaveis_do_texto(texto: &str) -> Vec<String> {
+    let mut variaveis: Vec<String> = Vec::new();
+    let mut resto = texto;
+    while let Some(inicio) = resto.find("{{") {
+        let depois = &resto[inicio + 2..];
+        let Some(fim) = depois.find("}}") else { break };
+        let nome = depois[..fim].trim();
+        if !nome.is_empty() && nome.chars().all(|c| c.is_alphanumeric() || c == '_') && !variaveis.iter().any(|v| v == nome) {
+            variaveis.push(nome.to_string());
+        }
+        resto = &depois[fim + 2..];
+    }
+    variaveis
+}
+
+const SELECT_VERSAO: &str = "
+    SELECT t.id_template, t.nome, v.id_versao, v.versao, v.system_prompt, v.conteudo, v.variaveis,
+           v.modelo_padrao, v.temperature, v.num_ctx
+    FROM Prompt_Template t
+    INNER JOIN Prompt_Template_Versao v ON v.id_template = t.id_template
+";
+
+/// Carrega o template pelo nome: a versão pedida ou, sem versão, a mais recente.
+/// Templates embutidos são usados quando o nome não existe no banco.
+pub async fn carregar_template(client_db: &DbClient, nome: &str, versao: Option<i32>) -> Result<PromptTemplate, String> {
+    let row = client_db
+        .query_opt(
+            &format!(
+                "{} WHERE t.nome = $1 AND t.ativo AND ($2::INTEGER IS NULL OR v.versao = $2) ORDER BY v.versao DESC LIMIT 1;",
+                SELECT_VERSAO
+            ),
+            &[&nome, &versao],
+        )
+        .await
+        .map_err(|e| format!("Failed to fetch prompt template: {}", e))?;
+
+    match row {
+        Some(row) => Ok(PromptTemplate::from_row(&row)),
+        None if versao.is_none() => PromptTemplate::embutido(nome)
+            .ok_or_else(|| format!("Template de prompt '{}' não encontrado.", nome)),
+        None => Err(format!("Versão {} do template '{}' não encontrada.", versao.unwrap_or_default(), nome)),
+    }
+}
+
+/// Carrega uma versão específica pelo id (usado para reproduzir respostas).
+pub async fn carregar_versao(client_db: &DbClient, id_versao: i32) -> Result<Option<PromptTemplate>, tokio_postgres::Error> {
+    let row = client_db
+        .query_opt(&format!("{} WHERE v.id_versao = $1;", SELECT_VERSAO), &[&id_versao])
+        .await?;
+    Ok(row.map(|r| PromptTemplate::from_row(&r)))
+}
+
+pub struct NovaVersao<'a> {
+    pub system_prompt: Option<&'a str>,
+    pub conteudo: &'a str,
+    pub modelo_padrao: Option<&'a str>,
+    pub temperature: Option<f64>,
+    pub num_ctx: Option<i32>,
+}
+
+/// Grava uma nova versão do template (versões nunca são alteradas).
+pub async fn criar_versao<C: GenericClient>(client_db: &C, id_template: i32, nova: &NovaVersao<'_>) -> Result<(i32, i32), tokio_postgres::Error> {
+    let variaveis = variaveis_do_texto(&format!("{}{}", nova.system_prompt.unwrap_or(""), nova.conteudo));
+    let row = client_db
+        .query_one(
+            "INSERT INTO Prompt_Template_Versao (id_template, versao, system_prompt, conteudo, variaveis, modelo_padrao, temperature, num_ctx)
+             VALUES ($1, (SELECT COALESCE(MAX(versao), 0) + 1 FROM Prompt_Template_Versao WHERE id_template = $1), $2, $3, $4, $5, $6, $7)
+             RETURNING id_versao, versao;",
+            &[&id_template, &nova.system_prompt, &nova.conteudo, &variaveis, &nova.modelo_padrao, &nova.temperature, &nova.num_ctx],
+        )
+        .await?;
+    Ok((row.get("id_versao"), row.get("versao")))
+}
+
+/// Cria os templates padrão que ainda não existem.
+pub async fn semear_templates_padrao(client_db: &DbClient) -> Result<(), tokio_postgres::Error> {
+    for (nome, descricao, system, conteudo) in TEMPLATES_PADRAO.iter() {
+        let row = client_db
+            .query_opt(
+                "INSERT INTO Prompt_Template (nome, descricao) VALUES ($1, $2) ON CONFLICT (nome) DO NOTHING RETURNING id_template;",
+                &[nome, descricao],
+            )
+            .await?;
+
+        if let Some(row) = row {
+            let nova = NovaVersao { system_prompt: *system, conteudo, modelo_padrao: None, temperature: None, num_ctx: None };
+            criar_versao(client_db, row.get("id_template"), &nova).await?;
+        }
+    }
+    Ok(())
+}
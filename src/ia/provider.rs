@@ -0,0 +1,207 @@
+// src/ia/provider.rs
+// Abstração dos provedores de LLM (Ollama, servidores compatíveis com a API da OpenAI, mock).
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tuono_lib::axum::http::StatusCode;
+
+use super::mock::MockProvider;
+use super::ollama::OllamaProvider;
+use super::openai::OpenAiProvider;
+use super::fila::{enfileirar, Identificacao};
+use super::modelos::restringir;
+use super::redacao::redigir;
+use crate::config::ConfigLlm;
+
+#[derive(Debug, Clone)]
+pub enum LlmError {
+    /// Provedor mal configurado (variável de ambiente ausente ou inválida).
+    Configuracao(String),
+    /// Não foi possível falar com o servidor do modelo.
+    Conexao(String),
+    /// O servidor respondeu com status de erro.
+    Api { status: u16, corpo: String },
+    /// A resposta não tinha o formato esperado.
+    Resposta(String),
+    /// A fila do modelo está cheia.
+    Ocupado(String),
+    /// A chamada excedeu `LLM_TIMEOUT_SEGUNDOS`.
+    Tempo(String),
+    /// A requisição foi cancelada pelo cliente.
+    Cancelado,
+    /// O modelo pedido não está em `LLM_MODELOS_PERMITIDOS`.
+    ModeloNaoPermitido(String),
+}
+
+impl fmt::Display for LlmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LlmError::Configuracao(e) => write!(f, "LLM provider configuration error: {}", e),
+            LlmError::Conexao(e) => write!(f, "Failed to connect to LLM provider: {}", e),
+            LlmError::Api { status, corpo } => write!(f, "LLM provider error: Status {}, Body: {}", status, corpo),
+            LlmError::Resposta(e) => write!(f, "Failed to parse LLM provider response: {}", e),
+            LlmError::Ocupado(e) => write!(f, "LLM queue is full: {}", e),
+            LlmError::Tempo(e) => write!(f, "LLM request timed out: {}", e),
+            LlmError::Cancelado => write!(f, "LLM request was cancelled"),
+            LlmError::ModeloNaoPermitido(modelo) => write!(f, "Model '{}' is not allowed (LLM_MODELOS_PERMITIDOS)", modelo),
+        }
+    }
+}
+
+impl From<LlmError> for String {
+    fn from(e: LlmError) -> String {
+        e.to_string()
+    }
+}
+
+impl LlmError {
+    /// Status HTTP a devolver para o frontend.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            LlmError::Configuracao(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            LlmError::Conexao(_) => StatusCode::BAD_GATEWAY,
+            LlmError::Api { status, .. } => StatusCode::from_u16(*status).unwrap_or(StatusCode::BAD_GATEWAY),
+            LlmError::Resposta(_) => StatusCode::BAD_GATEWAY,
+            LlmError::Ocupado(_) => StatusCode::SERVICE_UNAVAILABLE,
+            LlmError::Tempo(_) => StatusCode::GATEWAY_TIMEOUT,
+            // 499: "client closed request"
+            LlmError::Cancelado => StatusCode::from_u16(499).unwrap_or(StatusCode::BAD_REQUEST),
+            LlmError::ModeloNaoPermitido(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+/// Parâmetros de amostragem. Campos não suportados pelo provedor são ignorados.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpcoesGeracao {
+    pub temperature: Option<f64>,
+    /// Só o Ollama aceita o tamanho do contexto por requisição. Nos servidores
+    /// compatíveis com a OpenAI ele é fixado ao subir o servidor (ex.: `-c` no
+    /// llama.cpp, `--max-model-len` no vLLM) e este campo é ignorado.
+    pub num_ctx: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String, // "system", "user" ou "assistant"
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        ChatMessage { role: "system".to_string(), content: content.into() }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        ChatMessage { role: "user".to_string(), content: content.into() }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenerateRequest {
+    pub model: String,
+    pub prompt: String,
+    pub system: Option<String>,
+    /// JSON Schema que a resposta deve seguir.
+    pub format: Option<Value>,
+    pub options: OpcoesGeracao,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    /// JSON Schema que a resposta deve seguir.
+    pub format: Option<Value>,
+    pub options: OpcoesGeracao,
+}
+
+/// Resposta de geração/chat com os metadados que o provedor informar.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LlmResponse {
+    pub text: String,
+    pub model: String,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub total_duration_ns: Option<i64>,
+    pub load_duration_ns: Option<i64>,
+    pub prompt_eval_duration_ns: Option<i64>,
+    pub eval_duration_ns: Option<i64>,
+}
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Nome do provedor, usado em logs e metadados.
+    fn name(&self) -> &'static str;
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError>;
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError>;
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError>;
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError>;
+}
+
+/// Garante que a URL base termina em `/`, para que os caminhos possam ser concatenados.
+pub(crate) fn normalizar_url(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{}/", url)
+    }
+}
+
+/// Monta o provedor configurado para esta instalação:
+///
+/// * `LLM_PROVIDER`: `ollama` (padrão), `openai` ou `mock`;
+/// * `LLM_API_URL`: URL base do servidor (para Ollama, `OLLAMA_API_URL` também é aceita);
+/// * `LLM_API_KEY`: chave enviada como `Bearer` para servidores compatíveis com a OpenAI;
+/// * `LLM_REDACAO`: dados pessoais substituídos antes do envio (ver `redacao`);
+/// * `LLM_MODELOS_PERMITIDOS`: modelos que podem ser usados (ver `modelos`).
+///
+/// As chamadas passam pela fila do processo (ver `fila`).
+pub fn provider_from_env(fetch: Client) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    provider_para_requisicao(fetch, None)
+}
+
+/// Como `provider_from_env`, mas identifica as chamadas na fila, para que o
+/// usuário consulte a posição e cancele.
+pub fn provider_para_requisicao(fetch: Client, identificacao: Option<Identificacao>) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let categorias = crate::config::atual().llm.redacao.clone();
+    Ok(enfileirar(restringir(redigir(provider_base(fetch)?, categorias)), identificacao))
+}
+
+/// URL base do Ollama: `LLM_API_URL` ou `OLLAMA_API_URL`.
+fn url_ollama(config: &ConfigLlm) -> Result<String, LlmError> {
+    config.api_url.clone().ok_or_else(|| LlmError::Configuracao("LLM_API_URL (ou OLLAMA_API_URL) não configurada.".to_string()))
+}
+
+/// Cliente Ollama sem fila nem redação, para as rotas de administração de modelos.
+pub fn ollama_from_env(fetch: Client) -> Result<OllamaProvider, LlmError> {
+    let config = crate::config::atual();
+    if config.llm.provedor != "ollama" {
+        return Err(LlmError::Configuracao(format!("Gerenciamento de modelos só é suportado com LLM_PROVIDER=ollama (atual: '{}').", config.llm.provedor)));
+    }
+    Ok(OllamaProvider::new(fetch, &url_ollama(&config.llm)?))
+}
+
+fn provider_base(fetch: Client) -> Result<Arc<dyn LlmProvider>, LlmError> {
+    let config = crate::config::atual();
+    let llm = &config.llm;
+
+    match llm.provedor.as_str() {
+        "ollama" => Ok(Arc::new(OllamaProvider::new(fetch, &url_ollama(llm)?))),
+        "openai" => {
+            let url = llm.api_url.as_deref().ok_or_else(|| LlmError::Configuracao("LLM_API_URL não configurada.".to_string()))?;
+            let api_key = llm.api_key.as_ref().map(|chave| chave.expor().to_string());
+            Ok(Arc::new(OpenAiProvider::new(fetch, url, api_key)))
+        }
+        "mock" => Ok(Arc::new(MockProvider)),
+        outro => Err(LlmError::Configuracao(format!("LLM_PROVIDER desconhecido: '{}'. Use ollama, openai ou mock.", outro))),
+    }
+}
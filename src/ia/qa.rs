@@ -0,0 +1,34 @@
+// src/ia/qa.rs
+// Perguntas e respostas sobre um documento. É o caminho usado por
+// `POST /api/ollama` e pelo avaliador offline (`avaliar_qa`), para que a
+// avaliação meça exatamente o que o usuário recebe.
+use std::collections::HashMap;
+
+use super::prompts::PromptTemplate;
+use super::provider::{ChatMessage, ChatRequest, LlmError, LlmProvider, LlmResponse};
+
+/// Regras no papel "system" e documento + pergunta no papel "user".
+pub fn mensagens_qa(template: &PromptTemplate, documento: String, pergunta: String, dados_caso: String) -> Result<Vec<ChatMessage>, String> {
+    let valores = HashMap::from([
+        ("documento", documento),
+        ("pergunta", pergunta),
+        ("dados_caso", dados_caso),
+    ]);
+    let (system_prompt, full_prompt) = template.render(&valores)?;
+
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    if let Some(system_prompt) = system_prompt {
+        messages.push(ChatMessage::system(system_prompt));
+    }
+    messages.push(ChatMessage::user(full_prompt));
+    Ok(messages)
+}
+
+pub async fn responder_pergunta(llm: &dyn LlmProvider, template: &PromptTemplate, modelo: String, messages: Vec<ChatMessage>) -> Result<LlmResponse, LlmError> {
+    llm.chat(&ChatRequest {
+        model: modelo,
+        messages,
+        options: template.opcoes(),
+        ..Default::default()
+    }).await
+}
@@ -0,0 +1,323 @@
+// src/ia/redacao.rs
+// Substitui dados pessoais (LGPD) por marcadores reversíveis antes de enviar o
+// texto ao provedor de LLM e restaura os valores originais na resposta.
+//
+// A detecção é por padrões, então só pega o que tem forma reconhecível. Dados de
+// saúde são o caso mais fraco: entram os códigos CID, uma lista de condições
+// sensíveis e o que vem depois de "diagnosticado com", "portador de" e afins;
+// descrições livres de sintomas, laudos e nomes de remédios passam. Para textos
+// com muitos dados de saúde, prefira um provedor local com `LLM_REDACAO=nenhuma`.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::provider::{ChatRequest, GenerateRequest, LlmError, LlmProvider, LlmResponse};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Categoria {
+    Cpf,
+    Cnpj,
+    Rg,
+    Endereco,
+    Cep,
+    Saude,
+    Email,
+    Telefone,
+}
+
+impl Categoria {
+    pub const TODAS: [Categoria; 8] = [
+        Categoria::Cnpj,
+        Categoria::Cpf,
+        Categoria::Rg,
+        Categoria::Endereco,
+        Categoria::Cep,
+        Categoria::Saude,
+        Categoria::Email,
+        Categoria::Telefone,
+    ];
+
+    pub fn nome(&self) -> &'static str {
+        match self {
+            Categoria::Cpf => "cpf",
+            Categoria::Cnpj => "cnpj",
+            Categoria::Rg => "rg",
+            Categoria::Endereco => "endereco",
+            Categoria::Cep => "cep",
+            Categoria::Saude => "saude",
+            Categoria::Email => "email",
+            Categoria::Telefone => "telefone",
+        }
+    }
+
+    fn rotulo(&self) -> &'static str {
+        match self {
+            Categoria::Cpf => "CPF",
+            Categoria::Cnpj => "CNPJ",
+            Categoria::Rg => "RG",
+            Categoria::Endereco => "ENDERECO",
+            Categoria::Cep => "CEP",
+            Categoria::Saude => "SAUDE",
+            Categoria::Email => "EMAIL",
+            Categoria::Telefone => "TELEFONE",
+        }
+    }
+
+    /// Expressões da categoria. Quando há um grupo de captura, só ele é substituído.
+    fn regexes(&self) -> Vec<Regex> {
+        let padroes: &[&str] = match self {
+            Categoria::Cnpj => &[r"\b\d{2}\.?\d{3}\.?\d{3}/?\d{4}-?\d{2}\b"],
+            Categoria::Cpf => &[r"\b\d{3}\.?\d{3}\.?\d{3}-?\d{2}\b"],
+            Categoria::Rg => &[r"(?i)\b(?:RG|registro geral|carteira de identidade)\b[^\d\n]{0,20}(\d[\d.\-]{4,12}[\dxX])"],
+            Categoria::Endereco => &[r"(?i)\b(?:rua|r\.|avenida|av\.|travessa|alameda|rodovia|estrada|praça|largo|servidão)\s+[^,\n]{2,60},?\s*(?:n[º°o.]?\s*)?\d+"],
+            Categoria::Cep => &[r"\b\d{5}-\d{3}\b"],
+            // Na ordem: código CID, condições da lista e o trecho depois de uma
+            // expressão de diagnóstico (sem pegar marcadores já colocados)
+            Categoria::Saude => &[
+                r"(?i)\bCID(?:-?10)?\s*:?\s*[A-Z]\d{2}(?:\.\d{1,2})?\b",
+                r"(?i)\b(?:HIV|AIDS|soropositiv[oa]|hepatite [BC]|c[âa]ncer|neoplasia|tumor|esquizofrenia|transtorno bipolar|depress[ãa]o|ansiedade generalizada|autismo|epilepsia|dependência química|alcoolismo|gesta[çc][ãa]o|gravidez|gestante)\b",
+                r"(?i)\b(?:diagnosticad[oa] com|diagnóstico de|portador(?:a)? de|acometid[oa] de|sofre de|em tratamento (?:de|para))\s+([^\s,.;:\n\[\]()][^,.;:\n\[\]()]{2,59})",
+            ],
+            Categoria::Email => &[r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+"],
+            Categoria::Telefone => &[r"\(\d{2}\)\s?9?\d{4}-?\d{4}\b"],
+        };
+        padroes.iter().map(|padrao| Regex::new(padrao).unwrap()).collect()
+    }
+}
+
+/// Interpreta `LLM_REDACAO`: lista separada por vírgulas (`cpf,cnpj,...`),
+/// `todas` (padrão) ou `nenhuma`. Lida uma vez por `crate::config`.
+pub fn categorias_de(valor: &str) -> Result<Vec<Categoria>, String> {
+    match valor.trim().to_lowercase().as_str() {
+        "todas" | "" => Ok(Categoria::TODAS.to_vec()),
+        "nenhuma" => Ok(Vec::new()),
+        lista => lista
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(|nome| {
+                Categoria::TODAS
+                    .iter()
+                    .copied()
+                    .find(|c| c.nome() == nome)
+                    .ok_or_else(|| format!("Categoria de LLM_REDACAO desconhecida: '{}'.", nome))
+            })
+            .collect(),
+    }
+}
+
+/// Guarda a correspondência marcador -> valor original de uma chamada.
+/// O mesmo valor recebe sempre o mesmo marcador.
+pub struct Redator {
+    regras: Vec<(Categoria, Regex)>,
+    originais: HashMap<String, String>,
+    marcadores: HashMap<String, String>,
+    contagem: HashMap<&'static str, usize>,
+}
+
+impl Redator {
+    pub fn new(categorias: &[Categoria]) -> Self {
+        Redator {
+            regras: categorias.iter().flat_map(|c| c.regexes().into_iter().map(move |regex| (*c, regex))).collect(),
+            originais: HashMap::new(),
+            marcadores: HashMap::new(),
+            contagem: HashMap::new(),
+        }
+    }
+
+    fn marcador_para(&mut self, categoria: Categoria, valor: &str) -> String {
+        if let Some(marcador) = self.marcadores.get(valor) {
+            return marcador.clone();
+        }
+        let n = self.contagem.entry(categoria.rotulo()).or_insert(0);
+        *n += 1;
+        let marcador = format!("[{}_{}]", categoria.rotulo(), n);
+        self.marcadores.insert(valor.to_string(), marcador.clone());
+        self.originais.insert(marcador.clone(), valor.to_string());
+        marcador
+    }
+
+    /// Substitui os dados pessoais do texto por marcadores como `[CPF_1]`.
+    pub fn redigir(&mut self, texto: &str) -> String {
+        let mut saida = texto.to_string();
+        let regras: Vec<(Categoria, Regex)> = self.regras.clone();
+        for (categoria, regex) in regras {
+            let mut resultado = String::with_capacity(saida.len());
+            let mut ultimo = 0;
+            for caps in regex.captures_iter(&saida) {
+                let alvo = caps.get(1).or_else(|| caps.get(0)).unwrap();
+                resultado.push_str(&saida[ultimo..alvo.start()]);
+                resultado.push_str(&self.marcador_para(categoria, alvo.as_str()));
+                ultimo = alvo.end();
+            }
+            resultado.push_str(&saida[ultimo..]);
+            saida = resultado;
+        }
+        saida
+    }
+
+    /// Requisição de geração como o provedor a recebe: prompt e, depois, instrução de sistema.
+    pub fn redigir_generate(&mut self, request: &GenerateRequest) -> GenerateRequest {
+        GenerateRequest {
+            prompt: self.redigir(&request.prompt),
+            system: request.system.as_deref().map(|s| self.redigir(s)),
+            ..request.clone()
+        }
+    }
+
+    /// Requisição de chat como o provedor a recebe.
+    pub fn redigir_chat(&mut self, request: &ChatRequest) -> ChatRequest {
+        let mut redigida = request.clone();
+        for mensagem in redigida.messages.iter_mut() {
+            mensagem.content = self.redigir(&mensagem.content);
+        }
+        redigida
+    }
+
+    /// Devolve os valores originais no lugar dos marcadores. Com `json`, os
+    /// valores são escapados para continuar válidos dentro de strings JSON.
+    pub fn restaurar(&self, texto: &str, json: bool) -> String {
+        let mut saida = texto.to_string();
+        for (marcador, original) in &self.originais {
+            let valor = if json {
+                let escapado = serde_json::to_string(original).unwrap_or_default();
+                escapado[1..escapado.len() - 1].to_string()
+            } else {
+                original.clone()
+            };
+            saida = saida.replace(marcador, &valor);
+        }
+        saida
+    }
+}
+
+/// Provedor que redige os dados pessoais de cada requisição e restaura a resposta.
+pub struct ProviderRedigido {
+    interno: Arc<dyn LlmProvider>,
+    categorias: Vec<Categoria>,
+}
+
+/// Envolve o provedor com a redação configurada; sem categorias, devolve o próprio provedor.
+pub fn redigir(interno: Arc<dyn LlmProvider>, categorias: Vec<Categoria>) -> Arc<dyn LlmProvider> {
+    if categorias.is_empty() {
+        interno
+    } else {
+        Arc::new(ProviderRedigido { interno, categorias })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ProviderRedigido {
+    fn name(&self) -> &'static str {
+        self.interno.name()
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, LlmError> {
+        self.interno.list_models().await
+    }
+
+    async fn generate(&self, request: &GenerateRequest) -> Result<LlmResponse, LlmError> {
+        let mut redator = Redator::new(&self.categorias);
+        let redigida = redator.redigir_generate(request);
+        let mut resposta = self.interno.generate(&redigida).await?;
+        resposta.text = redator.restaurar(&resposta.text, request.format.is_some());
+        Ok(resposta)
+    }
+
+    async fn chat(&self, request: &ChatRequest) -> Result<LlmResponse, LlmError> {
+        let mut redator = Redator::new(&self.categorias);
+        let redigida = redator.redigir_chat(request);
+        let mut resposta = self.interno.chat(&redigida).await?;
+        resposta.text = redator.restaurar(&resposta.text, request.format.is_some());
+        Ok(resposta)
+    }
+
+    async fn embeddings(&self, model: &str, input: &[String]) -> Result<Vec<Vec<f32>>, LlmError> {
+        // Vetores não têm texto a restaurar; os marcadores só evitam o envio dos dados.
+        let mut redator = Redator::new(&self.categorias);
+        let redigido: Vec<String> = input.iter().map(|t| redator.redigir(t)).collect();
+        self.interno.embeddings(model, &redigido).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redigir_tudo(texto: &str) -> (String, Redator) {
+        let mut redator = Redator::new(&Categoria::TODAS);
+        (redator.redigir(texto), redator)
+    }
+
+    #[test]
+    fn troca_cada_categoria_e_restaura() {
+        let texto = "Maria, CPF 123.456.789-09, RG nº 12.345.678-X, da empresa CNPJ 12.345.678/0001-90, \
+                     mora na Rua das Flores, 120, CEP 88010-000, e-mail maria.silva+adv@exemplo.com.br, \
+                     telefone (48) 99999-1234. Atestado com CID-10: F32.1.";
+        let (redigido, redator) = redigir_tudo(texto);
+        for valor in ["123.456.789-09", "12.345.678-X", "12.345.678/0001-90", "Rua das Flores, 120", "88010-000",
+                      "maria.silva+adv@exemplo.com.br", "(48) 99999-1234", "F32.1"] {
+            assert!(!redigido.contains(valor), "{} não foi redigido: {}", valor, redigido);
+        }
+        for marcador in ["[CPF_1]", "[RG_1]", "[CNPJ_1]", "[ENDERECO_1]", "[CEP_1]", "[EMAIL_1]", "[TELEFONE_1]", "[SAUDE_1]"] {
+            assert!(redigido.contains(marcador), "{} ausente: {}", marcador, redigido);
+        }
+        // Só o número do RG é trocado, não o rótulo
+        assert!(redigido.contains("RG nº [RG_1]"));
+        assert_eq!(redator.restaurar(&redigido, false), texto);
+    }
+
+    #[test]
+    fn mesmo_valor_recebe_o_mesmo_marcador() {
+        let (redigido, _) = redigir_tudo("CPF 123.456.789-09 e de novo 12345678909 e 123.456.789-09; outro 987.654.321-00.");
+        assert_eq!(redigido, "CPF [CPF_1] e de novo [CPF_2] e [CPF_1]; outro [CPF_3].");
+    }
+
+    #[test]
+    fn dados_de_saude_alem_do_cid() {
+        let texto = "O autor foi diagnosticado com transtorno de ansiedade, é portador de HIV e sofre de depressão (CID F32).";
+        let (redigido, redator) = redigir_tudo(texto);
+        for valor in ["transtorno de ansiedade", "HIV", "depressão", "F32"] {
+            assert!(!redigido.contains(valor), "{} não foi redigido: {}", valor, redigido);
+        }
+        // O trecho depois de "portador de" já era marcador e não é marcado de novo
+        assert!(redigido.contains("portador de [SAUDE_"));
+        assert!(!redigido.contains("[SAUDE_[") && !redigido.contains("][SAUDE"));
+        assert_eq!(redator.restaurar(&redigido, false), texto);
+    }
+
+    #[test]
+    fn restaurar_em_json_escapa_o_valor() {
+        let mut redator = Redator::new(&[Categoria::Endereco]);
+        let redigido = redator.redigir("Endereço: Rua \"Nova\" Esperança, 45");
+        assert_eq!(redigido, "Endereço: [ENDERECO_1]");
+        let resposta = format!(r#"{{"endereco": "{}"}}"#, "[ENDERECO_1]");
+        let restaurada = redator.restaurar(&resposta, true);
+        let valor: serde_json::Value = serde_json::from_str(&restaurada).unwrap();
+        assert_eq!(valor["endereco"], "Rua \"Nova\" Esperança, 45");
+    }
+
+    #[test]
+    fn so_as_categorias_escolhidas() {
+        let mut redator = Redator::new(&[Categoria::Email]);
+        let redigido = redator.redigir("CPF 123.456.789-09, contato@exemplo.com");
+        assert_eq!(redigido, "CPF 123.456.789-09, [EMAIL_1]");
+    }
+
+    #[test]
+    fn texto_sem_dados_pessoais_fica_igual() {
+        let texto = "Processo em fase de instrução; audiência designada para 10/05/2024 às 14h.";
+        assert_eq!(redigir_tudo(texto).0, texto);
+    }
+
+    #[test]
+    fn categorias_de_llm_redacao() {
+        assert_eq!(categorias_de("").unwrap(), Categoria::TODAS.to_vec());
+        assert_eq!(categorias_de(" Todas ").unwrap(), Categoria::TODAS.to_vec());
+        assert!(categorias_de("nenhuma").unwrap().is_empty());
+        assert_eq!(categorias_de("cpf, saude,").unwrap(), vec![Categoria::Cpf, Categoria::Saude]);
+        assert!(categorias_de("cpf,senha").unwrap_err().contains("'senha'"));
+    }
+}
@@ -0,0 +1,331 @@
+// src/ia/resumo_longo.rs
+// Resumo hierárquico (map-reduce) de documentos maiores que o contexto do modelo.
+// Cada resumo parcial fica em Resumo_Parcial, indexado pelo hash do documento e
+// pelo modelo, então um job interrompido retoma de onde parou.
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio_postgres::{Client as DbClient, Row};
+
+use super::{extrair_texto, hash_hex};
+use super::prompts::{carregar_template, PromptTemplate, TEMPLATE_RESUMO_CONSOLIDADO, TEMPLATE_RESUMO_PARTE};
+use super::provider::{GenerateRequest, LlmProvider};
+use crate::cifra::arquivo_da_linha;
+
+/// Tamanho máximo, em caracteres, de cada parte enviada ao modelo.
+pub const TAMANHO_PARTE: usize = 12_000;
+
+/// Um job em `processando` sem atualização há mais tempo que isso é
+/// considerado interrompido e pode ser retomado.
+const MINUTOS_JOB_PARADO: i32 = 10;
+
+fn quebrar_em_caracteres(texto: &str, max: usize) -> Vec<String> {
+    let chars: Vec<char> = texto.chars().collect();
+    chars.chunks(max).map(|c| c.iter().collect()).collect()
+}
+
+/// Divide o texto em partes de até `max` caracteres, preferindo quebrar
+/// entre parágrafos e depois entre linhas.
+pub fn dividir_em_partes(texto: &str, max: usize) -> Vec<String> {
+    let mut partes: Vec<String> = Vec::new();
+    let mut atual = String::new();
+
+    let adicionar = |bloco: &str, separador: &str, partes: &mut Vec<String>, atual: &mut String| {
+        if !atual.is_empty() && atual.chars().count() + separador.len() + bloco.chars().count() > max {
+            partes.push(std::mem::take(atual));
+        }
+        if !atual.is_empty() {
+            atual.push_str(separador);
+        }
+        atual.push_str(bloco);
+    };
+
+    for paragrafo in texto.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if paragrafo.chars().count() <= max {
+            adicionar(paragrafo, "\n\n", &mut partes, &mut atual);
+            continue;
+        }
+        for linha in paragrafo.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if linha.chars().count() <= max {
+                adicionar(linha, "\n", &mut partes, &mut atual);
+            } else {
+                for pedaco in quebrar_em_caracteres(linha, max) {
+                    adicionar(&pedaco, "", &mut partes, &mut atual);
+                }
+            }
+        }
+    }
+    if !atual.is_empty() {
+        partes.push(atual);
+    }
+    partes
+}
+
+/// Agrupa resumos consecutivos para a próxima etapa. Todo grupo tem pelo
+/// menos dois resumos, o que garante que cada nível é menor que o anterior.
+pub fn agrupar_resumos(resumos: &[String], max: usize) -> Vec<Vec<usize>> {
+    let mut grupos: Vec<Vec<usize>> = Vec::new();
+    let mut atual: Vec<usize> = Vec::new();
+    let mut tamanho = 0;
+
+    for (i, resumo) in resumos.iter().enumerate() {
+        let len = resumo.chars().count();
+        if atual.len() >= 2 && tamanho + len > max {
+            grupos.push(std::mem::take(&mut atual));
+            tamanho = 0;
+        }
+        atual.push(i);
+        tamanho += len;
+    }
+    if atual.len() == 1 && !grupos.is_empty() {
+        grupos.last_mut().unwrap().extend(atual);
+    } else if !atual.is_empty() {
+        grupos.push(atual);
+    }
+    grupos
+}
+
+/// Cria (ou reaproveita) o job de resumo para o conteúdo atual do documento.
+/// Devolve o id do job e o status em que ele está.
+pub async fn registrar_resumo_longo(client_db: &DbClient, id_documento: i32, modelo: &str) -> Result<(i32, String), String> {
+    let row = client_db
+        .query_opt("SELECT arquivo, chave_cifrada, id_chave_mestra FROM Documento WHERE id_documento = $1;", &[&id_documento])
+        .await
+        .map_err(|e| format!("Failed to fetch document: {}", e))?
+        .ok_or_else(|| "Documento não encontrado.".to_string())?;
+    let arquivo = arquivo_da_linha(&row)?.ok_or_else(|| "Documento sem conteúdo.".to_string())?;
+    let hash_documento = hash_hex(&arquivo);
+
+    let row = client_db
+        .query_one(
+            "INSERT INTO Resumo_Longo (id_documento, hash_documento, modelo, status) VALUES ($1, $2, $3, 'pendente')
+             ON CONFLICT (id_documento, hash_documento, modelo) DO UPDATE SET id_documento = EXCLUDED.id_documento
+             RETURNING id_resumo, status;",
+            &[&id_documento, &hash_documento, &modelo],
+        )
+        .await
+        .map_err(|e| format!("Failed to register long summary: {}", e))?;
+
+    Ok((row.get("id_resumo"), row.get("status")))
+}
+
+/// Marca o job como em execução se ninguém mais o estiver processando.
+async fn reivindicar_job(client_db: &DbClient, id_resumo: i32) -> Result<Option<Row>, String> {
+    client_db
+        .query_opt(
+            "UPDATE Resumo_Longo r SET status = 'processando', erro = NULL, data_atualizacao = CURRENT_TIMESTAMP
+             FROM Documento d
+             WHERE r.id_resumo = $1 AND d.id_documento = r.id_documento
+               AND (r.status IN ('pendente', 'erro')
+                    OR (r.status = 'processando' AND r.data_atualizacao < CURRENT_TIMESTAMP - make_interval(mins => $2)))
+             RETURNING r.hash_documento, r.modelo, d.arquivo, d.chave_cifrada, d.id_chave_mestra;",
+            &[&id_resumo, &MINUTOS_JOB_PARADO],
+        )
+        .await
+        .map_err(|e| format!("Failed to claim long summary job: {}", e))
+}
+
+async fn atualizar_progresso(client_db: &DbClient, id_resumo: i32, nivel: i32, partes_nivel: i32, partes_concluidas: i32) {
+    if let Err(e) = client_db
+        .execute(
+            "UPDATE Resumo_Longo SET nivel_atual = $1, partes_nivel = $2, partes_concluidas = $3, data_atualizacao = CURRENT_TIMESTAMP WHERE id_resumo = $4;",
+            &[&nivel, &partes_nivel, &partes_concluidas, &id_resumo],
+        )
+        .await
+    {
+        eprintln!("Failed to update long summary progress: {}", e);
+    }
+}
+
+struct Etapa<'a> {
+    llm: &'a dyn LlmProvider,
+    client_db: &'a DbClient,
+    hash_documento: &'a str,
+    modelo: &'a str,
+    chamadas_llm: i32,
+    chamadas_cache: i32,
+}
+
+impl Etapa<'_> {
+    /// Resume uma parte, usando o cache quando a mesma entrada já foi resumida.
+    async fn resumir(&mut self, template: &PromptTemplate, valores: &HashMap<&str, String>, nivel: i32, indice: i32) -> Result<String, String> {
+        let (system, prompt) = template.render(valores)?;
+        let opcoes = serde_json::to_string(&template.opcoes()).unwrap_or_default();
+        let hash_entrada = hash_hex(format!("{}\u{0}{}\u{0}{}", system.as_deref().unwrap_or(""), prompt, opcoes).as_bytes());
+
+        let cache = self
+            .client_db
+            .query_opt(
+                "SELECT resumo FROM Resumo_Parcial WHERE hash_documento = $1 AND modelo = $2 AND nivel = $3 AND indice = $4 AND hash_entrada = $5;",
+                &[&self.hash_documento, &self.modelo, &nivel, &indice, &hash_entrada],
+            )
+            .await
+            .map_err(|e| format!("Failed to read summary cache: {}", e))?;
+        if let Some(row) = cache {
+            self.chamadas_cache += 1;
+            return Ok(row.get("resumo"));
+        }
+
+        let resposta = self.llm.generate(&GenerateRequest {
+            model: self.modelo.to_string(),
+            prompt,
+            system,
+            options: template.opcoes(),
+            ..Default::default()
+        }).await?;
+        self.chamadas_llm += 1;
+
+        self.client_db
+            .execute(
+                "INSERT INTO Resumo_Parcial (hash_documento, modelo, nivel, indice, hash_entrada, resumo) VALUES ($1, $2, $3, $4, $5, $6)
+                 ON CONFLICT (hash_documento, modelo, nivel, indice) DO UPDATE SET hash_entrada = EXCLUDED.hash_entrada, resumo = EXCLUDED.resumo, data_criacao = CURRENT_TIMESTAMP;",
+                &[&self.hash_documento, &self.modelo, &nivel, &indice, &hash_entrada, &resposta.text],
+            )
+            .await
+            .map_err(|e| format!("Failed to store partial summary: {}", e))?;
+
+        Ok(resposta.text)
+    }
+}
+
+/// Executa (ou retoma) o job `id_resumo`. Retorna `Ok(false)` quando outro
+/// processo já está cuidando dele.
+pub async fn executar_resumo_longo(llm: &dyn LlmProvider, client_db: &DbClient, id_resumo: i32) -> Result<bool, String> {
+    let Some(row) = reivindicar_job(client_db, id_resumo).await? else {
+        return Ok(false);
+    };
+    let hash_documento: String = row.get("hash_documento");
+    let modelo: String = row.get("modelo");
+    let arquivo = arquivo_da_linha(&row);
+
+    let mut etapa = Etapa { llm, client_db, hash_documento: &hash_documento, modelo: &modelo, chamadas_llm: 0, chamadas_cache: 0 };
+
+    let resultado = async {
+        let arquivo = arquivo?.ok_or_else(|| "Documento sem conteúdo.".to_string())?;
+        if hash_hex(&arquivo) != hash_documento {
+            return Err("O arquivo do documento foi substituído; inicie um novo resumo.".to_string());
+        }
+        let texto = extrair_texto(&arquivo)?;
+        let template_parte = carregar_template(client_db, TEMPLATE_RESUMO_PARTE, None).await?;
+        let template_consolidado = carregar_template(client_db, TEMPLATE_RESUMO_CONSOLIDADO, None).await?;
+        let versoes_prompt = json!({
+            TEMPLATE_RESUMO_PARTE: template_parte.id_versao,
+            TEMPLATE_RESUMO_CONSOLIDADO: template_consolidado.id_versao,
+        });
+
+        let partes = dividir_em_partes(&texto, TAMANHO_PARTE);
+        if partes.is_empty() {
+            return Err("Não foi possível extrair texto do documento.".to_string());
+        }
+
+        // Nível 0: resumo de cada parte do documento.
+        let total = partes.len() as i32;
+        let mut resumos: Vec<String> = Vec::with_capacity(partes.len());
+        atualizar_progresso(client_db, id_resumo, 0, total, 0).await;
+        for (i, parte) in partes.into_iter().enumerate() {
+            let valores = HashMap::from([
+                ("documento", parte),
+                ("parte", (i + 1).to_string()),
+                ("total_partes", total.to_string()),
+            ]);
+            resumos.push(etapa.resumir(&template_parte, &valores, 0, i as i32).await?);
+            atualizar_progresso(client_db, id_resumo, 0, total, i as i32 + 1).await;
+        }
+
+        // Níveis seguintes: resumos de resumos até sobrar um só.
+        let mut nivel = 0;
+        while resumos.len() > 1 {
+            nivel += 1;
+            let grupos = agrupar_resumos(&resumos, TAMANHO_PARTE);
+            let total = grupos.len() as i32;
+            atualizar_progresso(client_db, id_resumo, nivel, total, 0).await;
+
+            let mut proximos: Vec<String> = Vec::with_capacity(grupos.len());
+            for (i, grupo) in grupos.iter().enumerate() {
+                let texto_grupo = grupo
+                    .iter()
+                    .map(|&j| format!("--- Resumo {} ---\n{}", j + 1, resumos[j]))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                let valores = HashMap::from([("resumos", texto_grupo)]);
+                proximos.push(etapa.resumir(&template_consolidado, &valores, nivel, i as i32).await?);
+                atualizar_progresso(client_db, id_resumo, nivel, total, i as i32 + 1).await;
+            }
+            resumos = proximos;
+        }
+
+        Ok::<_, String>((resumos.remove(0), versoes_prompt))
+    }
+    .await;
+
+    match resultado {
+        Ok((resumo, versoes_prompt)) => {
+            client_db
+                .execute(
+                    "UPDATE Resumo_Longo SET status = 'concluido', resumo = $1, versoes_prompt = $2, chamadas_llm = chamadas_llm + $3, chamadas_cache = chamadas_cache + $4, data_atualizacao = CURRENT_TIMESTAMP WHERE id_resumo = $5;",
+                    &[&resumo, &versoes_prompt, &etapa.chamadas_llm, &etapa.chamadas_cache, &id_resumo],
+                )
+                .await
+                .map_err(|e| format!("Failed to store long summary: {}", e))?;
+            Ok(true)
+        }
+        Err(e) => {
+            if let Err(db_err) = client_db
+                .execute(
+                    "UPDATE Resumo_Longo SET status = 'erro', erro = $1, chamadas_llm = chamadas_llm + $2, chamadas_cache = chamadas_cache + $3, data_atualizacao = CURRENT_TIMESTAMP WHERE id_resumo = $4;",
+                    &[&e, &etapa.chamadas_llm, &etapa.chamadas_cache, &id_resumo],
+                )
+                .await
+            {
+                eprintln!("Failed to store long summary error: {}", db_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Executa o job em segundo plano.
+pub fn agendar_resumo_longo(llm: Arc<dyn LlmProvider>, id_resumo: i32) {
+    tuono_lib::tokio::spawn(async move {
+        let client_db = match crate::connect_db().await {
+            Ok(client) => client,
+            Err(e) => {
+                eprintln!("Failed to connect to database for long summary: {}", e);
+                return;
+            }
+        };
+
+        match executar_resumo_longo(llm.as_ref(), &client_db, id_resumo).await {
+            Ok(true) => println!("Long summary {} finished", id_resumo),
+            Ok(false) => {}
+            Err(e) => eprintln!("Long summary {} failed: {}", id_resumo, e),
+        }
+    });
+}
+
+pub fn resumo_longo_json(row: &Row) -> Value {
+    let data_inicio: Option<chrono::NaiveDateTime> = row.get("data_inicio");
+    let data_atualizacao: Option<chrono::NaiveDateTime> = row.get("data_atualizacao");
+    json!({
+        "id_resumo": row.get::<_, i32>("id_resumo"),
+        "id_documento": row.get::<_, i32>("id_documento"),
+        "hash_documento": row.get::<_, String>("hash_documento"),
+        "modelo": row.get::<_, String>("modelo"),
+        "status": row.get::<_, String>("status"),
+        "progresso": {
+            "nivel": row.get::<_, i32>("nivel_atual"),
+            "partes_nivel": row.get::<_, i32>("partes_nivel"),
+            "partes_concluidas": row.get::<_, i32>("partes_concluidas"),
+        },
+        "chamadas_llm": row.get::<_, i32>("chamadas_llm"),
+        "chamadas_cache": row.get::<_, i32>("chamadas_cache"),
+        "resumo": row.get::<_, Option<String>>("resumo"),
+        "versoes_prompt": row.get::<_, Option<Value>>("versoes_prompt"),
+        "erro": row.get::<_, Option<String>>("erro"),
+        "data_inicio": data_inicio.map(|d| d.to_string()),
+        "data_atualizacao": data_atualizacao.map(|d| d.to_string()),
+    })
+}
+
+pub const SELECT_RESUMO_LONGO: &str = "SELECT id_resumo, id_documento, hash_documento, modelo, status, nivel_atual, partes_nivel, partes_concluidas, chamadas_llm, chamadas_cache, resumo, versoes_prompt, erro, data_inicio, data_atualizacao FROM Resumo_Longo";
@@ -0,0 +1,148 @@
+// src/ia/saude.rs
+// Saúde do serviço de IA: última lista de modelos conhecida, falhas recentes e
+// reconexão em segundo plano enquanto o provedor estiver fora do ar.
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use reqwest::Client;
+use serde::Serialize;
+
+use super::modelo_analise;
+use super::modelos::mesmo_modelo;
+use super::provider::{provider_from_env, LlmError, LlmProvider};
+
+const INTERVALO_SAUDAVEL: Duration = Duration::from_secs(60);
+const INTERVALO_RECONEXAO_INICIAL: Duration = Duration::from_secs(5);
+const INTERVALO_RECONEXAO_MAXIMO: Duration = Duration::from_secs(60);
+
+/// Situação do serviço de IA enviada ao frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct SituacaoIa {
+    /// "disponivel", "degradado" (provedor no ar sem o modelo padrão), "indisponivel"
+    /// ou "desconhecido" (nenhuma verificação feita ainda).
+    pub status: &'static str,
+    pub mensagem: Option<String>,
+    /// Última lista de modelos obtida com sucesso.
+    pub modelos: Vec<String>,
+    /// `true` quando `modelos` vem do cache porque o provedor não respondeu.
+    pub modelos_em_cache: bool,
+    /// Se o modelo padrão estava entre os instalados na última verificação.
+    pub modelo_padrao_instalado: Option<bool>,
+    pub ultimo_sucesso: Option<DateTime<Local>>,
+    pub ultima_falha: Option<DateTime<Local>>,
+    pub falhas_consecutivas: u32,
+}
+
+static SAUDE: OnceLock<Mutex<SituacaoIa>> = OnceLock::new();
+static MONITOR_INICIADO: AtomicBool = AtomicBool::new(false);
+
+fn estado() -> &'static Mutex<SituacaoIa> {
+    SAUDE.get_or_init(|| {
+        Mutex::new(SituacaoIa {
+            status: "desconhecido",
+            mensagem: None,
+            modelos: Vec::new(),
+            modelos_em_cache: false,
+            modelo_padrao_instalado: None,
+            ultimo_sucesso: None,
+            ultima_falha: None,
+            falhas_consecutivas: 0,
+        })
+    })
+}
+
+pub fn situacao() -> SituacaoIa {
+    estado().lock().map(|s| s.clone()).unwrap_or_else(|e| e.into_inner().clone())
+}
+
+fn registrar_sucesso(modelos: &[String]) {
+    let padrao = modelo_analise();
+    let padrao_instalado = modelos.iter().any(|m| mesmo_modelo(m, &padrao));
+    if let Ok(mut s) = estado().lock() {
+        if s.status == "indisponivel" {
+            eprintln!("LLM provider is reachable again after {} failures", s.falhas_consecutivas);
+        }
+        // As análises automáticas usam o modelo padrão: sem ele o serviço só atende quem escolhe outro modelo
+        if padrao_instalado {
+            s.status = "disponivel";
+            s.mensagem = None;
+        } else {
+            s.status = "degradado";
+            s.mensagem = Some(format!(
+                "O modelo padrão '{}' não está instalado: as análises automáticas vão falhar até ele ser baixado (POST /api/modelos_ia) ou LLM_MODELO_PADRAO ser alterado.",
+                padrao
+            ));
+        }
+        s.modelo_padrao_instalado = Some(padrao_instalado);
+        s.modelos = modelos.to_vec();
+        s.modelos_em_cache = false;
+        s.ultimo_sucesso = Some(Local::now());
+        s.falhas_consecutivas = 0;
+    }
+}
+
+fn registrar_falha(erro: &LlmError) {
+    if let Ok(mut s) = estado().lock() {
+        s.status = "indisponivel";
+        s.mensagem = Some(format!("Serviço de IA indisponível: {}", erro));
+        s.modelos_em_cache = !s.modelos.is_empty();
+        s.ultima_falha = Some(Local::now());
+        s.falhas_consecutivas += 1;
+    }
+}
+
+/// Lista os modelos e atualiza a saúde do serviço. Se o provedor falhar,
+/// devolve a última lista conhecida (marcada como cache) em vez do erro;
+/// o erro só é devolvido quando não há nada em cache.
+pub async fn listar_modelos(llm: &dyn LlmProvider) -> Result<SituacaoIa, LlmError> {
+    match llm.list_models().await {
+        Ok(modelos) => {
+            registrar_sucesso(&modelos);
+            Ok(situacao())
+        }
+        Err(e) => {
+            registrar_falha(&e);
+            let atual = situacao();
+            if atual.modelos.is_empty() { Err(e) } else { Ok(atual) }
+        }
+    }
+}
+
+/// Registra uma falha de configuração (provedor que nem pôde ser montado).
+pub fn marcar_indisponivel(erro: &LlmError) {
+    registrar_falha(erro);
+}
+
+/// Inicia, uma única vez por processo, a verificação periódica do provedor:
+/// a cada minuto quando saudável e com espera crescente (5 s até 1 min) quando fora do ar.
+pub fn iniciar_monitoramento(fetch: Client) {
+    if MONITOR_INICIADO.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tuono_lib::tokio::spawn(async move {
+        let mut espera_reconexao = INTERVALO_RECONEXAO_INICIAL;
+        loop {
+            let resultado = match provider_from_env(fetch.clone()) {
+                Ok(llm) => llm.list_models().await,
+                Err(e) => Err(e),
+            };
+            let espera = match resultado {
+                Ok(modelos) => {
+                    registrar_sucesso(&modelos);
+                    espera_reconexao = INTERVALO_RECONEXAO_INICIAL;
+                    INTERVALO_SAUDAVEL
+                }
+                Err(e) => {
+                    eprintln!("LLM provider health check failed: {}", e);
+                    registrar_falha(&e);
+                    let espera = espera_reconexao;
+                    espera_reconexao = (espera_reconexao * 2).min(INTERVALO_RECONEXAO_MAXIMO);
+                    espera
+                }
+            };
+            tuono_lib::tokio::time::sleep(espera).await;
+        }
+    });
+}
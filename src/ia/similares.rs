@@ -0,0 +1,230 @@
+// src/ia/similares.rs
+// Casos semelhantes por embeddings da descrição do caso, dos andamentos e dos
+// resumos de documentos. Os vetores ficam em Embedding_Cache, indexados pelo
+// hash do texto, e só textos novos ou alterados são enviados ao modelo.
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tokio_postgres::{Client as DbClient, GenericClient};
+
+use super::hash_hex;
+use super::provider::LlmProvider;
+
+/// Modelo de embeddings usado quando `LLM_MODELO_EMBEDDING` não está definida.
+pub const MODELO_EMBEDDING_PADRAO: &str = "nomic-embed-text";
+
+/// Caracteres de cada trecho enviados ao modelo de embeddings.
+const MAX_CARACTERES_TRECHO: usize = 2_000;
+const TAMANHO_LOTE: usize = 32;
+/// Trechos correspondentes devolvidos para cada caso semelhante.
+const TRECHOS_POR_CASO: usize = 3;
+
+pub fn modelo_embedding() -> String {
+    crate::config::atual().llm.modelo_embedding.clone()
+}
+
+/// Texto de um caso que participa da comparação.
+#[derive(Debug, Clone)]
+pub struct Trecho {
+    pub id_caso: i32,
+    /// "descricao", "andamento" ou "resumo_documento".
+    pub origem: &'static str,
+    pub texto: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TrechoCorrespondente {
+    pub origem: &'static str,
+    pub trecho: String,
+    /// Trecho do caso de referência mais próximo deste.
+    pub origem_referencia: &'static str,
+    pub trecho_referencia: String,
+    pub similaridade: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CasoSemelhante {
+    pub id_caso: i32,
+    pub numero_processo: Option<String>,
+    pub descricao: Option<String>,
+    pub similaridade: f32,
+    pub trechos: Vec<TrechoCorrespondente>,
+}
+
+fn cortar(texto: &str) -> String {
+    match texto.char_indices().nth(MAX_CARACTERES_TRECHO) {
+        Some((idx, _)) => texto[..idx].to_string(),
+        None => texto.to_string(),
+    }
+}
+
+/// Trechos de todos os casos (ou só de `ids_caso`): descrição, andamentos e o
+/// resumo mais recente de cada documento.
+pub async fn trechos_dos_casos<C: GenericClient>(client_db: &C, ids_caso: Option<&[i32]>) -> Result<Vec<Trecho>, tokio_postgres::Error> {
+    let rows = client_db
+        .query(
+            "SELECT id_caso, 'descricao' AS origem, descricao AS texto FROM Caso
+             WHERE ($1::INTEGER[] IS NULL OR id_caso = ANY($1))
+             UNION ALL
+             SELECT id_caso, 'andamento', descricao FROM Andamento_processual
+             WHERE ($1::INTEGER[] IS NULL OR id_caso = ANY($1))
+             UNION ALL
+             SELECT id_caso, 'resumo_documento', resumo FROM (
+                 SELECT DISTINCT ON (d.id_documento) d.id_caso, a.resumo
+                 FROM Documento d INNER JOIN Documento_Analise a ON a.id_documento = d.id_documento
+                 WHERE a.status = 'concluida' AND ($1::INTEGER[] IS NULL OR d.id_caso = ANY($1))
+                 ORDER BY d.id_documento, a.data_analise DESC
+             ) ultimos;",
+            &[&ids_caso],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            let texto: Option<String> = row.get("texto");
+            let texto = texto.filter(|t| !t.trim().is_empty())?;
+            let origem = match row.get::<_, &str>("origem") {
+                "descricao" => "descricao",
+                "andamento" => "andamento",
+                _ => "resumo_documento",
+            };
+            Some(Trecho { id_caso: row.get("id_caso"), origem, texto: cortar(texto.trim()) })
+        })
+        .collect())
+}
+
+/// Chave do texto em Embedding_Cache.
+pub fn hash_texto(texto: &str) -> String {
+    hash_hex(texto.as_bytes())
+}
+
+/// Vetores dos textos, na mesma ordem, usando o cache e calculando os que faltam.
+pub async fn vetores(llm: &dyn LlmProvider, client_db: &DbClient, modelo: &str, textos: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let hashes: Vec<String> = textos.iter().map(|t| hash_texto(t)).collect();
+
+    let rows = client_db
+        .query(
+            "SELECT hash_texto, vetor FROM Embedding_Cache WHERE modelo = $1 AND hash_texto = ANY($2);",
+            &[&modelo, &hashes],
+        )
+        .await
+        .map_err(|e| format!("Failed to read embedding cache: {}", e))?;
+    let mut cache: HashMap<String, Vec<f32>> = rows.iter().map(|r| (r.get::<_, String>("hash_texto"), r.get("vetor"))).collect();
+
+    let mut faltando: Vec<(String, String)> = Vec::new();
+    for (hash, texto) in hashes.iter().zip(textos) {
+        if !cache.contains_key(hash) && !faltando.iter().any(|(h, _)| h == hash) {
+            faltando.push((hash.clone(), texto.clone()));
+        }
+    }
+
+    for lote in faltando.chunks(TAMANHO_LOTE) {
+        let entrada: Vec<String> = lote.iter().map(|(_, texto)| texto.clone()).collect();
+        let calculados = llm.embeddings(modelo, &entrada).await.map_err(|e| e.to_string())?;
+        if calculados.len() != lote.len() {
+            return Err(format!("Embedding model returned {} vectors for {} inputs", calculados.len(), lote.len()));
+        }
+        for ((hash, _), vetor) in lote.iter().zip(calculados) {
+            if let Err(e) = client_db
+                .execute(
+                    "INSERT INTO Embedding_Cache (hash_texto, modelo, vetor) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
+                    &[hash, &modelo, &vetor],
+                )
+                .await
+            {
+                eprintln!("Failed to write embedding cache: {}", e);
+            }
+            cache.insert(hash.clone(), vetor);
+        }
+    }
+
+    Ok(hashes.iter().map(|h| cache.get(h).cloned().unwrap_or_default()).collect())
+}
+
+pub fn similaridade_cosseno(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut produto, mut norma_a, mut norma_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        produto += x * y;
+        norma_a += x * x;
+        norma_b += y * y;
+    }
+    if norma_a == 0.0 || norma_b == 0.0 { 0.0 } else { produto / (norma_a.sqrt() * norma_b.sqrt()) }
+}
+
+/// Casos mais parecidos com `id_caso`, entre os de `candidatos` (todos, com `None`).
+/// A nota de cada caso é a maior similaridade entre um trecho dele e um trecho
+/// do caso de referência; os trechos que mais contribuíram vão junto.
+pub async fn casos_semelhantes(
+    llm: &dyn LlmProvider,
+    client_db: &DbClient,
+    id_caso: i32,
+    candidatos: Option<&[i32]>,
+    limite: usize,
+) -> Result<Vec<CasoSemelhante>, String> {
+    let modelo = modelo_embedding();
+    let referencia = trechos_dos_casos(client_db, Some(&[id_caso][..]))
+        .await
+        .map_err(|e| format!("Failed to fetch case passages: {}", e))?;
+    if referencia.is_empty() {
+        return Ok(Vec::new());
+    }
+    let outros: Vec<Trecho> = trechos_dos_casos(client_db, candidatos)
+        .await
+        .map_err(|e| format!("Failed to fetch case passages: {}", e))?
+        .into_iter()
+        .filter(|t| t.id_caso != id_caso)
+        .collect();
+
+    let vetores_referencia = vetores(llm, client_db, &modelo, &referencia.iter().map(|t| t.texto.clone()).collect::<Vec<_>>()).await?;
+    let vetores_outros = vetores(llm, client_db, &modelo, &outros.iter().map(|t| t.texto.clone()).collect::<Vec<_>>()).await?;
+
+    let mut por_caso: HashMap<i32, Vec<TrechoCorrespondente>> = HashMap::new();
+    for (trecho, vetor) in outros.iter().zip(&vetores_outros) {
+        let melhor = referencia
+            .iter()
+            .zip(&vetores_referencia)
+            .map(|(ref_trecho, ref_vetor)| (ref_trecho, similaridade_cosseno(vetor, ref_vetor)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((ref_trecho, similaridade)) = melhor {
+            por_caso.entry(trecho.id_caso).or_default().push(TrechoCorrespondente {
+                origem: trecho.origem,
+                trecho: trecho.texto.clone(),
+                origem_referencia: ref_trecho.origem,
+                trecho_referencia: ref_trecho.texto.clone(),
+                similaridade,
+            });
+        }
+    }
+
+    let mut ranking: Vec<(i32, f32, Vec<TrechoCorrespondente>)> = por_caso
+        .into_iter()
+        .map(|(id, mut trechos)| {
+            trechos.sort_by(|a, b| b.similaridade.total_cmp(&a.similaridade));
+            trechos.truncate(TRECHOS_POR_CASO);
+            let nota = trechos.first().map(|t| t.similaridade).unwrap_or(0.0);
+            (id, nota, trechos)
+        })
+        .collect();
+    ranking.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranking.truncate(limite);
+
+    let ids: Vec<i32> = ranking.iter().map(|(id, _, _)| *id).collect();
+    let rows = client_db
+        .query("SELECT id_caso, numero_processo, descricao FROM Caso WHERE id_caso = ANY($1);", &[&ids])
+        .await
+        .map_err(|e| format!("Failed to fetch similar cases: {}", e))?;
+    let mut dados: HashMap<i32, (Option<String>, Option<String>)> =
+        rows.iter().map(|r| (r.get("id_caso"), (r.get("numero_processo"), r.get("descricao")))).collect();
+
+    Ok(ranking
+        .into_iter()
+        .map(|(id, similaridade, trechos)| {
+            let (numero_processo, descricao) = dados.remove(&id).unwrap_or((None, None));
+            CasoSemelhante { id_caso: id, numero_processo, descricao, similaridade, trechos }
+        })
+        .collect())
+}
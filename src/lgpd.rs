@@ -0,0 +1,313 @@
+// src/lgpd.rs
+// Direitos do titular (LGPD, art. 18): exportação dos dados de um cliente e
+// anonimização.
+//
+// A exportação monta um ZIP com o cadastro, os casos, os andamentos e os
+// documentos do cliente (metadados em JSON e os arquivos originais).
+//
+// A anonimização troca os dados pessoais do cadastro por pseudônimos e apaga o
+// conteúdo dos casos que não precisam mais ser guardados. Casos em andamento ou
+// encerrados há menos de `LGPD_RETENCAO_ANOS` (padrão 5, o prazo de prescrição
+// da responsabilidade do advogado) ficam como estão, e o relatório diz por quê.
+use std::io::{Cursor, Write};
+
+use chrono::{Local, Months, NaiveDate};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio_postgres::GenericClient;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::cifra::arquivo_da_linha;
+use crate::ia::similares::{hash_texto, trechos_dos_casos};
+
+pub const RETENCAO_ANOS_PADRAO: u32 = 5;
+
+/// Erro de `anonimizar_cliente` para um cliente já anonimizado.
+pub const ERRO_JA_ANONIMIZADO: &str = "O cliente já foi anonimizado.";
+
+/// Texto que substitui descrições apagadas.
+const TEXTO_ANONIMIZADO: &str = "[anonimizado]";
+
+const MOTIVO_CASO_EM_ANDAMENTO: &str =
+    "Caso em andamento: os dados são necessários ao exercício regular de direitos em processo judicial (LGPD, art. 7º, VI, e art. 16, I).";
+const MOTIVO_AUDITORIA: &str =
+    "A trilha de auditoria só aceita inserções e serve de prova das operações realizadas (LGPD, art. 37); os registros anteriores continuam com os dados da época.";
+
+pub fn retencao_anos() -> u32 {
+    crate::config::atual().lgpd.retencao_anos
+}
+
+async fn linhas_json<C: GenericClient>(client: &C, query: &str, id_cliente: i32) -> Result<Vec<Value>, String> {
+    let rows = client.query(query, &[&id_cliente]).await.map_err(|e| e.to_string())?;
+    Ok(rows.iter().map(|row| row.get::<_, Value>(0)).collect())
+}
+
+fn nome_no_zip(id_documento: i32, nome_arquivo: Option<&str>) -> String {
+    let nome = nome_arquivo.unwrap_or("arquivo").replace(['/', '\\'], "_");
+    format!("documentos/{}_{}", id_documento, nome)
+}
+
+/// ZIP com os dados do cliente, ou `None` se ele não existir.
+pub async fn exportar_cliente<C: GenericClient>(client: &C, id_cliente: i32) -> Result<Option<Vec<u8>>, String> {
+    let cliente = client
+        .query_opt(
+            "SELECT to_jsonb(c) || jsonb_build_object('pessoa_fisica', to_jsonb(pf), 'pessoa_juridica', to_jsonb(pj))
+             FROM Cliente c
+             LEFT JOIN Pessoa_Fisica pf ON pf.id_cliente = c.id_cliente
+             LEFT JOIN Pessoa_Juridica pj ON pj.id_cliente = c.id_cliente
+             WHERE c.id_cliente = $1;",
+            &[&id_cliente],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(cliente) = cliente.map(|row| row.get::<_, Value>(0)) else {
+        return Ok(None);
+    };
+
+    let casos = linhas_json(client, "SELECT to_jsonb(c) FROM Caso c WHERE c.id_cliente = $1 ORDER BY c.id_caso;", id_cliente).await?;
+    let andamentos = linhas_json(
+        client,
+        "SELECT to_jsonb(a) FROM Andamento_processual a
+         INNER JOIN Caso c ON c.id_caso = a.id_caso
+         WHERE c.id_cliente = $1 ORDER BY a.id_caso, a.data_andamento, a.id_andamento;",
+        id_cliente,
+    )
+    .await?;
+    let documentos = client
+        .query(
+            "SELECT (to_jsonb(d) - 'arquivo' - 'chave_cifrada' - 'id_chave_mestra') AS documento, d.id_documento, d.nome_arquivo,
+                    d.arquivo, d.chave_cifrada, d.id_chave_mestra
+             FROM Documento d
+             INNER JOIN Caso c ON c.id_caso = d.id_caso
+             WHERE c.id_cliente = $1 ORDER BY d.id_documento;",
+            &[&id_cliente],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let opcoes = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut adicionar = |nome: &str, conteudo: &[u8]| -> Result<(), String> {
+        zip.start_file(nome, opcoes).map_err(|e| e.to_string())?;
+        zip.write_all(conteudo).map_err(|e| e.to_string())
+    };
+
+    let mut metadados_documentos = Vec::new();
+    for row in &documentos {
+        let mut documento: Value = row.get("documento");
+        if let Some(arquivo) = arquivo_da_linha(row)? {
+            let caminho = nome_no_zip(row.get("id_documento"), row.get("nome_arquivo"));
+            adicionar(&caminho, &arquivo)?;
+            documento["arquivo_no_zip"] = json!(caminho);
+        }
+        metadados_documentos.push(documento);
+    }
+
+    let manifesto = json!({
+        "id_cliente": id_cliente,
+        "data_exportacao": Local::now().to_rfc3339(),
+        "conteudo": {
+            "cliente.json": "Cadastro do cliente, com CPF (pessoa física) ou CNPJ (pessoa jurídica).",
+            "casos.json": "Casos do cliente.",
+            "andamentos.json": "Andamentos processuais dos casos.",
+            "documentos.json": "Metadados dos documentos; os arquivos estão na pasta documentos/.",
+        },
+    });
+    for (nome, valor) in [
+        ("manifesto.json", manifesto),
+        ("cliente.json", cliente),
+        ("casos.json", json!(casos)),
+        ("andamentos.json", json!(andamentos)),
+        ("documentos.json", json!(metadados_documentos)),
+    ] {
+        let conteudo = serde_json::to_vec_pretty(&valor).map_err(|e| e.to_string())?;
+        adicionar(nome, &conteudo)?;
+    }
+
+    let cursor = zip.finish().map_err(|e| e.to_string())?;
+    Ok(Some(cursor.into_inner()))
+}
+
+/// Algo que a anonimização manteve e o motivo.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemMantido {
+    pub item: String,
+    pub id_caso: Option<i32>,
+    pub motivo: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatorioAnonimizacao {
+    pub id_cliente: i32,
+    /// `false` na prévia: o relatório mostra o que seria feito.
+    pub aplicado: bool,
+    pub campos_pseudonimizados: Vec<&'static str>,
+    /// Casos cujas descrições, andamentos, audiências e peças foram apagados.
+    pub casos_anonimizados: Vec<i32>,
+    pub documentos_removidos: Vec<i32>,
+    pub mantidos: Vec<ItemMantido>,
+}
+
+/// Por que o caso ainda precisa ser guardado, se precisar.
+fn motivo_retencao(data_fechamento: Option<NaiveDate>, hoje: NaiveDate, anos: u32) -> Option<String> {
+    let Some(fechamento) = data_fechamento else {
+        return Some(MOTIVO_CASO_EM_ANDAMENTO.to_string());
+    };
+    let limite = fechamento.checked_add_months(Months::new(anos * 12)).unwrap_or(NaiveDate::MAX);
+    (hoje < limite).then(|| {
+        format!(
+            "Caso encerrado em {}: guardado até {} pelo prazo de prescrição da responsabilidade do advogado (Lei 8.906/94, art. 25) e para cumprimento de obrigação legal (LGPD, art. 16, I).",
+            fechamento.format("%d/%m/%Y"),
+            limite.format("%d/%m/%Y"),
+        )
+    })
+}
+
+/// Anonimiza o cliente, ou só monta o relatório quando `aplicar` é `false`.
+/// Deve rodar numa transação. Devolve `None` se o cliente não existir.
+pub async fn anonimizar_cliente<C: GenericClient>(client: &C, id_cliente: i32, aplicar: bool) -> Result<Option<RelatorioAnonimizacao>, String> {
+    let cliente = client
+        .query_opt(
+            "SELECT c.data_anonimizacao IS NOT NULL AS anonimizado, pf.id_cliente IS NOT NULL AS fisica, pj.id_cliente IS NOT NULL AS juridica
+             FROM Cliente c
+             LEFT JOIN Pessoa_Fisica pf ON pf.id_cliente = c.id_cliente
+             LEFT JOIN Pessoa_Juridica pj ON pj.id_cliente = c.id_cliente
+             WHERE c.id_cliente = $1 FOR UPDATE OF c;",
+            &[&id_cliente],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let Some(cliente) = cliente else {
+        return Ok(None);
+    };
+    if cliente.get::<_, bool>("anonimizado") {
+        return Err(ERRO_JA_ANONIMIZADO.to_string());
+    }
+
+    let mut campos_pseudonimizados = vec!["nome", "email", "telefone", "endereco"];
+    if cliente.get::<_, bool>("fisica") {
+        campos_pseudonimizados.push("cpf");
+    }
+    if cliente.get::<_, bool>("juridica") {
+        campos_pseudonimizados.push("cnpj");
+    }
+
+    let casos = client
+        .query(
+            "SELECT id_caso, numero_processo, data_fechamento FROM Caso WHERE id_cliente = $1 ORDER BY id_caso;",
+            &[&id_cliente],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let hoje = Local::now().date_naive();
+    let anos = retencao_anos();
+    let mut casos_anonimizados = Vec::new();
+    let mut mantidos = Vec::new();
+    for row in &casos {
+        let id_caso: i32 = row.get("id_caso");
+        match motivo_retencao(row.get("data_fechamento"), hoje, anos) {
+            Some(motivo) => {
+                let numero = row.get::<_, Option<String>>("numero_processo").unwrap_or_else(|| "sem número".to_string());
+                mantidos.push(ItemMantido {
+                    item: format!("Caso {} (processo {}), com andamentos e documentos", id_caso, numero),
+                    id_caso: Some(id_caso),
+                    motivo,
+                });
+            }
+            None => casos_anonimizados.push(id_caso),
+        }
+    }
+    mantidos.push(ItemMantido {
+        item: "Registros de auditoria do cliente, dos casos e dos documentos".to_string(),
+        id_caso: None,
+        motivo: MOTIVO_AUDITORIA.to_string(),
+    });
+    if !casos_anonimizados.is_empty() {
+        mantidos.push(ItemMantido {
+            item: "Número do processo, datas, advogado, vara e situação dos casos anonimizados".to_string(),
+            id_caso: None,
+            motivo: "Dados do processo judicial, que é público, sem dados pessoais do titular; mantêm a contagem e o histórico do escritório.".to_string(),
+        });
+    }
+
+    let documentos_removidos: Vec<i32> = client
+        .query("SELECT id_documento FROM Documento WHERE id_caso = ANY($1) ORDER BY id_documento;", &[&casos_anonimizados])
+        .await
+        .map_err(|e| e.to_string())?
+        .iter()
+        .map(|row| row.get("id_documento"))
+        .collect();
+
+    if aplicar {
+        aplicar_anonimizacao(client, id_cliente, &casos_anonimizados, &documentos_removidos).await.map_err(|e| e.to_string())?;
+    }
+
+    Ok(Some(RelatorioAnonimizacao {
+        id_cliente,
+        aplicado: aplicar,
+        campos_pseudonimizados,
+        casos_anonimizados,
+        documentos_removidos,
+        mantidos,
+    }))
+}
+
+async fn aplicar_anonimizacao<C: GenericClient>(
+    client: &C,
+    id_cliente: i32,
+    casos: &[i32],
+    documentos: &[i32],
+) -> Result<(), tokio_postgres::Error> {
+    // CPF e CNPJ são únicos e obrigatórios: o pseudônimo usa o id (14 caracteres)
+    let pseudonimo = format!("ANON{:010}", id_cliente);
+    client
+        .execute(
+            "UPDATE Cliente SET nome = $2, email = NULL, telefone = NULL, endereco = NULL, data_anonimizacao = CURRENT_TIMESTAMP
+             WHERE id_cliente = $1;",
+            &[&id_cliente, &format!("Cliente anonimizado #{}", id_cliente)],
+        )
+        .await?;
+    client.execute("UPDATE Pessoa_Fisica SET cpf = $2 WHERE id_cliente = $1;", &[&id_cliente, &pseudonimo]).await?;
+    client.execute("UPDATE Pessoa_Juridica SET cnpj = $2 WHERE id_cliente = $1;", &[&id_cliente, &pseudonimo]).await?;
+
+    if casos.is_empty() {
+        return Ok(());
+    }
+
+    // Os vetores de descrições, andamentos e resumos saem do cache de
+    // embeddings; um texto igual em outro caso só é recalculado
+    let hashes: Vec<String> = trechos_dos_casos(client, Some(casos)).await?.iter().map(|t| hash_texto(&t.texto)).collect();
+    client.execute("DELETE FROM Embedding_Cache WHERE hash_texto = ANY($1);", &[&hashes]).await?;
+
+    // Resumos parciais e chamadas de IA guardam trechos dos documentos e dos
+    // casos. O cache de resumos é por conteúdo: só sai o que nenhum documento
+    // mantido usa. Análises, resumos longos, minutas e sugestões de tarefa saem
+    // junto com o documento (ON DELETE CASCADE).
+    client
+        .execute(
+            "DELETE FROM Resumo_Parcial p
+             WHERE p.hash_documento IN (SELECT hash_documento FROM Resumo_Longo WHERE id_documento = ANY($1))
+               AND NOT EXISTS (SELECT 1 FROM Resumo_Longo r WHERE r.hash_documento = p.hash_documento AND r.id_documento <> ALL($1));",
+            &[&documentos],
+        )
+        .await?;
+    client
+        .execute(
+            "UPDATE Llm_Chamada SET prompt = NULL, resposta = NULL WHERE ids_documento && $1 OR ids_caso && $2;",
+            &[&documentos, &casos],
+        )
+        .await?;
+    client.execute("DELETE FROM Documento WHERE id_documento = ANY($1);", &[&documentos]).await?;
+
+    client.execute("UPDATE Caso SET descricao = $2 WHERE id_caso = ANY($1);", &[&casos, &TEXTO_ANONIMIZADO]).await?;
+    client.execute("UPDATE Andamento_processual SET descricao = $2 WHERE id_caso = ANY($1);", &[&casos, &TEXTO_ANONIMIZADO]).await?;
+    client
+        .execute("UPDATE Audiencia SET descricao = $2, endereco = NULL WHERE id_caso = ANY($1);", &[&casos, &TEXTO_ANONIMIZADO])
+        .await?;
+    client.execute("UPDATE Pecas SET descricao = $2 WHERE id_caso = ANY($1);", &[&casos, &TEXTO_ANONIMIZADO]).await?;
+    client.execute("UPDATE Tarefa SET descricao = $2 WHERE id_caso = ANY($1);", &[&casos, &TEXTO_ANONIMIZADO]).await?;
+    client.execute("UPDATE Categoria_Sugerida SET justificativa = NULL WHERE id_caso = ANY($1);", &[&casos]).await?;
+    Ok(())
+}
@@ -1,21 +1,22 @@
 use std::collections::HashMap;
 
-use tokio_postgres::{Client, Error};
-use tuono_lib::tokio;
+use tokio_postgres::Client;
 use urlencoding;
 
-pub async fn connect_db() -> Result<Client, Error> {
-    let database_string = std::env::var("DATABASE_URL").expect("Fail to read DATABASE_URL env");
-    let database_url: &str = &database_string;
-    let (client, connection) = tokio_postgres::connect(database_url, tokio_postgres::NoTls).await?;
-
-    tokio::spawn(async move {
-        if let Err(e) = connection.await {
-            eprintln!("Database connection error: {}", e);
-        }
-    });
-
-    Ok(client)
+pub mod acesso;
+pub mod auditoria;
+pub mod auth;
+pub mod cifra;
+pub mod conexao;
+pub mod config;
+pub mod ia;
+pub mod lgpd;
+pub mod limite;
+pub mod migracao;
+
+/// Conexão com o banco de `DATABASE_URL`, com o TLS de `conexao`.
+pub async fn connect_db() -> Result<Client, String> {
+    conexao::conectar(config::atual().url_banco()?).await
 }
 
 pub fn extract_query_values(query: &str) -> Result<HashMap<String, String>, String> {
@@ -0,0 +1,284 @@
+// src/limite.rs
+// Limite de requisições por usuário ou IP, com balde de fichas. Cada política
+// tem uma rajada (capacidade do balde) e uma recarga por minuto; cada
+// requisição gasta uma ficha e, com o balde vazio, a API responde `429` com
+// `Retry-After`.
+//
+//   LIMITE_ARMAZENAMENTO            memoria (padrão), banco ou desativado
+//   LIMITE_<POLITICA>_POR_MINUTO    recarga do balde (IA, AUTENTICACAO, LEITURA)
+//   LIMITE_<POLITICA>_RAJADA        requisições seguidas antes de limitar
+//
+// Em memória cada instância conta sozinha; com `banco` os baldes ficam na
+// tabela Limite_Requisicao e valem para todas as instâncias. Se o banco falhar,
+// a requisição passa: o limite não pode derrubar a aplicação.
+//
+// As rotas limitam pelo usuário que o `exigir_permissao` já carregou
+// (`limitar_usuario`); o login, que ainda não tem usuário, pelo IP e e-mail.
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use serde_json::json;
+use tokio_postgres::Client as DbClient;
+use tuono_lib::axum::http::{header, StatusCode};
+use tuono_lib::axum::response::{IntoResponse, Json, Response};
+
+use crate::auth::UsuarioSessao;
+use crate::connect_db;
+
+/// Em memória, acima disso os baldes usados há mais tempo são descartados (um
+/// balde descartado volta cheio).
+const MAXIMO_BALDES_MEMORIA: usize = 10_000;
+/// No banco, a limpeza dos baldes antigos roda a cada tantas requisições.
+const LIMPEZA_A_CADA: u64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Politica {
+    /// Chamadas ao LLM: caras, então o limite é estrito.
+    Ia,
+    /// Login, por IP e por e-mail.
+    Autenticacao,
+    /// Leituras pesadas (download de documentos, exportações).
+    Leitura,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Armazenamento {
+    Memoria,
+    Banco,
+    Desativado,
+}
+
+struct Limites {
+    por_minuto: f64,
+    rajada: f64,
+}
+
+impl Politica {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Politica::Ia => "ia",
+            Politica::Autenticacao => "autenticacao",
+            Politica::Leitura => "leitura",
+        }
+    }
+
+    fn limites(&self) -> Limites {
+        let config = crate::config::atual();
+        let limites = match self {
+            Politica::Ia => config.limite.ia,
+            Politica::Autenticacao => config.limite.autenticacao,
+            Politica::Leitura => config.limite.leitura,
+        };
+        Limites { por_minuto: limites.por_minuto as f64, rajada: limites.rajada as f64 }
+    }
+}
+
+fn armazenamento() -> Armazenamento {
+    crate::config::atual().limite.armazenamento
+}
+
+/// Recarrega o balde pelo tempo decorrido e tenta gastar uma ficha. Devolve as
+/// fichas restantes e, se não havia ficha, os segundos até a próxima.
+fn gastar(fichas: f64, decorrido_segundos: f64, limites: &Limites) -> (f64, Option<u64>) {
+    let fichas = (fichas + decorrido_segundos * limites.por_minuto / 60.0).min(limites.rajada);
+    if fichas >= 1.0 {
+        (fichas - 1.0, None)
+    } else {
+        let espera = ((1.0 - fichas) * 60.0 / limites.por_minuto).ceil().max(1.0);
+        (fichas, Some(espera as u64))
+    }
+}
+
+struct Balde {
+    fichas: f64,
+    atualizado: Instant,
+    /// Posição em `BaldesMemoria::por_uso`.
+    uso: u64,
+}
+
+/// Baldes em memória, com a ordem de uso para descartar os mais antigos sem
+/// percorrer o mapa.
+#[derive(Default)]
+struct BaldesMemoria {
+    baldes: HashMap<String, Balde>,
+    /// Contador de uso -> chave; o primeiro é o menos usado recentemente.
+    por_uso: BTreeMap<u64, String>,
+    proximo_uso: u64,
+}
+
+impl BaldesMemoria {
+    fn consumir(&mut self, chave: &str, limites: &Limites, agora: Instant, maximo: usize) -> Option<u64> {
+        let uso = self.proximo_uso;
+        self.proximo_uso += 1;
+        let (fichas, decorrido) = match self.baldes.get(chave) {
+            Some(balde) => {
+                self.por_uso.remove(&balde.uso);
+                (balde.fichas, agora.duration_since(balde.atualizado).as_secs_f64())
+            }
+            None => (limites.rajada, 0.0),
+        };
+        let (fichas, espera) = gastar(fichas, decorrido, limites);
+        self.baldes.insert(chave.to_string(), Balde { fichas, atualizado: agora, uso });
+        self.por_uso.insert(uso, chave.to_string());
+
+        while self.baldes.len() > maximo {
+            let Some((_, antiga)) = self.por_uso.pop_first() else { break };
+            self.baldes.remove(&antiga);
+        }
+        espera
+    }
+}
+
+fn consumir_memoria(chave: &str, limites: &Limites) -> Option<u64> {
+    static BALDES: OnceLock<Mutex<BaldesMemoria>> = OnceLock::new();
+    let mut baldes = BALDES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    baldes.consumir(chave, limites, Instant::now(), MAXIMO_BALDES_MEMORIA)
+}
+
+async fn consumir_banco(client_db: &mut DbClient, chave: &str, limites: &Limites) -> Result<Option<u64>, String> {
+    let transaction = client_db.transaction().await.map_err(|e| e.to_string())?;
+    transaction
+        .execute(
+            "INSERT INTO Limite_Requisicao (chave, fichas, atualizado_em) VALUES ($1, $2, clock_timestamp()) ON CONFLICT (chave) DO NOTHING;",
+            &[&chave, &limites.rajada],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    // O tempo decorrido vem do relógio do banco, o mesmo para todas as instâncias
+    let row = transaction
+        .query_one(
+            "SELECT fichas, EXTRACT(EPOCH FROM clock_timestamp() - atualizado_em)::FLOAT8 AS decorrido
+             FROM Limite_Requisicao WHERE chave = $1 FOR UPDATE;",
+            &[&chave],
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    let (fichas, espera) = gastar(row.get("fichas"), row.get::<_, f64>("decorrido").max(0.0), limites);
+    transaction
+        .execute("UPDATE Limite_Requisicao SET fichas = $1, atualizado_em = clock_timestamp() WHERE chave = $2;", &[&fichas, &chave])
+        .await
+        .map_err(|e| e.to_string())?;
+    transaction.commit().await.map_err(|e| e.to_string())?;
+    Ok(espera)
+}
+
+async fn limpar_banco(client_db: &DbClient) {
+    static CHAMADAS: AtomicU64 = AtomicU64::new(0);
+    if !CHAMADAS.fetch_add(1, Ordering::Relaxed).is_multiple_of(LIMPEZA_A_CADA) {
+        return;
+    }
+    // Balde apagado volta cheio; com os limites usuais, uma hora parado já o encheu
+    if let Err(e) = client_db
+        .execute("DELETE FROM Limite_Requisicao WHERE atualizado_em < clock_timestamp() - INTERVAL '1 hour';", &[])
+        .await
+    {
+        eprintln!("Failed to clean up rate limit buckets: {}", e);
+    }
+}
+
+fn resposta_limite(segundos: u64) -> Response {
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, segundos.to_string())],
+        Json(json!({
+            "error": format!("Muitas requisições. Tente novamente em {} segundos.", segundos),
+            "retry_after": segundos,
+        })),
+    )
+        .into_response()
+}
+
+/// Gasta uma ficha do balde de cada chave na política. Com algum balde vazio,
+/// devolve a resposta 429 com a maior espera.
+pub async fn limitar_chaves(politica: Politica, chaves: &[String]) -> Result<(), Response> {
+    let limites = politica.limites();
+    let chaves: Vec<String> = chaves.iter().map(|chave| format!("{}:{}", politica.as_str(), chave)).collect();
+
+    let espera = match armazenamento() {
+        Armazenamento::Desativado => return Ok(()),
+        Armazenamento::Memoria => chaves.iter().map(|chave| consumir_memoria(chave, &limites)).max().flatten(),
+        Armazenamento::Banco => {
+            let resultado = async {
+                let mut client_db = connect_db().await?;
+                let mut espera = None;
+                for chave in &chaves {
+                    espera = espera.max(consumir_banco(&mut client_db, chave, &limites).await?);
+                }
+                limpar_banco(&client_db).await;
+                Ok::<_, String>(espera)
+            }
+            .await;
+            resultado.unwrap_or_else(|e| {
+                eprintln!("Rate limiter unavailable, allowing request: {}", e);
+                None
+            })
+        }
+    };
+
+    match espera {
+        None => Ok(()),
+        Some(segundos) => {
+            eprintln!("Rate limit exceeded for policy '{}' (retry after {}s)", politica.as_str(), segundos);
+            Err(resposta_limite(segundos))
+        }
+    }
+}
+
+/// Limite pelo usuário da sessão, depois do `exigir_permissao` da rota.
+pub async fn limitar_usuario(usuario: &UsuarioSessao, politica: Politica) -> Result<(), Response> {
+    limitar_chaves(politica, &[format!("usuario:{}", usuario.id_usuario)]).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const LIMITES: Limites = Limites { por_minuto: 6.0, rajada: 3.0 };
+
+    #[test]
+    fn gastar_consome_uma_ficha_e_recarrega_pelo_tempo() {
+        assert_eq!(gastar(3.0, 0.0, &LIMITES), (2.0, None));
+        // 6 por minuto: uma ficha a cada 10 segundos
+        assert_eq!(gastar(0.0, 10.0, &LIMITES), (0.0, None));
+        let (fichas, espera) = gastar(0.5, 0.0, &LIMITES);
+        assert_eq!((fichas, espera), (0.5, Some(5)));
+    }
+
+    #[test]
+    fn gastar_nao_passa_da_rajada() {
+        assert_eq!(gastar(1.0, 3600.0, &LIMITES), (2.0, None));
+    }
+
+    #[test]
+    fn gastar_espera_ao_menos_um_segundo() {
+        assert_eq!(gastar(0.99, 0.0, &LIMITES).1, Some(1));
+    }
+
+    #[test]
+    fn baldes_em_memoria_limitam_apos_a_rajada() {
+        let mut baldes = BaldesMemoria::default();
+        let agora = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(baldes.consumir("a", &LIMITES, agora, 10), None);
+        }
+        assert_eq!(baldes.consumir("a", &LIMITES, agora, 10), Some(10));
+        assert_eq!(baldes.consumir("a", &LIMITES, agora + Duration::from_secs(10), 10), None);
+    }
+
+    #[test]
+    fn baldes_em_memoria_descartam_o_usado_ha_mais_tempo() {
+        let mut baldes = BaldesMemoria::default();
+        let agora = Instant::now();
+        baldes.consumir("a", &LIMITES, agora, 2);
+        baldes.consumir("b", &LIMITES, agora, 2);
+        baldes.consumir("a", &LIMITES, agora, 2);
+        baldes.consumir("c", &LIMITES, agora, 2);
+        assert_eq!(baldes.baldes.len(), 2);
+        assert_eq!(baldes.por_uso.len(), 2);
+        assert!(baldes.baldes.contains_key("a") && baldes.baldes.contains_key("c"));
+    }
+}
@@ -0,0 +1,399 @@
+// src/migracao.rs
+// Esquema do banco: as tabelas da versão inicial e as tabelas, colunas, índices
+// e funções criadas depois. Tudo é idempotente (`IF NOT EXISTS`, `CREATE OR
+// REPLACE`) e nada é apagado, então o mesmo comando instala um banco vazio e
+// atualiza um existente, a cada atualização:
+//
+//   cargo run --bin migrar_banco
+//
+// O `init` (modo de depuração) aplica a mesma migração; o `clean` não passa por aqui.
+use tokio_postgres::Client as DbClient;
+
+use crate::auth::criar_usuario_inicial;
+use crate::ia::prompts::semear_templates_padrao;
+
+const MIGRACAO: &str = r#"
+    -- Tabelas da versão inicial do banco
+    CREATE TABLE IF NOT EXISTS Cliente (
+        id_cliente SERIAL PRIMARY KEY,
+        nome VARCHAR(255) NOT NULL,
+        email VARCHAR(255),
+        telefone VARCHAR(20),
+        endereco TEXT,
+        data_cadastro DATE
+    );
+
+    CREATE TABLE IF NOT EXISTS Pessoa_Fisica (
+        id_cliente INTEGER PRIMARY KEY REFERENCES Cliente(id_cliente),
+        cpf VARCHAR(14) UNIQUE NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS Pessoa_Juridica (
+        id_cliente INTEGER PRIMARY KEY REFERENCES Cliente(id_cliente),
+        cnpj VARCHAR(18) UNIQUE NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS Advogado (
+        id_advogado SERIAL PRIMARY KEY,
+        nome VARCHAR(255) NOT NULL,
+        oab VARCHAR(20) UNIQUE NOT NULL,
+        telefone VARCHAR(20),
+        email VARCHAR(255),
+        especialidade VARCHAR(255)
+    );
+
+    CREATE TABLE IF NOT EXISTS Vara_Judicial (
+        id_vara_judicial SERIAL PRIMARY KEY,
+        nome_vara VARCHAR(255) NOT NULL,
+        cidade VARCHAR(100),
+        estado VARCHAR(50)
+    );
+
+    CREATE TABLE IF NOT EXISTS Status (
+        id_status SERIAL PRIMARY KEY,
+        descricao VARCHAR(255) NOT NULL,
+        data_modificacao DATE
+    );
+
+    CREATE TABLE IF NOT EXISTS Categoria_caso (
+        id_categoria_caso SERIAL PRIMARY KEY,
+        descricao VARCHAR(255) NOT NULL,
+        ativo BOOLEAN DEFAULT TRUE
+    );
+
+    CREATE TABLE IF NOT EXISTS Caso (
+        id_caso SERIAL PRIMARY KEY,
+        id_cliente INTEGER NOT NULL REFERENCES Cliente(id_cliente),
+        id_advogado INTEGER NOT NULL REFERENCES Advogado(id_advogado),
+        id_status INTEGER NOT NULL REFERENCES Status(id_status),
+        id_vara_judicial INTEGER REFERENCES Vara_Judicial(id_vara_judicial),
+        id_categoria_caso INTEGER REFERENCES Categoria_caso(id_categoria_caso),
+        descricao TEXT,
+        numero_processo VARCHAR(255) UNIQUE,
+        data_fechamento DATE,
+        data_abertura DATE NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS Andamento_processual (
+        id_andamento SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        descricao TEXT,
+        data_andamento DATE NOT NULL,
+        responsavel VARCHAR(255)
+    );
+
+    CREATE TABLE IF NOT EXISTS Audiencia (
+        id_audiencia SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        data_audiencia TIMESTAMP NOT NULL,
+        horario TIME,
+        descricao TEXT,
+        endereco TEXT,
+        tipo_audiencia VARCHAR(100)
+    );
+
+    CREATE TABLE IF NOT EXISTS Pecas (
+        id_peca SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        descricao TEXT,
+        data_registro DATE NOT NULL,
+        tipo_midia VARCHAR(100)
+    );
+
+    CREATE TABLE IF NOT EXISTS Documento (
+        id_documento SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        descricao TEXT,
+        data_envio DATE,
+        arquivo BYTEA,
+        nome_arquivo VARCHAR(255)
+    );
+
+    CREATE TABLE IF NOT EXISTS Tarefa (
+        id_tarefa SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        id_advogado INTEGER NOT NULL REFERENCES Advogado(id_advogado),
+        descricao TEXT,
+        data_tarefa DATE NOT NULL
+    );
+
+    ALTER TABLE Cliente ADD COLUMN IF NOT EXISTS data_anonimizacao TIMESTAMP;
+
+    -- Chave de dados do arquivo, cifrada com a chave mestra (ver src/cifra.rs)
+    ALTER TABLE Documento ADD COLUMN IF NOT EXISTS chave_cifrada BYTEA;
+    ALTER TABLE Documento ADD COLUMN IF NOT EXISTS id_chave_mestra VARCHAR(16);
+    ALTER TABLE Documento ADD COLUMN IF NOT EXISTS versao INTEGER NOT NULL DEFAULT 1;
+    ALTER TABLE Documento ADD COLUMN IF NOT EXISTS id_documento_original INTEGER REFERENCES Documento(id_documento) ON DELETE SET NULL;
+    ALTER TABLE Documento ADD COLUMN IF NOT EXISTS gerado_por_ia BOOLEAN NOT NULL DEFAULT FALSE;
+
+    CREATE TABLE IF NOT EXISTS Prompt_Template (
+        id_template SERIAL PRIMARY KEY,
+        nome VARCHAR(100) UNIQUE NOT NULL,
+        descricao TEXT,
+        ativo BOOLEAN DEFAULT TRUE
+    );
+
+    CREATE TABLE IF NOT EXISTS Prompt_Template_Versao (
+        id_versao SERIAL PRIMARY KEY,
+        id_template INTEGER NOT NULL REFERENCES Prompt_Template(id_template),
+        versao INTEGER NOT NULL,
+        system_prompt TEXT,
+        conteudo TEXT NOT NULL,
+        variaveis TEXT[] NOT NULL,
+        modelo_padrao VARCHAR(255),
+        temperature DOUBLE PRECISION,
+        num_ctx INTEGER,
+        data_criacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (id_template, versao)
+    );
+
+    CREATE TABLE IF NOT EXISTS Documento_Analise (
+        id_analise SERIAL PRIMARY KEY,
+        id_documento INTEGER NOT NULL REFERENCES Documento(id_documento) ON DELETE CASCADE,
+        modelo VARCHAR(255) NOT NULL,
+        status VARCHAR(20) NOT NULL,
+        resumo TEXT,
+        extracao JSONB,
+        versoes_prompt JSONB,
+        erro TEXT,
+        tentativas INTEGER DEFAULT 0,
+        data_analise TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS Resumo_Longo (
+        id_resumo SERIAL PRIMARY KEY,
+        id_documento INTEGER NOT NULL REFERENCES Documento(id_documento) ON DELETE CASCADE,
+        hash_documento CHAR(64) NOT NULL,
+        modelo VARCHAR(255) NOT NULL,
+        status VARCHAR(20) NOT NULL,
+        nivel_atual INTEGER NOT NULL DEFAULT 0,
+        partes_nivel INTEGER NOT NULL DEFAULT 0,
+        partes_concluidas INTEGER NOT NULL DEFAULT 0,
+        chamadas_llm INTEGER NOT NULL DEFAULT 0,
+        chamadas_cache INTEGER NOT NULL DEFAULT 0,
+        resumo TEXT,
+        versoes_prompt JSONB,
+        erro TEXT,
+        data_inicio TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        data_atualizacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        UNIQUE (id_documento, hash_documento, modelo)
+    );
+
+    CREATE TABLE IF NOT EXISTS Resumo_Parcial (
+        hash_documento CHAR(64) NOT NULL,
+        modelo VARCHAR(255) NOT NULL,
+        nivel INTEGER NOT NULL,
+        indice INTEGER NOT NULL,
+        hash_entrada CHAR(64) NOT NULL,
+        resumo TEXT NOT NULL,
+        data_criacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (hash_documento, modelo, nivel, indice)
+    );
+
+    CREATE TABLE IF NOT EXISTS Llm_Chamada (
+        id_chamada SERIAL PRIMARY KEY,
+        data_chamada TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        origem VARCHAR(50) NOT NULL,
+        usuario VARCHAR(255),
+        provedor VARCHAR(20) NOT NULL,
+        operacao VARCHAR(20) NOT NULL,
+        modelo VARCHAR(255) NOT NULL,
+        ids_documento INTEGER[] NOT NULL DEFAULT '{}',
+        -- Casos das chamadas sem documento (classificação, minutas, casos
+        -- semelhantes), para a anonimização achar os registros
+        ids_caso INTEGER[] NOT NULL DEFAULT '{}',
+        id_versao_prompt INTEGER REFERENCES Prompt_Template_Versao(id_versao),
+        hash_prompt CHAR(64) NOT NULL,
+        prompt TEXT,
+        resposta TEXT,
+        prompt_tokens BIGINT,
+        completion_tokens BIGINT,
+        total_duration_ms BIGINT,
+        load_duration_ms BIGINT,
+        prompt_eval_duration_ms BIGINT,
+        eval_duration_ms BIGINT,
+        latencia_ms BIGINT NOT NULL,
+        sucesso BOOLEAN NOT NULL,
+        erro TEXT
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_llm_chamada_data ON Llm_Chamada (data_chamada);
+
+    CREATE TABLE IF NOT EXISTS Embedding_Cache (
+        hash_texto CHAR(64) NOT NULL,
+        modelo VARCHAR(255) NOT NULL,
+        vetor REAL[] NOT NULL,
+        data_criacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (hash_texto, modelo)
+    );
+
+    CREATE TABLE IF NOT EXISTS Minuta_Ia (
+        id_documento INTEGER PRIMARY KEY REFERENCES Documento(id_documento) ON DELETE CASCADE,
+        tipo VARCHAR(100) NOT NULL,
+        modelo VARCHAR(255) NOT NULL,
+        id_versao_prompt INTEGER REFERENCES Prompt_Template_Versao(id_versao),
+        documentos_base INTEGER[] NOT NULL DEFAULT '{}',
+        instrucoes TEXT,
+        texto TEXT NOT NULL,
+        status VARCHAR(20) NOT NULL DEFAULT 'pendente',
+        id_advogado_aprovador INTEGER REFERENCES Advogado(id_advogado),
+        data_aprovacao TIMESTAMP,
+        data_geracao TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS Tarefa_Sugerida (
+        id_sugestao SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
+        id_documento INTEGER NOT NULL REFERENCES Documento(id_documento) ON DELETE CASCADE,
+        descricao TEXT NOT NULL,
+        prazo_dias INTEGER NOT NULL,
+        dias_uteis BOOLEAN NOT NULL DEFAULT TRUE,
+        data_base DATE NOT NULL,
+        data_vencimento DATE NOT NULL,
+        origem VARCHAR(10) NOT NULL,
+        trecho TEXT NOT NULL,
+        inicio_trecho INTEGER NOT NULL,
+        fim_trecho INTEGER NOT NULL,
+        id_versao_prompt INTEGER REFERENCES Prompt_Template_Versao(id_versao),
+        status VARCHAR(20) NOT NULL DEFAULT 'pendente',
+        id_tarefa INTEGER REFERENCES Tarefa(id_tarefa),
+        data_decisao TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS Categoria_Sugerida (
+        id_sugestao SERIAL PRIMARY KEY,
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso) ON DELETE CASCADE,
+        id_categoria_caso INTEGER NOT NULL REFERENCES Categoria_caso(id_categoria_caso),
+        confianca REAL NOT NULL,
+        origem VARCHAR(10) NOT NULL,
+        justificativa TEXT,
+        modelo VARCHAR(255),
+        id_versao_prompt INTEGER REFERENCES Prompt_Template_Versao(id_versao),
+        status VARCHAR(20) NOT NULL DEFAULT 'pendente',
+        data_sugestao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        data_decisao TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS Usuario (
+        id_usuario SERIAL PRIMARY KEY,
+        nome VARCHAR(255) NOT NULL,
+        email VARCHAR(255) NOT NULL UNIQUE,
+        senha_hash TEXT NOT NULL,
+        papel VARCHAR(20) NOT NULL DEFAULT 'estagiario'
+            CHECK (papel IN ('admin', 'socio', 'advogado', 'estagiario', 'financeiro')),
+        id_advogado INTEGER REFERENCES Advogado(id_advogado) ON DELETE SET NULL,
+        ativo BOOLEAN NOT NULL DEFAULT TRUE,
+        data_criacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+    );
+
+    CREATE TABLE IF NOT EXISTS Sessao (
+        id_sessao SERIAL PRIMARY KEY,
+        id_usuario INTEGER NOT NULL REFERENCES Usuario(id_usuario) ON DELETE CASCADE,
+        token_hash CHAR(64) NOT NULL UNIQUE,
+        data_criacao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        ultimo_acesso TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        expira_em TIMESTAMP NOT NULL,
+        revogada_em TIMESTAMP,
+        ip VARCHAR(64),
+        user_agent TEXT
+    );
+
+    CREATE TABLE IF NOT EXISTS Caso_Acesso (
+        id_caso INTEGER NOT NULL REFERENCES Caso(id_caso) ON DELETE CASCADE,
+        id_usuario INTEGER NOT NULL REFERENCES Usuario(id_usuario) ON DELETE CASCADE,
+        concedido_por INTEGER REFERENCES Usuario(id_usuario) ON DELETE SET NULL,
+        data_concessao TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+        PRIMARY KEY (id_caso, id_usuario)
+    );
+
+    -- Baldes do limite de requisições quando LIMITE_ARMAZENAMENTO=banco (ver src/limite.rs)
+    CREATE TABLE IF NOT EXISTS Limite_Requisicao (
+        chave VARCHAR(300) PRIMARY KEY,
+        fichas DOUBLE PRECISION NOT NULL,
+        atualizado_em TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+
+    -- Trilha de auditoria (ver src/auditoria.rs). Sem chaves estrangeiras: o
+    -- registro sobrevive à exclusão do usuário ou da entidade.
+    CREATE TABLE IF NOT EXISTS Auditoria (
+        id_auditoria BIGSERIAL PRIMARY KEY,
+        data_registro TIMESTAMPTZ NOT NULL,
+        id_usuario INTEGER,
+        usuario VARCHAR(255),
+        entidade VARCHAR(50) NOT NULL,
+        id_entidade INTEGER NOT NULL,
+        acao VARCHAR(30) NOT NULL,
+        antes JSONB,
+        depois JSONB,
+        diferenca JSONB,
+        id_requisicao VARCHAR(64),
+        hash_anterior CHAR(64) NOT NULL UNIQUE,
+        hash CHAR(64) NOT NULL UNIQUE
+    );
+
+    CREATE INDEX IF NOT EXISTS idx_auditoria_entidade ON Auditoria (entidade, id_entidade, id_auditoria);
+
+    -- Conteúdo coberto pelo hash de um registro. O hash é um HMAC calculado na
+    -- aplicação, com uma chave que não fica no banco (ver `crate::auditoria`)
+    CREATE OR REPLACE FUNCTION auditoria_conteudo(r Auditoria) RETURNS TEXT LANGUAGE SQL STABLE AS $$
+        SELECT jsonb_build_array(
+            r.hash_anterior, r.id_auditoria, to_char(r.data_registro AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.US'),
+            r.id_usuario, r.usuario, r.entidade, r.id_entidade, r.acao, r.antes, r.depois, r.diferenca, r.id_requisicao
+        )::text;
+    $$;
+
+    -- Monta o próximo registro, sem o hash, para a aplicação calcular e inserir.
+    -- O bloqueio vai até o fim da transação de quem registra, então o próximo
+    -- registro sempre encadeia no último já confirmado
+    CREATE OR REPLACE FUNCTION preparar_auditoria(
+        p_id_usuario INTEGER, p_usuario TEXT, p_entidade TEXT, p_id_entidade INTEGER, p_acao TEXT,
+        p_antes JSONB, p_depois JSONB, p_diferenca JSONB, p_id_requisicao TEXT
+    ) RETURNS Auditoria LANGUAGE plpgsql AS $$
+    DECLARE
+        r Auditoria;
+    BEGIN
+        PERFORM pg_advisory_xact_lock(hashtext('auditoria'));
+        SELECT a.hash INTO r.hash_anterior FROM Auditoria a ORDER BY a.id_auditoria DESC LIMIT 1;
+        r.hash_anterior := COALESCE(r.hash_anterior, repeat('0', 64));
+        r.id_auditoria := nextval(pg_get_serial_sequence('auditoria', 'id_auditoria'));
+        r.data_registro := clock_timestamp();
+        r.id_usuario := p_id_usuario;
+        r.usuario := p_usuario;
+        r.entidade := p_entidade;
+        r.id_entidade := p_id_entidade;
+        r.acao := p_acao;
+        r.antes := p_antes;
+        r.depois := p_depois;
+        r.diferenca := p_diferenca;
+        r.id_requisicao := p_id_requisicao;
+        RETURN r;
+    END;
+    $$;
+
+    CREATE OR REPLACE FUNCTION auditoria_somente_insercao() RETURNS TRIGGER LANGUAGE plpgsql AS $$
+    BEGIN
+        RAISE EXCEPTION 'A tabela Auditoria só aceita inserções.';
+    END;
+    $$;
+
+    DO $$
+    BEGIN
+        IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'auditoria_somente_insercao') THEN
+            CREATE TRIGGER auditoria_somente_insercao
+                BEFORE UPDATE OR DELETE OR TRUNCATE ON Auditoria
+                FOR EACH STATEMENT EXECUTE FUNCTION auditoria_somente_insercao();
+        END IF;
+    END;
+    $$;
+"#;
+
+/// Aplica a migração numa transação e semeia os templates de prompt e o
+/// usuário inicial que ainda não existirem.
+pub async fn migrar(client_db: &mut DbClient) -> Result<(), String> {
+    let transaction = client_db.transaction().await.map_err(|e| e.to_string())?;
+    transaction.batch_execute(MIGRACAO).await.map_err(|e| format!("Falha ao migrar o banco: {}", e))?;
+    transaction.commit().await.map_err(|e| e.to_string())?;
+
+    semear_templates_padrao(client_db).await.map_err(|e| format!("Falha ao semear os templates de prompt: {}", e))?;
+    criar_usuario_inicial(client_db).await.map_err(|e| format!("Falha ao criar o usuário inicial: {}", e))
+}
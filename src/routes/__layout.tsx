@@ -1,10 +1,49 @@
+import { useEffect, useState } from 'react'
 import type { JSX } from 'react'
-import { Link, TuonoScripts } from 'tuono' // Import TuonoScripts
+import { Link, TuonoScripts, useRouter } from 'tuono' // Import TuonoScripts
 import type { TuonoLayoutProps } from 'tuono'
 
 import '../styles/global.css' // Importa o CSS global
 
+interface UsuarioLogado {
+  id_usuario: number;
+  nome: string;
+  email: string;
+  papel: string;
+}
+
+// Espelha as permissões de src/acesso.rs só para esconder links; o servidor é quem barra o acesso
+const PODE_VER_RELATORIOS = ['admin', 'socio', 'financeiro'];
+
 export default function RootLayout({ children }: TuonoLayoutProps): JSX.Element {
+  const router = useRouter();
+  const paginaLogin = router.pathname === '/login';
+  const [usuario, setUsuario] = useState<UsuarioLogado | null>(null);
+  const financeiro = usuario?.papel === 'financeiro';
+  const veRelatorios = usuario !== null && PODE_VER_RELATORIOS.includes(usuario.papel);
+
+  // As páginas sem handler no servidor só descobrem aqui que a sessão expirou
+  useEffect(() => {
+    if (paginaLogin) return;
+    fetch('/api/login')
+      .then(async (response) => {
+        if (response.status === 401) {
+          window.location.href = `/login?destino=${encodeURIComponent(window.location.pathname)}`;
+          return;
+        }
+        if (response.ok) setUsuario(await response.json());
+      })
+      .catch((err) => console.error('Erro ao verificar a sessão:', err));
+  }, [paginaLogin, router.pathname]);
+
+  const handleLogout = async () => {
+    try {
+      await fetch('/api/logout', { method: 'POST' });
+    } finally {
+      window.location.href = '/login';
+    }
+  };
+
   // Simple console logs for button testing, as requested.
   // These will appear in the browser's developer console when the links are clicked.
   const handleLinkClick = (e: React.MouseEvent<HTMLAnchorElement>, path: string) => {
@@ -16,6 +55,7 @@ export default function RootLayout({ children }: TuonoLayoutProps): JSX.Element
   return (
     <html lang="pt">
       <body className="dashboard-body">
+        {!paginaLogin && (
         <aside className="sidebar">
           <h1 className="sidebar-title">📚 Dashbord</h1>
           <nav className="sidebar-nav">
@@ -25,20 +65,33 @@ export default function RootLayout({ children }: TuonoLayoutProps): JSX.Element
             <Link href="/clientes" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/clientes')}>
               🧑 Clientes
             </Link>
-            <Link href="/documentos" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/documentos')}>
-              📄 Documentos
-            </Link>
-            <Link href="/ia-integrada" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/ia-integrada')}>
-              🤖 IA Integrada
-            </Link>
-            <Link href="/relatorios" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/relatorios')}>
-              📊 Relatórios
-            </Link>
+            {!financeiro && (
+              <Link href="/documentos" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/documentos')}>
+                📄 Documentos
+              </Link>
+            )}
+            {!financeiro && (
+              <Link href="/ia-integrada" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/ia-integrada')}>
+                🤖 IA Integrada
+              </Link>
+            )}
+            {veRelatorios && (
+              <Link href="/relatorios" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/relatorios')}>
+                📊 Relatórios
+              </Link>
+            )}
             <Link href="/configuracoes" className="sidebar-link" onClick={(e) => handleLinkClick(e, '/configuracoes')}>
               ⚙️ Configurações
             </Link>
           </nav>
+          <div className="sidebar-user">
+            {usuario && <span>{usuario.nome}</span>}
+            <button type="button" className="sidebar-link" onClick={handleLogout}>
+              🚪 Sair
+            </button>
+          </div>
         </aside>
+        )}
         <main className="main-content">{children}</main>
         <TuonoScripts /> {/* This is the crucial addition */}
       </body>
@@ -1,17 +1,22 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::auth::exigir_sessao;
 use tuono_app::{connect_db};
 
 use serde_json::{json, Value};
 
 #[tuono_lib::api(GET)]
-pub async fn advogado(_req: Request) -> Json<Value> {
+pub async fn advogado(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_sessao(&_req).await {
+        return resposta.into_response();
+    }
+
     let client_db = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
-            return Json(json!({ "error": format!("Database connection error: {}", e) }));
+            return Json(json!({ "error": format!("Database connection error: {}", e) })).into_response();
         }
     };
 
@@ -25,7 +30,7 @@ pub async fn advogado(_req: Request) -> Json<Value> {
         Ok(rows) => rows,
         Err(e) => {
             eprintln!("Failed to fetch lawyers: {}", e);
-            return Json(json!({ "error": format!("Failed to fetch lawyers: {}", e) }));
+            return Json(json!({ "error": format!("Failed to fetch lawyers: {}", e) })).into_response();
         }
     };
 
@@ -37,5 +42,5 @@ pub async fn advogado(_req: Request) -> Json<Value> {
         })
     }).collect();
 
-    Json(json!(lawyers_list))
+    Json(json!(lawyers_list)).into_response()
 }
\ No newline at end of file
@@ -0,0 +1,135 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_documentos, exigir_permissao, Permissao};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::{analise, provider_from_env};
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct ReanalisePayload {
+    id_documento: i32,
+    model: Option<String>,
+}
+
+// GET /api/analises_documento?id_documento= (histórico de análises do documento)
+#[tuono_lib::api(GET)]
+async fn analises_documento(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)}))),
+    };
+
+    let id_documento = match query_values.get("id_documento").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento é obrigatório."}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
+    }
+
+    let rows = match client_db
+        .query(
+            "SELECT id_analise, modelo, status, resumo, extracao, versoes_prompt, erro, tentativas, data_analise FROM Documento_Analise WHERE id_documento = $1 ORDER BY data_analise DESC, id_analise DESC;",
+            &[&id_documento],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch document analyses: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch document analyses: {}", e)})));
+        }
+    };
+
+    let analises: Vec<Value> = rows.iter().map(analise::analise_json).collect();
+
+    (StatusCode::OK, Json(json!(analises)))
+}
+
+// POST /api/analises_documento (reexecuta a análise, opcionalmente com outro modelo)
+#[tuono_lib::api(POST)]
+async fn reanalisar_documento(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    reanalisar_documento_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn reanalisar_documento_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    let payload: ReanalisePayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let llm = match provider_from_env(fetch) {
+        Ok(llm) => auditar(llm, ContextoAuditoria::new("analise_documento", vec![payload.id_documento]).por(&usuario.email)),
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[payload.id_documento]).await {
+        return resposta;
+    }
+
+    let modelo = analise::modelo_para_analise(&client_db, payload.model).await;
+
+    let id_analise = match analise::registrar_analise(&client_db, payload.id_documento, &modelo).await {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("Failed to register document analysis: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to register analysis: {}", e)})));
+        }
+    };
+
+    if let Err(e) = analise::executar_analise(llm.as_ref(), &client_db, id_analise).await {
+        eprintln!("Document analysis {} failed: {}", id_analise, e);
+        return (StatusCode::BAD_GATEWAY, Json(json!({"error": e, "id_analise": id_analise})));
+    }
+
+    match analise::ultima_analise(&client_db, payload.id_documento).await {
+        Ok(analise) => (StatusCode::OK, Json(json!({"message": "Análise concluída com sucesso", "analise": analise}))),
+        Err(e) => {
+            eprintln!("Failed to fetch document analysis: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch analysis: {}", e)})))
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::auditoria::{historico, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+
+use serde_json::json;
+
+// GET /api/auditoria?entidade=caso&id= (histórico de alterações, do mais antigo ao mais recente)
+#[tuono_lib::api(GET)]
+async fn historico_entidade(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::VerAuditoria).await {
+        return resposta;
+    }
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Failed to extract query values for GET (Auditoria): {}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)})));
+        }
+    };
+
+    let entidade = match query_values.get("entidade").map(|nome| Entidade::pelo_nome(nome)) {
+        Some(Some(entidade)) => entidade,
+        Some(None) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Entidade inválida. Use cliente, caso, documento, usuario, sessao, tarefa, prompt ou banco."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "A entidade é obrigatória."}))),
+    };
+    let id = match query_values.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID é obrigatório."}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    match historico(&client_db, entidade, id).await {
+        Ok(registros) => (StatusCode::OK, Json(json!(registros))),
+        Err(e) => {
+            eprintln!("Failed to fetch audit history: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch audit history: {}", e)})))
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::auditoria::verificar_cadeia;
+use tuono_app::connect_db;
+
+use serde_json::json;
+
+// GET /api/auditoria/verificacao (recalcula a cadeia de hashes da trilha inteira)
+#[tuono_lib::api(GET)]
+async fn verificar_auditoria(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::VerAuditoria).await {
+        return resposta;
+    }
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    match verificar_cadeia(&client_db).await {
+        Ok(verificacao) => {
+            if !verificacao.integra {
+                eprintln!("Audit trail verification failed at record {:?}: {:?}", verificacao.id_auditoria_invalido, verificacao.problema);
+            }
+            (StatusCode::OK, Json(json!(verificacao)))
+        }
+        Err(e) => {
+            eprintln!("Failed to verify audit trail: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to verify audit trail: {}", e)})))
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
 use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::{classificacao, provider_from_env};
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::limite::{limitar_usuario, Politica};
 
 use serde_json::{json, Value};
 use chrono::NaiveDate;
@@ -39,6 +44,11 @@ struct CasoUpdatePayload {
 // GET /api/casos (Listar todos os casos ou um específico por ID)
 #[tuono_lib::api(GET)]
 async fn caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+
     let query_string = _req.uri.query().unwrap_or("");
     let query_values_result = tuono_app::extract_query_values(query_string);
 
@@ -50,6 +60,15 @@ async fn caso(_req: Request) -> impl IntoResponse {
         }
     };
 
+    // Só os casos visíveis para o usuário ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)}))).into_response();
+        }
+    };
+
     let base_query = "
         SELECT
             c.id_caso, c.descricao, c.numero_processo, c.data_abertura, c.data_fechamento,
@@ -64,6 +83,7 @@ async fn caso(_req: Request) -> impl IntoResponse {
         INNER JOIN Status s ON c.id_status = s.id_status
         LEFT JOIN Vara_Judicial vj ON c.id_vara_judicial = vj.id_vara_judicial
         LEFT JOIN Categoria_caso cc ON c.id_categoria_caso = cc.id_categoria_caso
+        WHERE ($1::INTEGER[] IS NULL OR c.id_caso = ANY($1))
     ";
 
     // Se um ID for fornecido na query, buscar um caso específico
@@ -76,7 +96,7 @@ async fn caso(_req: Request) -> impl IntoResponse {
                 Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso deve ser um número inteiro."}))).into_response(),
             };
             match client_db
-                .query(&format!("{} WHERE c.id_caso = $1;", base_query), &[&id])
+                .query(&format!("{} AND c.id_caso = $2;", base_query), &[&ids_visiveis, &id])
                 .await
             {
                 Ok(r) => r,
@@ -88,7 +108,7 @@ async fn caso(_req: Request) -> impl IntoResponse {
         } else {
             // Nenhum ID na query, retornar todos
             match client_db
-                .query(&format!("{} ORDER BY c.data_abertura DESC;", base_query), &[])
+                .query(&format!("{} ORDER BY c.data_abertura DESC;", base_query), &[&ids_visiveis])
                 .await
             {
                 Ok(r) => r,
@@ -101,7 +121,7 @@ async fn caso(_req: Request) -> impl IntoResponse {
     } else {
         // Erro ao extrair query values, então retornar todos os casos
         match client_db
-            .query(&format!("{} ORDER BY c.data_abertura DESC;", base_query), &[])
+            .query(&format!("{} ORDER BY c.data_abertura DESC;", base_query), &[&ids_visiveis])
             .await
         {
             Ok(r) => r,
@@ -155,7 +175,13 @@ async fn caso(_req: Request) -> impl IntoResponse {
 
 
 #[tuono_lib::api(POST)]
-async fn create_caso(_req: Request) -> impl IntoResponse {
+async fn create_caso(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let payload: CasoPayload = match _req.body() {
         Ok(p) => p,
         Err(e) => {
@@ -178,7 +204,7 @@ async fn create_caso(_req: Request) -> impl IntoResponse {
     let data_fechamento = payload.data_fechamento.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
 
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -211,25 +237,77 @@ async fn create_caso(_req: Request) -> impl IntoResponse {
         RETURNING id_caso;
     ";
 
-    let rows = match client_db.query(
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let rows = match transaction.query(
         insert_query,
         &[&id_cliente, &id_advogado, &id_status, &id_vara_judicial, &id_categoria_caso, &descricao, &numero_processo, &data_abertura, &data_fechamento],
     ).await {
         Ok(rows) => rows,
         Err(e) => {
             eprintln!("Failed to insert Caso: {}", e);
+            let _ = transaction.rollback().await;
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create case: {}", e)})));
         }
     };
 
     let id_caso_new: i32 = rows[0].get("id_caso");
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Caso, id_caso_new, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    // Quem cria o caso para outro advogado continua com acesso a ele
+    if !usuario.papel.ve_todos_os_casos()
+        && usuario.id_advogado != Some(id_advogado)
+        && let Err(e) = client_db
+            .execute(
+                "INSERT INTO Caso_Acesso (id_caso, id_usuario, concedido_por) VALUES ($1, $2, $2) ON CONFLICT DO NOTHING;",
+                &[&id_caso_new, &usuario.id_usuario],
+            )
+            .await
+    {
+        eprintln!("Failed to share case {} with its creator: {}", id_caso_new, e);
+    }
+
+    // Sem categoria informada, uma sugestão é gerada em segundo plano para
+    // confirmação, dentro do limite de IA do usuário
+    if id_categoria_caso.is_none() {
+        if limitar_usuario(&usuario, Politica::Ia).await.is_err() {
+            eprintln!("Case classification skipped: AI rate limit reached for user {}", usuario.id_usuario);
+        } else {
+            match provider_from_env(fetch) {
+                Ok(llm) => classificacao::agendar_sugestao(auditar(llm, ContextoAuditoria::new("classificacao_caso", Vec::new()).por(&usuario.email).do_caso(id_caso_new)), id_caso_new),
+                Err(e) => eprintln!("Case classification skipped: {}", e),
+            }
+        }
+    }
+
     (StatusCode::CREATED, Json(json!({"message": "Caso jurídico criado com sucesso", "id_caso": id_caso_new})))
 }
 
 
 #[tuono_lib::api(PUT)]
 async fn update_caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let payload: CasoUpdatePayload = match _req.body() {
         Ok(p) => p,
         Err(e) => {
@@ -253,7 +331,7 @@ async fn update_caso(_req: Request) -> impl IntoResponse {
     let data_fechamento = payload.data_fechamento.and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok());
 
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -261,6 +339,10 @@ async fn update_caso(_req: Request) -> impl IntoResponse {
         }
     };
 
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
+    }
+
     // Validação de FKs
     let cliente_exists = client_db.query_opt("SELECT 1 FROM Cliente WHERE id_cliente = $1;", &[&id_cliente]).await.map_err(|e| format!("DB error checking Cliente: {}", e)).unwrap_or_default().is_some();
     let advogado_exists = client_db.query_opt("SELECT 1 FROM Advogado WHERE id_advogado = $1;", &[&id_advogado]).await.map_err(|e| format!("DB error checking Advogado: {}", e)).unwrap_or_default().is_some();
@@ -293,17 +375,46 @@ async fn update_caso(_req: Request) -> impl IntoResponse {
         WHERE id_caso = $10;
     ";
 
-    let rows_affected = match client_db.execute(
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Caso, id_caso).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Caso state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case: {}", e)})));
+        }
+    };
+
+    let rows_affected = match transaction.execute(
         update_query,
         &[&id_cliente, &id_advogado, &id_status, &id_vara_judicial, &id_categoria_caso, &descricao, &numero_processo, &data_abertura, &data_fechamento, &id_caso],
     ).await {
         Ok(rows) => rows,
         Err(e) => {
             eprintln!("Failed to update Caso: {}", e);
+            let _ = transaction.rollback().await;
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update case: {}", e)})));
         }
     };
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Caso, id_caso, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
     if rows_affected > 0 {
         (StatusCode::OK, Json(json!({"message": "Caso jurídico atualizado com sucesso."})))
     } else {
@@ -315,6 +426,12 @@ async fn update_caso(_req: Request) -> impl IntoResponse {
 // DELETE /api/casos (Excluir caso)
 #[tuono_lib::api(DELETE)]
 async fn delete_caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::ExcluirCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let query_string = _req.uri.query().unwrap_or("");
     let query_values = match tuono_app::extract_query_values(query_string) {
         Ok(value) => value,
@@ -333,7 +450,7 @@ async fn delete_caso(_req: Request) -> impl IntoResponse {
         Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso deve ser um número inteiro."}))),
     };
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -341,15 +458,48 @@ async fn delete_caso(_req: Request) -> impl IntoResponse {
         }
     };
 
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
+    }
+
     let delete_query = "DELETE FROM Caso WHERE id_caso = $1;";
-    let rows_affected = match client_db.execute(delete_query, &[&id_caso]).await {
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Caso, id_caso).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Caso state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case: {}", e)})));
+        }
+    };
+
+    let rows_affected = match transaction.execute(delete_query, &[&id_caso]).await {
         Ok(rows) => rows,
         Err(e) => {
             eprintln!("Failed to delete Caso: {}", e);
+            let _ = transaction.rollback().await;
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to delete case: {}", e)})));
         }
     };
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Caso, id_caso, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
     if rows_affected > 0 {
         (StatusCode::OK, Json(json!({"message": "Caso jurídico excluído com sucesso."})))
     } else {
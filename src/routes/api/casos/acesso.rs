@@ -0,0 +1,227 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_caso, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Acao, Autor, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct CompartilharPayload {
+    id_caso: i32,
+    id_usuario: i32,
+}
+
+fn id_da_query(values: &std::collections::HashMap<String, String>, nome: &str) -> Result<i32, String> {
+    match values.get(nome).map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Ok(id),
+        Some(Err(_)) => Err(format!("{} deve ser um número inteiro.", nome)),
+        None => Err(format!("{} é obrigatório.", nome)),
+    }
+}
+
+// GET /api/casos/acesso?id_caso= (usuários com quem o caso foi compartilhado)
+#[tuono_lib::api(GET)]
+async fn acessos_do_caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let id_caso = match extract_query_values(query_string).and_then(|values| id_da_query(&values, "id_caso")) {
+        Ok(id) => id,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
+    }
+
+    let rows = match client_db
+        .query(
+            "SELECT u.id_usuario, u.nome, u.email, u.papel, c.nome AS concedido_por,
+                    to_char(a.data_concessao, 'YYYY-MM-DD HH24:MI:SS') AS data_concessao
+             FROM Caso_Acesso a
+             INNER JOIN Usuario u ON u.id_usuario = a.id_usuario
+             LEFT JOIN Usuario c ON c.id_usuario = a.concedido_por
+             WHERE a.id_caso = $1
+             ORDER BY u.nome ASC;",
+            &[&id_caso],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch case access list: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch case access list: {}", e)})));
+        }
+    };
+
+    let acessos: Vec<Value> = rows.into_iter().map(|row| {
+        json!({
+            "id_usuario": row.get::<_, i32>("id_usuario"),
+            "nome": row.get::<_, String>("nome"),
+            "email": row.get::<_, String>("email"),
+            "papel": row.get::<_, String>("papel"),
+            "concedido_por": row.get::<_, Option<String>>("concedido_por"),
+            "data_concessao": row.get::<_, Option<String>>("data_concessao"),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(acessos)))
+}
+
+// POST /api/casos/acesso (compartilha o caso com outro usuário)
+#[tuono_lib::api(POST)]
+async fn compartilhar_caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::CompartilharCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: CompartilharPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    // Só compartilha quem já vê o caso
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, payload.id_caso).await {
+        return resposta;
+    }
+
+    match client_db.query_opt("SELECT 1 FROM Usuario WHERE id_usuario = $1 AND ativo;", &[&payload.id_usuario]).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Usuário não encontrado ou inativo."}))),
+        Err(e) => {
+            eprintln!("Failed to check Usuario existence: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database error checking user: {}", e)})));
+        }
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    match transaction
+        .execute(
+            "INSERT INTO Caso_Acesso (id_caso, id_usuario, concedido_por) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
+            &[&payload.id_caso, &payload.id_usuario, &usuario.id_usuario],
+        )
+        .await
+    {
+        Ok(0) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::OK, Json(json!({"message": "O caso já estava compartilhado com este usuário."})));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to insert Caso_Acesso: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to share case: {}", e)})));
+        }
+    }
+
+    let acesso = json!({"id_usuario": payload.id_usuario});
+    if let Err(e) = auditoria::registrar(&transaction, &Autor::new(&_req, &usuario), Entidade::Caso, payload.id_caso, Acao::Compartilhar, None, Some(&acesso)).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::CREATED, Json(json!({"message": "Caso compartilhado com sucesso."})))
+}
+
+// DELETE /api/casos/acesso?id_caso=&id_usuario= (remove o compartilhamento)
+#[tuono_lib::api(DELETE)]
+async fn remover_acesso_caso(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::CompartilharCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let ids = extract_query_values(query_string)
+        .and_then(|values| Ok((id_da_query(&values, "id_caso")?, id_da_query(&values, "id_usuario")?)));
+    let (id_caso, id_usuario) = match ids {
+        Ok(ids) => ids,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    match transaction.execute("DELETE FROM Caso_Acesso WHERE id_caso = $1 AND id_usuario = $2;", &[&id_caso, &id_usuario]).await {
+        Ok(0) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Compartilhamento não encontrado."})));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to delete Caso_Acesso: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to remove case access: {}", e)})));
+        }
+    }
+
+    let acesso = json!({"id_usuario": id_usuario});
+    if let Err(e) = auditoria::registrar(&transaction, &Autor::new(&_req, &usuario), Entidade::Caso, id_caso, Acao::RemoverCompartilhamento, Some(&acesso), None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Compartilhamento removido."})))
+}
@@ -0,0 +1,86 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_permissao, Permissao};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::provider_from_env;
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::ia::similares::{casos_semelhantes, modelo_embedding};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde_json::json;
+
+const LIMITE_PADRAO: usize = 5;
+const LIMITE_MAXIMO: usize = 20;
+
+// GET /api/casos/similares?id=&limite= (casos passados com fatos parecidos, por similaridade de embeddings)
+#[tuono_lib::api(GET)]
+async fn casos_similares(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    casos_similares_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn casos_similares_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    let query_string = _req.uri.query().unwrap_or("");
+    let values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Parâmetro id é obrigatório."}))),
+    };
+    let id_caso = match values.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Parâmetro id é obrigatório."}))),
+    };
+    let limite = match values.get("limite").map(|l| l.parse::<usize>()) {
+        Some(Ok(l)) if l > 0 => l.min(LIMITE_MAXIMO),
+        Some(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "limite deve ser um número positivo."}))),
+        None => LIMITE_PADRAO,
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
+    }
+    let candidatos = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)})));
+        }
+    };
+
+    let llm = match provider_from_env(fetch) {
+        Ok(llm) => auditar(llm, ContextoAuditoria::new("casos_similares", Vec::new()).por(&usuario.email).do_caso(id_caso)),
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    // Só os casos visíveis para o usuário são candidatos
+    match casos_semelhantes(llm.as_ref(), &client_db, id_caso, candidatos.as_deref(), limite).await {
+        Ok(similares) => (StatusCode::OK, Json(json!({
+            "id_caso": id_caso,
+            "modelo": modelo_embedding(),
+            "similares": similares,
+        }))),
+        Err(e) => {
+            eprintln!("Failed to find similar cases for {}: {}", id_caso, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})))
+        }
+    }
+}
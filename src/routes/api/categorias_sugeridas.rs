@@ -0,0 +1,323 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::fila::Identificacao;
+use tuono_app::ia::provider_para_requisicao;
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::ia::classificacao::sugerir_categoria;
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Deserialize)]
+struct SugerirPayload {
+    id_caso: i32,
+    modelo: Option<String>,
+    id_requisicao: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DecisaoPayload {
+    id_sugestao: i32,
+    acao: String, // "aceitar" ou "rejeitar"
+    id_categoria_caso: Option<i32>, // Aceitar com outra categoria (padrão: a sugerida)
+}
+
+// GET /api/categorias_sugeridas?id_caso= (opcional: &status=pendente). Sem id_caso, lista as pendentes.
+#[tuono_lib::api(GET)]
+async fn categorias_sugeridas(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    // Sem parâmetros, lista as sugestões pendentes de todos os casos
+    let query_values = extract_query_values(query_string).unwrap_or_default();
+
+    let id_caso = match query_values.get("id_caso").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso deve ser um número inteiro."}))),
+        None => None,
+    };
+    let status = match (id_caso, query_values.get("status")) {
+        (_, Some(status)) => Some(status.clone()),
+        (None, None) => Some("pendente".to_string()),
+        (Some(_), None) => None,
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)})));
+        }
+    };
+
+    let rows = match client_db
+        .query(
+            "SELECT cs.id_sugestao, cs.id_caso, c.numero_processo, cs.id_categoria_caso, cc.descricao AS categoria,
+                    cs.confianca, cs.origem, cs.justificativa, cs.modelo, cs.status, cs.data_sugestao, cs.data_decisao
+             FROM Categoria_Sugerida cs
+             INNER JOIN Caso c ON cs.id_caso = c.id_caso
+             INNER JOIN Categoria_caso cc ON cs.id_categoria_caso = cc.id_categoria_caso
+             WHERE ($1::INTEGER IS NULL OR cs.id_caso = $1)
+               AND ($2::VARCHAR IS NULL OR cs.status = $2)
+               AND ($3::INTEGER[] IS NULL OR cs.id_caso = ANY($3))
+             ORDER BY cs.data_sugestao DESC, cs.id_sugestao DESC;",
+            &[&id_caso, &status, &ids_visiveis],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch category suggestions: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch category suggestions: {}", e)})));
+        }
+    };
+
+    let sugestoes: Vec<Value> = rows.into_iter().map(|row| {
+        let data_sugestao: Option<NaiveDateTime> = row.get("data_sugestao");
+        let data_decisao: Option<NaiveDateTime> = row.get("data_decisao");
+        json!({
+            "id_sugestao": row.get::<_, i32>("id_sugestao"),
+            "id_caso": row.get::<_, i32>("id_caso"),
+            "numero_processo": row.get::<_, Option<String>>("numero_processo"),
+            "id_categoria_caso": row.get::<_, i32>("id_categoria_caso"),
+            "categoria": row.get::<_, String>("categoria"),
+            "confianca": row.get::<_, f32>("confianca"),
+            "origem": row.get::<_, String>("origem"),
+            "justificativa": row.get::<_, Option<String>>("justificativa"),
+            "modelo": row.get::<_, Option<String>>("modelo"),
+            "status": row.get::<_, String>("status"),
+            "data_sugestao": data_sugestao.map(|d| d.to_string()),
+            "data_decisao": data_decisao.map(|d| d.to_string()),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(sugestoes)))
+}
+
+// POST /api/categorias_sugeridas (classifica o caso agora)
+#[tuono_lib::api(POST)]
+async fn sugerir_categoria_caso(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    sugerir_categoria_caso_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn sugerir_categoria_caso_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    let payload: SugerirPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, payload.id_caso).await {
+        return resposta;
+    }
+
+    let llm = match provider_para_requisicao(fetch, payload.id_requisicao.map(|id_requisicao| Identificacao { id_usuario: usuario.id_usuario, id_requisicao })) {
+        Ok(llm) => auditar(llm, ContextoAuditoria::new("classificacao_caso", Vec::new()).por(&usuario.email).do_caso(payload.id_caso)),
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    match sugerir_categoria(llm.as_ref(), &client_db, payload.id_caso, payload.modelo.as_deref()).await {
+        Ok(Some(sugestao)) => (StatusCode::CREATED, Json(json!(sugestao))),
+        Ok(None) => (StatusCode::OK, Json(json!({"message": "Não foi possível sugerir uma categoria para o caso."}))),
+        Err(e) => {
+            eprintln!("Case classification failed for case {}: {}", payload.id_caso, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})))
+        }
+    }
+}
+
+// PUT /api/categorias_sugeridas (aceitar ou rejeitar uma sugestão)
+#[tuono_lib::api(PUT)]
+async fn decidir_categoria_sugerida(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: DecisaoPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    if payload.acao != "aceitar" && payload.acao != "rejeitar" {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Ação inválida. Use 'aceitar' ou 'rejeitar'."})));
+    }
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    // Só decide a sugestão quem vê o caso
+    match client_db.query_opt("SELECT id_caso FROM Categoria_Sugerida WHERE id_sugestao = $1;", &[&payload.id_sugestao]).await {
+        Ok(Some(row)) => {
+            if let Err(resposta) = exigir_caso(&client_db, &usuario, row.get("id_caso")).await {
+                return resposta;
+            }
+        }
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Sugestão de categoria não encontrada."}))),
+        Err(e) => {
+            eprintln!("Failed to fetch category suggestion: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch category suggestion: {}", e)})));
+        }
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let row = match transaction
+        .query_opt(
+            "SELECT id_caso, id_categoria_caso, status FROM Categoria_Sugerida WHERE id_sugestao = $1 FOR UPDATE;",
+            &[&payload.id_sugestao],
+        )
+        .await
+    {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Sugestão de categoria não encontrada."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch category suggestion: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch category suggestion: {}", e)})));
+        }
+    };
+
+    let status: String = row.get("status");
+    if status != "pendente" {
+        let _ = transaction.rollback().await;
+        return (StatusCode::CONFLICT, Json(json!({"error": format!("Sugestão já foi decidida ({}).", status)})));
+    }
+
+    if payload.acao == "rejeitar" {
+        if let Err(e) = transaction
+            .execute(
+                "UPDATE Categoria_Sugerida SET status = 'rejeitada', data_decisao = CURRENT_TIMESTAMP WHERE id_sugestao = $1;",
+                &[&payload.id_sugestao],
+            )
+            .await
+        {
+            eprintln!("Failed to reject category suggestion: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to reject category suggestion: {}", e)})));
+        }
+
+        if let Err(e) = transaction.commit().await {
+            eprintln!("Failed to commit transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+        }
+        return (StatusCode::OK, Json(json!({"message": "Sugestão rejeitada."})));
+    }
+
+    let id_caso: i32 = row.get("id_caso");
+    let id_categoria_caso: i32 = payload.id_categoria_caso.unwrap_or_else(|| row.get("id_categoria_caso"));
+
+    match transaction
+        .query_opt("SELECT 1 FROM Categoria_caso WHERE id_categoria_caso = $1 AND ativo = TRUE;", &[&id_categoria_caso])
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "Categoria de Caso com o ID fornecido não existe ou está inativa."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch Categoria_caso: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch case category: {}", e)})));
+        }
+    }
+
+    let antes = match auditoria::estado(&transaction, Entidade::Caso, id_caso).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Caso state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case: {}", e)})));
+        }
+    };
+
+    if let Err(e) = transaction
+        .execute("UPDATE Caso SET id_categoria_caso = $1 WHERE id_caso = $2;", &[&id_categoria_caso, &id_caso])
+        .await
+    {
+        eprintln!("Failed to update Caso category: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update case category: {}", e)})));
+    }
+
+    if let Err(e) = transaction
+        .execute(
+            "UPDATE Categoria_Sugerida SET status = 'aceita', data_decisao = CURRENT_TIMESTAMP WHERE id_sugestao = $1;",
+            &[&payload.id_sugestao],
+        )
+        .await
+    {
+        eprintln!("Failed to accept category suggestion: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to accept category suggestion: {}", e)})));
+    }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Caso, id_caso, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Sugestão aceita e categoria atribuída ao caso.", "id_categoria_caso": id_categoria_caso})))
+}
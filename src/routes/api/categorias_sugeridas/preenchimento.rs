@@ -0,0 +1,61 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::ia::provider_from_env;
+use tuono_app::ia::auditoria::ContextoAuditoria;
+use tuono_app::ia::classificacao::{iniciar_preenchimento, situacao_preenchimento};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize, Default)]
+struct PreenchimentoPayload {
+    modelo: Option<String>,
+}
+
+// GET /api/categorias_sugeridas/preenchimento (andamento do preenchimento em lote)
+#[tuono_lib::api(GET)]
+async fn andamento_preenchimento(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        return resposta;
+    }
+
+    (StatusCode::OK, Json(json!(situacao_preenchimento())))
+}
+
+// POST /api/categorias_sugeridas/preenchimento (sugere categorias para todos os casos sem categoria)
+#[tuono_lib::api(POST)]
+async fn iniciar_preenchimento_categorias(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    iniciar_preenchimento_categorias_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn iniciar_preenchimento_categorias_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    // O corpo é opcional
+    let payload: PreenchimentoPayload = _req.body().unwrap_or_default();
+
+    // A auditoria é montada por caso, em `iniciar_preenchimento`
+    let llm = match provider_from_env(fetch) {
+        Ok(llm) => llm,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let contexto = ContextoAuditoria::new("classificacao_caso", Vec::new()).por(&usuario.email);
+    if !iniciar_preenchimento(llm, contexto, payload.modelo) {
+        return (StatusCode::CONFLICT, Json(json!({"error": "Já existe um preenchimento de categorias em execução.", "andamento": situacao_preenchimento()})));
+    }
+
+    (StatusCode::ACCEPTED, Json(json!({"message": "Preenchimento de categorias iniciado.", "andamento": situacao_preenchimento()})))
+}
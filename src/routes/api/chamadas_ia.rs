@@ -0,0 +1,123 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::{connect_db, extract_query_values};
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde_json::{json, Value};
+
+/// Limite padrão (e máximo) de registros devolvidos por consulta.
+const LIMITE_PADRAO: i64 = 100;
+const LIMITE_MAXIMO: i64 = 500;
+
+fn chamada_json(row: &tokio_postgres::Row) -> Value {
+    let data_chamada: Option<NaiveDateTime> = row.get("data_chamada");
+    json!({
+        "id_chamada": row.get::<_, i32>("id_chamada"),
+        "data_chamada": data_chamada.map(|d| d.to_string()),
+        "origem": row.get::<_, String>("origem"),
+        "usuario": row.get::<_, Option<String>>("usuario"),
+        "provedor": row.get::<_, String>("provedor"),
+        "operacao": row.get::<_, String>("operacao"),
+        "modelo": row.get::<_, String>("modelo"),
+        "ids_documento": row.get::<_, Vec<i32>>("ids_documento"),
+        "ids_caso": row.get::<_, Vec<i32>>("ids_caso"),
+        "id_versao_prompt": row.get::<_, Option<i32>>("id_versao_prompt"),
+        "hash_prompt": row.get::<_, String>("hash_prompt"),
+        "prompt": row.get::<_, Option<String>>("prompt"),
+        "resposta": row.get::<_, Option<String>>("resposta"),
+        "prompt_tokens": row.get::<_, Option<i64>>("prompt_tokens"),
+        "completion_tokens": row.get::<_, Option<i64>>("completion_tokens"),
+        "total_duration_ms": row.get::<_, Option<i64>>("total_duration_ms"),
+        "load_duration_ms": row.get::<_, Option<i64>>("load_duration_ms"),
+        "prompt_eval_duration_ms": row.get::<_, Option<i64>>("prompt_eval_duration_ms"),
+        "eval_duration_ms": row.get::<_, Option<i64>>("eval_duration_ms"),
+        "latencia_ms": row.get::<_, i64>("latencia_ms"),
+        "sucesso": row.get::<_, bool>("sucesso"),
+        "erro": row.get::<_, Option<String>>("erro"),
+    })
+}
+
+// GET /api/chamadas_ia (registro de chamadas ao LLM)
+// Filtros opcionais: ?id=, origem, modelo, usuario, id_documento, inicio, fim (AAAA-MM-DD), limite
+#[tuono_lib::api(GET)]
+async fn chamadas_ia(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        return resposta;
+    }
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(_) if query_string.trim().is_empty() => HashMap::new(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Some(id_str) = query_values.get("id") {
+        let id_chamada = match id_str.parse::<i32>() {
+            Ok(id) => id,
+            Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID da chamada deve ser um número inteiro."}))),
+        };
+        return match client_db.query_opt("SELECT * FROM Llm_Chamada WHERE id_chamada = $1;", &[&id_chamada]).await {
+            Ok(Some(row)) => (StatusCode::OK, Json(chamada_json(&row))),
+            Ok(None) => (StatusCode::NOT_FOUND, Json(json!({"error": "Chamada não encontrada."}))),
+            Err(e) => {
+                eprintln!("Failed to fetch LLM call: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch LLM call: {}", e)})))
+            }
+        };
+    }
+
+    let id_documento = match query_values.get("id_documento").map(|v| v.parse::<i32>()).transpose() {
+        Ok(id) => id,
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))),
+    };
+    let data = |campo: &str| query_values.get(campo).map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d")).transpose();
+    let (inicio, fim) = match (data("inicio"), data("fim")) {
+        (Ok(inicio), Ok(fim)) => (inicio, fim),
+        _ => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Datas devem estar no formato AAAA-MM-DD."}))),
+    };
+    let limite = match query_values.get("limite").map(|v| v.parse::<i64>()).transpose() {
+        Ok(limite) => limite.unwrap_or(LIMITE_PADRAO).clamp(1, LIMITE_MAXIMO),
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "O limite deve ser um número inteiro."}))),
+    };
+    let origem = query_values.get("origem");
+    let modelo = query_values.get("modelo");
+    let usuario = query_values.get("usuario");
+
+    let rows = match client_db
+        .query(
+            "SELECT * FROM Llm_Chamada
+             WHERE ($1::VARCHAR IS NULL OR origem = $1)
+               AND ($2::VARCHAR IS NULL OR modelo = $2)
+               AND ($3::VARCHAR IS NULL OR usuario = $3)
+               AND ($4::INTEGER IS NULL OR $4 = ANY(ids_documento))
+               AND ($5::DATE IS NULL OR data_chamada >= $5)
+               AND ($6::DATE IS NULL OR data_chamada < $6 + 1)
+             ORDER BY data_chamada DESC, id_chamada DESC
+             LIMIT $7;",
+            &[&origem, &modelo, &usuario, &id_documento, &inicio, &fim, &limite],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch LLM calls: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch LLM calls: {}", e)})));
+        }
+    };
+
+    let chamadas: Vec<Value> = rows.iter().map(chamada_json).collect();
+    (StatusCode::OK, Json(json!(chamadas)))
+}
@@ -1,13 +1,20 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
 use tuono_app::{connect_db, extract_query_values}; // Re-importando para qualified calls
 
 use serde_json::{json, Value};
 use chrono::NaiveDate;  
 
 #[tuono_lib::api(GET)]  
-async fn cliente(_req: Request) -> Json<Value> { 
+async fn cliente(_req: Request) -> impl IntoResponse { 
+    let usuario = match exigir_permissao(&_req, Permissao::VerClientes).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+
     let query_string = _req.uri.query().unwrap_or(""); 
     let query_values_result = tuono_app::extract_query_values(query_string); 
 
@@ -17,7 +24,17 @@ async fn cliente(_req: Request) -> Json<Value> {
             eprintln!("Failed to connect to database: {}", e); 
             return Json(json!({ 
                 "error": format!("Database connection error: {}", e) 
-            }));
+            })).into_response();
+        }
+    };
+
+    // Só clientes com algum caso visível para o usuário (nulo = todos); os
+    // demais respondem como inexistentes
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)}))).into_response();
         }
     };
 
@@ -28,7 +45,7 @@ async fn cliente(_req: Request) -> Json<Value> {
                 Err(_) => { 
                     return Json(json!({ 
                         "error": "ID parameter must be an integer." 
-                    }));
+                    })).into_response();
                 }
             };
 
@@ -41,8 +58,9 @@ async fn cliente(_req: Request) -> Json<Value> {
                     FROM Cliente c
                     LEFT JOIN Pessoa_Fisica pf ON c.id_cliente = pf.id_cliente
                     LEFT JOIN Pessoa_Juridica pj ON c.id_cliente = pj.id_cliente
-                    WHERE c.id_cliente = $1;",
-                    &[&id], 
+                    WHERE c.id_cliente = $1
+                      AND ($2::INTEGER[] IS NULL OR EXISTS (SELECT 1 FROM Caso k WHERE k.id_cliente = c.id_cliente AND k.id_caso = ANY($2)));",
+                    &[&id, &ids_visiveis], 
                 )
                 .await
             {
@@ -51,14 +69,14 @@ async fn cliente(_req: Request) -> Json<Value> {
                     eprintln!("Failed to execute query: {}", e); 
                     return Json(json!({ 
                         "error": format!("Failed to fetch client: {}", e) 
-                    }));
+                    })).into_response();
                 }
             };
 
             if rows.is_empty() { 
                 return Json(json!({ 
                     "error": "Client not found." 
-                }));
+                })).into_response();
             }
 
             let row = &rows[0]; 
@@ -76,14 +94,16 @@ async fn cliente(_req: Request) -> Json<Value> {
                     "cpf": row.get::<_, Option<String>>("cpf"),
                     "cnpj": row.get::<_, Option<String>>("cnpj"),
                 })
-            );
+            ).into_response();
         }
     }
 
     let rows = match client_db
         .query(
-            "SELECT id_cliente, nome FROM Cliente ORDER BY nome ASC;", 
-            &[], 
+            "SELECT c.id_cliente, c.nome FROM Cliente c
+             WHERE $1::INTEGER[] IS NULL OR EXISTS (SELECT 1 FROM Caso k WHERE k.id_cliente = c.id_cliente AND k.id_caso = ANY($1))
+             ORDER BY c.nome ASC;", 
+            &[&ids_visiveis], 
         )
         .await
     {
@@ -92,7 +112,7 @@ async fn cliente(_req: Request) -> Json<Value> {
             eprintln!("Failed to fetch all clients for lookup: {}", e); 
             return Json(json!({ 
                 "error": format!("Failed to fetch clients list: {}", e) 
-            }));
+            })).into_response();
         }
     };
 
@@ -103,11 +123,17 @@ async fn cliente(_req: Request) -> Json<Value> {
         })
     }).collect();
 
-    Json(json!(clients_list))
+    Json(json!(clients_list)).into_response()
 }
 
 #[tuono_lib::api(POST)]
 async fn create_client(_req: Request) -> impl IntoResponse { 
+    let usuario = match exigir_permissao(&_req, Permissao::EditarClientes).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let query_string = _req.uri.query().unwrap_or(""); 
     println!("Query Recebida para POST: {}", query_string);
     let query_values = match extract_query_values(query_string) { 
@@ -192,6 +218,12 @@ async fn create_client(_req: Request) -> impl IntoResponse {
         return (StatusCode::BAD_REQUEST, Json(json!({"error": "Invalid tipoCliente provided."}))); 
     }
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Cliente, client_id, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
     if let Err(e) = transaction.commit().await { 
         eprintln!("Failed to commit transaction: {}", e); 
         return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)}))); 
@@ -202,6 +234,12 @@ async fn create_client(_req: Request) -> impl IntoResponse {
 
 #[tuono_lib::api(PUT)] 
 async fn update_client(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarClientes).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let query_string = _req.uri.query().unwrap_or(""); 
     println!("Query Recebida para PUT: {}", query_string);
     let query_values = match extract_query_values(query_string) { 
@@ -257,6 +295,15 @@ async fn update_client(_req: Request) -> impl IntoResponse {
         }
     };
 
+    let antes = match auditoria::estado(&transaction, Entidade::Cliente, id).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Cliente state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load client: {}", e)})));
+        }
+    };
+
     let update_client_query = "UPDATE Cliente SET nome = $1, email = $2, telefone = $3, endereco = $4 WHERE id_cliente = $5;";
     if let Err(e) = transaction.execute(update_client_query, &[&nome, &email, &telefone, &endereco, &id]).await {
         eprintln!("Failed to update Cliente: {}", e);
@@ -347,6 +394,11 @@ async fn update_client(_req: Request) -> impl IntoResponse {
         }
     }
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Cliente, id, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
 
     if let Err(e) = transaction.commit().await { 
         eprintln!("Failed to commit transaction: {}", e); 
@@ -358,6 +410,12 @@ async fn update_client(_req: Request) -> impl IntoResponse {
 
 #[tuono_lib::api(DELETE)]
 async fn delete_client(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarClientes).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let query_string = _req.uri.query().unwrap_or(""); 
     println!("Query Recebida para DELETE: {}", query_string);
     let query_values = match extract_query_values(query_string) { 
@@ -394,6 +452,15 @@ async fn delete_client(_req: Request) -> impl IntoResponse {
         }
     };
 
+    let antes = match auditoria::estado(&transaction, Entidade::Cliente, id).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Cliente state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load client: {}", e)})));
+        }
+    };
+
     let client_type_query = "SELECT cpf, cnpj FROM Cliente c LEFT JOIN Pessoa_Fisica pf ON c.id_cliente = pf.id_cliente LEFT JOIN Pessoa_Juridica pj ON c.id_cliente = pj.id_cliente WHERE c.id_cliente = $1;";
     let client_rows = match transaction.query(client_type_query, &[&id]).await {
         Ok(rows) => rows,
@@ -442,6 +509,11 @@ async fn delete_client(_req: Request) -> impl IntoResponse {
     match transaction.execute(delete_client_query, &[&id]).await { 
         Ok(rows_affected) => { 
             if rows_affected > 0 { 
+                if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Cliente, id, antes).await {
+                    eprintln!("Failed to write audit record: {}", e);
+                    let _ = transaction.rollback().await;
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+                }
                 match transaction.commit().await {
                     Ok(_) => (StatusCode::OK, Json(json!({"message": "Client deleted successfully."}))),
                     Err(e) => {
@@ -0,0 +1,129 @@
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Acao, Autor, Entidade};
+use tuono_app::connect_db;
+use tuono_app::lgpd::{anonimizar_cliente, ERRO_JA_ANONIMIZADO};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Frase que o corpo do POST deve trazer: a anonimização não tem volta.
+const CONFIRMACAO: &str = "ANONIMIZAR";
+
+#[derive(Debug, Deserialize)]
+struct AnonimizacaoPayload {
+    confirmacao: Option<String>,
+}
+
+fn id_do_cliente(_req: &Request) -> Result<i32, (StatusCode, Json<Value>)> {
+    match _req.params.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Ok(id),
+        _ => Err((StatusCode::BAD_REQUEST, Json(json!({"error": "ID do cliente deve ser um número inteiro."})))),
+    }
+}
+
+// GET /api/clientes/{id}/anonimizacao (prévia: o que seria anonimizado e o que seria mantido)
+#[tuono_lib::api(GET)]
+async fn previa_anonimizacao(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::AtenderTitular).await {
+        return resposta;
+    }
+    let id_cliente = match id_do_cliente(&_req) {
+        Ok(id) => id,
+        Err(resposta) => return resposta,
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    match anonimizar_cliente(&client_db, id_cliente, false).await {
+        Ok(Some(relatorio)) => (StatusCode::OK, Json(json!(relatorio))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({"error": "Cliente não encontrado."}))),
+        Err(e) if e == ERRO_JA_ANONIMIZADO => (StatusCode::CONFLICT, Json(json!({"error": e}))),
+        Err(e) => {
+            eprintln!("Failed to build anonymization report: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to build anonymization report: {}", e)})))
+        }
+    }
+}
+
+// POST /api/clientes/{id}/anonimizacao {"confirmacao": "ANONIMIZAR"} (aplica e devolve o relatório)
+#[tuono_lib::api(POST)]
+async fn anonimizar(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::AtenderTitular).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let id_cliente = match id_do_cliente(&_req) {
+        Ok(id) => id,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: AnonimizacaoPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+    if payload.confirmacao.as_deref() != Some(CONFIRMACAO) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"error": format!("A anonimização não pode ser desfeita. Confirme enviando \"confirmacao\": \"{}\".", CONFIRMACAO)})),
+        );
+    }
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let relatorio = match anonimizar_cliente(&transaction, id_cliente, true).await {
+        Ok(Some(relatorio)) => relatorio,
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Cliente não encontrado."})));
+        }
+        Err(e) => {
+            let _ = transaction.rollback().await;
+            if e == ERRO_JA_ANONIMIZADO {
+                return (StatusCode::CONFLICT, Json(json!({"error": e})));
+            }
+            eprintln!("Failed to anonymize client {}: {}", id_cliente, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to anonymize client: {}", e)})));
+        }
+    };
+
+    // O registro leva só o relatório: guardar o estado anterior desfaria a anonimização
+    let relatorio_json = json!(relatorio);
+    if let Err(e) = auditoria::registrar(&transaction, &Autor::new(&_req, &usuario), Entidade::Cliente, id_cliente, Acao::Anonimizar, None, Some(&relatorio_json)).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(relatorio_json))
+}
@@ -0,0 +1,51 @@
+use tuono_lib::axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::connect_db;
+use tuono_app::lgpd::exportar_cliente;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde_json::json;
+
+// GET /api/clientes/{id}/export (ZIP com os dados do cliente, para pedidos de acesso do titular)
+#[tuono_lib::api(GET)]
+async fn exportar_dados_cliente(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::AtenderTitular).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Leitura).await {
+        return resposta;
+    }
+
+    let id_cliente = match _req.params.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        _ => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do cliente deve ser um número inteiro."}))).into_response(),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)}))).into_response();
+        }
+    };
+
+    match exportar_cliente(&client_db, id_cliente).await {
+        Ok(Some(zip)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/zip"));
+            headers.insert(
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&format!("attachment; filename=\"cliente_{}_dados.zip\"", id_cliente)).unwrap(),
+            );
+            (StatusCode::OK, headers, zip).into_response()
+        }
+        Ok(None) => (StatusCode::NOT_FOUND, Json(json!({"error": "Cliente não encontrado."}))).into_response(),
+        Err(e) => {
+            eprintln!("Failed to export client data: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to export client data: {}", e)}))).into_response()
+        }
+    }
+}
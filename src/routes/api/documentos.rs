@@ -1,7 +1,13 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::{StatusCode, HeaderMap, HeaderValue, header};
 use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_documentos, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::cifra::{arquivo_da_linha, cifrar_arquivo, ArquivoCifrado};
 use tuono_app::{connect_db, extract_query_values};
+use tuono_app::limite::{limitar_usuario, Politica};
+use tuono_app::ia::{analise, cliente_http, prazos, provider_from_env};
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
 
 use serde_json::{json, Value};
 use chrono::NaiveDate;
@@ -31,6 +37,11 @@ struct DocumentUpdatePayload {
 
 #[tuono_lib::api(GET)]
 async fn documento(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+
     let query_string = _req.uri.query().unwrap_or("");
     let query_values_result = tuono_app::extract_query_values(query_string);
 
@@ -46,13 +57,19 @@ async fn documento(_req: Request) -> impl IntoResponse {
     if let Ok(values) = &query_values_result {
         if let (Some(id_str), Some(download_str)) = (values.get("id"), values.get("download")) {
             if download_str == "true" {
+                if let Err(resposta) = limitar_usuario(&usuario, Politica::Leitura).await {
+                    return resposta;
+                }
                 let id = match id_str.parse::<i32>() {
                     Ok(id) => id,
                     Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))).into_response(),
                 };
+                if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id]).await {
+                    return resposta.into_response();
+                }
 
                 let row = match client_db
-                    .query_opt("SELECT nome_arquivo, arquivo FROM Documento WHERE id_documento = $1;", &[&id])
+                    .query_opt("SELECT nome_arquivo, arquivo, chave_cifrada, id_chave_mestra FROM Documento WHERE id_documento = $1;", &[&id])
                     .await
                 {
                     Ok(row) => row,
@@ -64,7 +81,13 @@ async fn documento(_req: Request) -> impl IntoResponse {
 
                 if let Some(r) = row {
                     let nome_arquivo: String = r.get("nome_arquivo");
-                    let arquivo_bytes: Option<Vec<u8>> = r.get("arquivo");
+                    let arquivo_bytes = match arquivo_da_linha(&r) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            eprintln!("Failed to decrypt document {}: {}", id, e);
+                            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e}))).into_response();
+                        }
+                    };
 
                     if let Some(bytes) = arquivo_bytes {
                         let mime_type = mime_guess::from_path(&nome_arquivo)
@@ -88,6 +111,15 @@ async fn documento(_req: Request) -> impl IntoResponse {
         }
     }
 
+    // Na listagem, só documentos dos casos visíveis ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)}))).into_response();
+        }
+    };
+
     let mut is_specific_id_requested = false; // Flag para rastrear se um ID foi solicitado
     let rows_result = if let Ok(values) = query_values_result {
         if let Some(id_str) = values.get("id").cloned() {
@@ -96,9 +128,12 @@ async fn documento(_req: Request) -> impl IntoResponse {
                 Ok(id) => id,
                 Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID parameter must be an integer."}))).into_response(),
             };
+            if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id]).await {
+                return resposta.into_response();
+            }
             match client_db
                 .query(
-                    "SELECT id_documento, id_caso, descricao, data_envio, nome_arquivo FROM Documento WHERE id_documento = $1;",
+                    "SELECT d.id_documento, d.id_caso, d.descricao, d.data_envio, d.nome_arquivo, d.versao, d.gerado_por_ia, m.status AS status_minuta FROM Documento d LEFT JOIN Minuta_Ia m ON m.id_documento = d.id_documento WHERE d.id_documento = $1;",
                     &[&id],
                 )
                 .await
@@ -113,8 +148,8 @@ async fn documento(_req: Request) -> impl IntoResponse {
             // Nenhum ID na query, retorna todos
             match client_db
                 .query(
-                    "SELECT id_documento, id_caso, descricao, data_envio, nome_arquivo FROM Documento ORDER BY data_envio DESC;",
-                    &[],
+                    "SELECT d.id_documento, d.id_caso, d.descricao, d.data_envio, d.nome_arquivo, d.versao, d.gerado_por_ia, m.status AS status_minuta FROM Documento d LEFT JOIN Minuta_Ia m ON m.id_documento = d.id_documento WHERE ($1::INTEGER[] IS NULL OR d.id_caso = ANY($1)) ORDER BY d.data_envio DESC;",
+                    &[&ids_visiveis],
                 )
                 .await
             {
@@ -128,8 +163,8 @@ async fn documento(_req: Request) -> impl IntoResponse {
     } else { // Erro ao extrair query values, mas não é um download, então lista tudo
         match client_db
             .query(
-                "SELECT id_documento, id_caso, descricao, data_envio, nome_arquivo FROM Documento ORDER BY data_envio DESC;",
-                &[],
+                "SELECT d.id_documento, d.id_caso, d.descricao, d.data_envio, d.nome_arquivo, d.versao, d.gerado_por_ia, m.status AS status_minuta FROM Documento d LEFT JOIN Minuta_Ia m ON m.id_documento = d.id_documento WHERE ($1::INTEGER[] IS NULL OR d.id_caso = ANY($1)) ORDER BY d.data_envio DESC;",
+                &[&ids_visiveis],
             )
             .await
         {
@@ -150,6 +185,9 @@ async fn documento(_req: Request) -> impl IntoResponse {
             "descricao": row.get::<_, String>("descricao"),
             "data_envio": data_envio.map(|d| d.to_string()),
             "nome_arquivo": row.get::<_, String>("nome_arquivo"),
+            "versao": row.get::<_, i32>("versao"),
+            "gerado_por_ia": row.get::<_, bool>("gerado_por_ia"),
+            "status_minuta": row.get::<_, Option<String>>("status_minuta"), // "pendente" ou "aprovada" para minutas de IA
         })
     }).collect();
 
@@ -157,8 +195,14 @@ async fn documento(_req: Request) -> impl IntoResponse {
         if documents.is_empty() {
             return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."}))).into_response();
         } else {
-            // retorne o primeiro (e único) objeto diretamente, não um array.
-            return (StatusCode::OK, Json(documents[0].clone())).into_response();
+            // retorne o primeiro (e único) objeto diretamente, não um array, com a última análise de IA.
+            let mut documento = documents[0].clone();
+            let id_documento = documento["id_documento"].as_i64().unwrap_or_default() as i32;
+            match analise::ultima_analise(&client_db, id_documento).await {
+                Ok(analise) => documento["analise"] = json!(analise),
+                Err(e) => eprintln!("Failed to fetch document analysis: {}", e),
+            }
+            return (StatusCode::OK, Json(documento)).into_response();
         }
     }
 
@@ -166,7 +210,13 @@ async fn documento(_req: Request) -> impl IntoResponse {
 }
 
 #[tuono_lib::api(POST)]
-async fn create_documento(_req: Request) -> impl IntoResponse {
+async fn create_documento(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let payload: DocumentPayload = match _req.body() {
         Ok(p) => p,
         Err(e) => {
@@ -182,6 +232,13 @@ async fn create_documento(_req: Request) -> impl IntoResponse {
             return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Conteúdo Base64 inválido: {}", e)})));
         }
     };
+    let arquivo = match cifrar_arquivo(&arquivo_bytes) {
+        Ok(arquivo) => arquivo,
+        Err(e) => {
+            eprintln!("Failed to encrypt document: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+        }
+    };
 
     let id_caso = payload.id_caso;
     let descricao = payload.descricao;
@@ -191,7 +248,7 @@ async fn create_documento(_req: Request) -> impl IntoResponse {
     };
     let nome_arquivo = payload.nome_arquivo;
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -199,31 +256,67 @@ async fn create_documento(_req: Request) -> impl IntoResponse {
         }
     };
 
-    let caso_exists = client_db.query_opt("SELECT 1 FROM Caso WHERE id_caso = $1;", &[&id_caso]).await;
-    if let Err(e) = caso_exists {
-        eprintln!("Failed to check Caso existence: {}", e);
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database error checking Case ID: {}", e)})));
-    }
-    if caso_exists.unwrap().is_none() {
-        return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso (id_caso) não existe."})));
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
     }
 
-    let insert_documento_query = "INSERT INTO Documento (id_caso, descricao, data_envio, nome_arquivo, arquivo) VALUES ($1, $2, $3, $4, $5) RETURNING id_documento;";
-    let rows = match client_db.query(insert_documento_query, &[&id_caso, &descricao, &data_envio, &nome_arquivo, &arquivo_bytes]).await {
+    let insert_documento_query = "INSERT INTO Documento (id_caso, descricao, data_envio, nome_arquivo, arquivo, chave_cifrada, id_chave_mestra)
+                                  VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id_documento;";
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let rows = match transaction.query(insert_documento_query, &[&id_caso, &descricao, &data_envio, &nome_arquivo, &arquivo.arquivo, &arquivo.chave_cifrada, &arquivo.id_chave_mestra]).await {
         Ok(rows) => rows,
         Err(e) => {
             eprintln!("Failed to insert Documento: {}", e);
+            let _ = transaction.rollback().await;
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to insert document: {}", e)})));
         }
     };
 
     let id_documento: i32 = rows[0].get("id_documento");
 
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Documento, id_documento, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    // Resumo, extração de fatos-chave e prazos rodam em segundo plano, dentro do
+    // limite de IA do usuário (acima dele, a análise pode ser pedida depois em /api/analises_documento)
+    if limitar_usuario(&usuario, Politica::Ia).await.is_err() {
+        eprintln!("Document analysis skipped: AI rate limit reached for user {}", usuario.id_usuario);
+    } else {
+        match provider_from_env(fetch) {
+            Ok(llm) => {
+                analise::agendar_analise(auditar(llm.clone(), ContextoAuditoria::new("analise_documento", vec![id_documento]).por(&usuario.email)), id_documento, None);
+                prazos::agendar_sugestoes(auditar(llm, ContextoAuditoria::new("prazos_documento", vec![id_documento]).por(&usuario.email)), id_documento, None);
+            }
+            Err(e) => eprintln!("Document analysis skipped: {}", e),
+        }
+    }
+
     (StatusCode::CREATED, Json(json!({"message": "Documento adicionado com sucesso", "id_documento": id_documento})))
 }
 
 #[tuono_lib::api(PUT)]
 async fn update_documento(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let payload: DocumentUpdatePayload = match _req.body() {
         Ok(p) => p,
         Err(e) => {
@@ -241,23 +334,30 @@ async fn update_documento(_req: Request) -> impl IntoResponse {
     };
     let nome_arquivo = payload.nome_arquivo;
 
-    let owned_bytes: Vec<u8>;
-    let arquivo_bytes_ref: Option<&Vec<u8>> = if let Some(base64_str) = payload.arquivo_base64 {
-        match general_purpose::STANDARD.decode(base64_str) {
-            Ok(bytes) => {
-                owned_bytes = bytes;
-                Some(&owned_bytes)
-            },
+    let owned_arquivo: ArquivoCifrado;
+    let arquivo_ref: Option<&ArquivoCifrado> = if let Some(base64_str) = payload.arquivo_base64 {
+        let bytes = match general_purpose::STANDARD.decode(base64_str) {
+            Ok(bytes) => bytes,
             Err(e) => {
                 eprintln!("Failed to decode Base64 content for update: {}", e);
                 return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Conteúdo Base64 inválido para atualização: {}", e)})));
             }
+        };
+        match cifrar_arquivo(&bytes) {
+            Ok(arquivo) => {
+                owned_arquivo = arquivo;
+                Some(&owned_arquivo)
+            }
+            Err(e) => {
+                eprintln!("Failed to encrypt document: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+            }
         }
     } else {
         None
     };
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -265,13 +365,12 @@ async fn update_documento(_req: Request) -> impl IntoResponse {
         }
     };
 
-    let caso_exists = client_db.query_opt("SELECT 1 FROM Caso WHERE id_caso = $1;", &[&id_caso]).await;
-    if let Err(e) = caso_exists {
-        eprintln!("Failed to check Caso existence: {}", e);
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database error checking Case ID: {}", e)})));
+    // O documento e o caso de destino precisam estar no escopo do usuário
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
     }
-    if caso_exists.unwrap().is_none() {
-        return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso (id_caso) não existe."})));
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, id_caso).await {
+        return resposta;
     }
 
     let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> = Vec::new();
@@ -283,34 +382,84 @@ async fn update_documento(_req: Request) -> impl IntoResponse {
 
     let update_query;
 
-    if let Some(bytes_ref) = arquivo_bytes_ref {
-        params.push(bytes_ref);
+    if let Some(arquivo) = arquivo_ref {
+        params.push(&arquivo.arquivo);
+        params.push(&arquivo.chave_cifrada);
+        params.push(&arquivo.id_chave_mestra);
         params.push(&id_documento);
-        update_query = "UPDATE Documento SET id_caso = $1, descricao = $2, data_envio = $3, nome_arquivo = $4, arquivo = $5 WHERE id_documento = $6;";
+        update_query = "UPDATE Documento SET id_caso = $1, descricao = $2, data_envio = $3, nome_arquivo = $4, arquivo = $5, chave_cifrada = $6, id_chave_mestra = $7 WHERE id_documento = $8;";
     } else {
         params.push(&id_documento);
         update_query = "UPDATE Documento SET id_caso = $1, descricao = $2, data_envio = $3, nome_arquivo = $4 WHERE id_documento = $5;";
     }
     
-    match client_db.execute(update_query, &params).await {
-        Ok(rows_affected) => {
-            if rows_affected > 0 {
-                (StatusCode::OK, Json(json!({"message": "Documento atualizado com sucesso"})))
-            } else {
-                (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."})))
-            }
-        },
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Documento, id_documento).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Documento state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load document: {}", e)})));
+        }
+    };
+
+    let rows_affected = match transaction.execute(update_query, &params).await {
+        Ok(rows_affected) => rows_affected,
         Err(e) => {
             eprintln!("Failed to update Documento: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update document: {}", e)})))
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update document: {}", e)})));
         }
+    };
+    if rows_affected == 0 {
+        let _ = transaction.rollback().await;
+        return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."})));
+    }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Documento, id_documento, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
     }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    // Um novo arquivo invalida a análise anterior (dentro do limite de IA, como no upload)
+    if arquivo_ref.is_some() {
+        if limitar_usuario(&usuario, Politica::Ia).await.is_err() {
+            eprintln!("Document analysis skipped: AI rate limit reached for user {}", usuario.id_usuario);
+        } else {
+            match provider_from_env(cliente_http()) {
+                Ok(llm) => {
+                    analise::agendar_analise(auditar(llm.clone(), ContextoAuditoria::new("analise_documento", vec![id_documento]).por(&usuario.email)), id_documento, None);
+                    prazos::agendar_sugestoes(auditar(llm, ContextoAuditoria::new("prazos_documento", vec![id_documento]).por(&usuario.email)), id_documento, None);
+                }
+                Err(e) => eprintln!("Document analysis skipped: {}", e),
+            }
+        }
+    }
+    (StatusCode::OK, Json(json!({"message": "Documento atualizado com sucesso"})))
 }
 
 #[tuono_lib::api(DELETE)]
 async fn delete_documento(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+    let autor = Autor::new(&_req, &usuario);
+
     let query_string = _req.uri.query().unwrap_or("");
-    println!("Query Recebida para DELETE (Documento): {}", query_string);
     let query_values = match tuono_app::extract_query_values(query_string) {
         Ok(value) => value,
         Err(e) => {
@@ -328,7 +477,7 @@ async fn delete_documento(_req: Request) -> impl IntoResponse {
         Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))),
     };
 
-    let client_db = match tuono_app::connect_db().await {
+    let mut client_db = match tuono_app::connect_db().await {
         Ok(client) => client,
         Err(e) => {
             eprintln!("Failed to connect to database: {}", e);
@@ -336,18 +485,51 @@ async fn delete_documento(_req: Request) -> impl IntoResponse {
         }
     };
 
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
+    }
+
     let delete_documento_query = "DELETE FROM Documento WHERE id_documento = $1;";
-    match client_db.execute(delete_documento_query, &[&id_documento]).await {
-        Ok(rows_affected) => {
-            if rows_affected > 0 {
-                (StatusCode::OK, Json(json!({"message": "Documento excluído com sucesso."})))
-            } else {
-                (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."})))
-            }
-        },
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Documento, id_documento).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Documento state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load document: {}", e)})));
+        }
+    };
+
+    let rows_affected = match transaction.execute(delete_documento_query, &[&id_documento]).await {
+        Ok(rows_affected) => rows_affected,
         Err(e) => {
             eprintln!("Failed to delete Documento: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to delete document: {}", e)})))
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to delete document: {}", e)})));
         }
+    };
+    if rows_affected == 0 {
+        let _ = transaction.rollback().await;
+        return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."})));
     }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Documento, id_documento, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Documento excluído com sucesso."})))
 }
\ No newline at end of file
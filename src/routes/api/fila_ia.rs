@@ -0,0 +1,49 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::extract_query_values;
+use tuono_app::ia::fila::fila;
+
+use serde_json::json;
+
+// GET /api/fila_ia (situação da fila por modelo) ou ?id_requisicao= (posição de uma requisição do próprio usuário)
+#[tuono_lib::api(GET)]
+async fn fila_ia(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let id_requisicao = extract_query_values(query_string).ok().and_then(|values| values.get("id_requisicao").cloned());
+
+    match id_requisicao {
+        Some(id) => match fila().situacao_requisicao(usuario.id_usuario, &id) {
+            Some(situacao) => (StatusCode::OK, Json(json!(situacao))),
+            None => (StatusCode::NOT_FOUND, Json(json!({"error": "Requisição não está na fila."}))),
+        },
+        None => (StatusCode::OK, Json(json!(fila().situacao()))),
+    }
+}
+
+// DELETE /api/fila_ia?id_requisicao= (cancela uma requisição do próprio usuário, aguardando ou em execução)
+#[tuono_lib::api(DELETE)]
+async fn cancelar_requisicao_ia(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let id_requisicao = match extract_query_values(query_string).ok().and_then(|values| values.get("id_requisicao").cloned()) {
+        Some(id) => id,
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "id_requisicao é obrigatório."}))),
+    };
+
+    if fila().cancelar(usuario.id_usuario, &id_requisicao) {
+        (StatusCode::OK, Json(json!({"message": "Requisição cancelada."})))
+    } else {
+        (StatusCode::NOT_FOUND, Json(json!({"error": "Requisição não está na fila."})))
+    }
+}
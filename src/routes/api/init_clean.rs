@@ -1,15 +1,170 @@
+// Operações de depuração do banco (limpar, inicializar e popular). As três só
+// existem com `BANCO_OPERACOES_DEBUG=true` e pedem papel de admin e a frase de
+// confirmação em `?confirmacao=`; limpar e popular também se recusam a rodar
+// sobre dados que não vieram da carga fictícia, a menos que venha `?forcar=true`.
+// Inicializar aplica `migracao::migrar`, que só cria o que faltar; um banco
+// vazio, ainda sem usuários, é instalado pelo binário `migrar_banco`. Execuções
+// e recusas vão para a trilha de auditoria (entidade `banco`), que `clean` não
+// apaga: a tentativa é registrada antes de apagar as tabelas e o resultado, depois.
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::acesso::{sem_permissao, Permissao};
+use tuono_app::auditoria::{self, Acao, Autor, Entidade};
+use tuono_app::auth::{exigir_sessao, UsuarioSessao};
+use tuono_app::config::{self, Config};
+use std::sync::Arc;
 use serde_json::{json, Value};
+use tokio_postgres::Client as DbClient;
 
-use tuono_app::{connect_db};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::migracao;
 
 use chrono::{NaiveDate, NaiveTime};
 
+const CONFIRMACAO_LIMPAR: &str = "APAGAR TODOS OS DADOS";
+const CONFIRMACAO_INICIAR: &str = "INICIAR BANCO";
+const CONFIRMACAO_POPULAR: &str = "POPULAR BANCO";
+
+type Resposta = (StatusCode, Json<Value>);
+
+/// `confirmacao` e `forcar` da query string.
+fn parametros_da_operacao(req: &Request) -> (Option<String>, bool) {
+    let values = extract_query_values(req.uri.query().unwrap_or("")).unwrap_or_default();
+    let forcar = values.get("forcar").map(|v| v == "true").unwrap_or(false);
+    (values.get("confirmacao").cloned(), forcar)
+}
+
+fn exigir_confirmacao(confirmacao: Option<&str>, esperada: &str) -> Result<(), Resposta> {
+    if confirmacao.map(str::trim) != Some(esperada) {
+        return Err((StatusCode::BAD_REQUEST, Json(json!({"error": format!("Para confirmar, digite \"{}\" (parâmetro confirmacao).", esperada)}))));
+    }
+    Ok(())
+}
+
+/// Modo de depuração, papel de admin e frase de confirmação, nessa ordem.
+fn verificar_liberacao(config: &Config, sessao: Result<UsuarioSessao, Resposta>, confirmacao: Option<&str>, esperada: &str) -> Result<UsuarioSessao, Resposta> {
+    if !config.operacoes_banco.debug {
+        return Err((StatusCode::NOT_FOUND, Json(json!({"error": "Operação de depuração desabilitada (BANCO_OPERACOES_DEBUG)."}))));
+    }
+    let usuario = sessao?;
+    if !usuario.pode(Permissao::AdministrarBanco) {
+        return Err(sem_permissao());
+    }
+    exigir_confirmacao(confirmacao, esperada)?;
+    Ok(usuario)
+}
+
+/// Libera a operação ou registra a recusa na auditoria. A sessão é lida antes
+/// das verificações, mesmo com o modo desabilitado, para o registro dizer quem tentou.
+async fn liberar_operacao_debug(req: &Request, config: &Config, acao: Acao, esperada: &str) -> Result<(UsuarioSessao, bool), Resposta> {
+    let (confirmacao, forcar) = parametros_da_operacao(req);
+    let sessao = exigir_sessao(req).await;
+    let quem = sessao.as_ref().ok().cloned();
+    match verificar_liberacao(config, sessao, confirmacao.as_deref(), esperada) {
+        Ok(usuario) => Ok((usuario, forcar)),
+        Err(resposta) => {
+            registrar_desfecho(req, acao, quem.as_ref(), forcar, &resposta).await;
+            Err(resposta)
+        }
+    }
+}
+
+/// Registros que não vieram de `populate_db`: clientes diferentes dos fictícios,
+/// casos com número de processo fora da carga, documentos com arquivo enviado,
+/// usuários além do administrador inicial (`USUARIO_ADMIN_EMAIL`, recriado pelo
+/// `migrar_banco`) e casos compartilhados.
+async fn dados_fora_da_carga_ficticia(client: &DbClient, config: &Config) -> Result<Vec<String>, tokio_postgres::Error> {
+    let inicializado = client
+        .query_one("SELECT to_regclass('public.cliente') IS NOT NULL AS existe;", &[])
+        .await?
+        .get::<_, bool>("existe");
+    if !inicializado {
+        return Ok(Vec::new());
+    }
+
+    let (ids_clientes, nomes_clientes): (Vec<i32>, Vec<&str>) = clientes_ficticios().iter().map(|c| (c.0, c.1)).unzip();
+    let numeros_processo: Vec<&str> = casos_ficticios().iter().filter_map(|c| c.6).collect();
+    let email_admin = config.admin_inicial.as_ref().map(|admin| admin.email.to_lowercase());
+    let row = client
+        .query_one(
+            "SELECT
+                (SELECT COUNT(*) FROM Cliente c WHERE NOT EXISTS (
+                    SELECT 1 FROM unnest($1::INTEGER[], $2::TEXT[]) AS f(id, nome) WHERE f.id = c.id_cliente AND f.nome = c.nome
+                )) AS clientes,
+                (SELECT COUNT(*) FROM Caso WHERE numero_processo IS NULL OR NOT (numero_processo = ANY($3))) AS casos,
+                (SELECT COUNT(*) FROM Documento WHERE arquivo IS NOT NULL) AS documentos,
+                (SELECT COUNT(*) FROM Usuario WHERE $4::TEXT IS NULL OR email <> $4) AS usuarios,
+                (SELECT COUNT(*) FROM Caso_Acesso) AS compartilhamentos;",
+            &[&ids_clientes, &nomes_clientes, &numeros_processo, &email_admin],
+        )
+        .await?;
+
+    let mut encontrados = Vec::new();
+    for (coluna, descricao) in [
+        ("clientes", "cliente(s)"),
+        ("casos", "caso(s)"),
+        ("documentos", "documento(s) com arquivo"),
+        ("usuarios", "usuário(s) além do administrador inicial"),
+        ("compartilhamentos", "caso(s) compartilhado(s)"),
+    ] {
+        let total: i64 = row.get(coluna);
+        if total > 0 {
+            encontrados.push(format!("{} {}", total, descricao));
+        }
+    }
+    Ok(encontrados)
+}
+
+async fn recusar_se_houver_dados_reais(client: &DbClient, config: &Config, forcar: bool) -> Result<(), Resposta> {
+    if forcar {
+        return Ok(());
+    }
+    match dados_fora_da_carga_ficticia(client, config).await {
+        Ok(encontrados) if encontrados.is_empty() => Ok(()),
+        Ok(encontrados) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": format!("O banco tem dados que não são da carga fictícia ({}). Use forcar=true para continuar mesmo assim.", encontrados.join(", ")),
+            })),
+        )),
+        Err(e) => {
+            eprintln!("Failed to check for non-fixture data: {}", e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to check existing data: {}", e)}))))
+        }
+    }
+}
+
+/// Grava a operação na trilha de auditoria, numa transação própria.
+async fn registrar_operacao(req: &Request, acao: Acao, usuario: Option<&UsuarioSessao>, detalhes: &Value) -> Result<(), String> {
+    let mut client = connect_db().await?;
+    let transaction = client.transaction().await.map_err(|e| e.to_string())?;
+    let autor = Autor::da_requisicao(req, usuario);
+    auditoria::registrar(&transaction, &autor, Entidade::Banco, 0, acao, None, Some(detalhes)).await?;
+    transaction.commit().await.map_err(|e| e.to_string())
+}
+
+/// Resultado (ou recusa) da operação. Sem a tabela Auditoria, que só existe
+/// depois de inicializar, fica apenas o aviso no log.
+async fn registrar_desfecho(req: &Request, acao: Acao, usuario: Option<&UsuarioSessao>, forcada: bool, resposta: &Resposta) {
+    let detalhes = json!({
+        "status": resposta.0.as_u16(),
+        "resultado": resposta.1.0.get("message").or_else(|| resposta.1.0.get("error")),
+        "forcada": forcada,
+    });
+    if let Err(e) = registrar_operacao(req, acao, usuario, &detalhes).await {
+        eprintln!("Failed to audit database operation '{}': {}", acao.as_str(), e);
+    }
+}
+
 
 #[tuono_lib::api(DELETE)]
-async fn clean(_req: Request) -> impl IntoResponse {
+async fn clean(_req: Request, config: Arc<Config>) -> impl IntoResponse {
+    let (usuario, forcar) = match liberar_operacao_debug(&_req, &config, Acao::Limpar, CONFIRMACAO_LIMPAR).await {
+        Ok(liberacao) => liberacao,
+        Err(resposta) => return resposta,
+    };
+
     let client = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -20,9 +175,28 @@ async fn clean(_req: Request) -> impl IntoResponse {
         }
     };
 
+    if let Err(resposta) = recusar_se_houver_dados_reais(&client, &config, forcar).await {
+        registrar_desfecho(&_req, Acao::Limpar, Some(&usuario), forcar, &resposta).await;
+        return resposta;
+    }
+
+    // Sem o registro da tentativa, não apaga
+    let tentativa = json!({"resultado": "Tabelas serão apagadas.", "forcada": forcar});
+    if let Err(e) = registrar_operacao(&_req, Acao::Limpar, Some(&usuario), &tentativa).await {
+        eprintln!("Failed to audit database clean, aborting: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Falha ao registrar a operação na auditoria: {}", e)})));
+    }
+
+    let resposta = apagar_tabelas(&client).await;
+    registrar_desfecho(&_req, Acao::Limpar, Some(&usuario), forcar, &resposta).await;
+    resposta
+}
+
+/// Todas as tabelas, menos a Auditoria.
+async fn apagar_tabelas(client: &DbClient) -> Resposta {
     let query_tables = "
         SELECT tablename FROM pg_tables
-        WHERE schemaname = 'public';
+        WHERE schemaname = 'public' AND tablename <> 'auditoria';
     ";
 
     let rows = match client.query(query_tables, &[]).await {
@@ -47,133 +221,48 @@ async fn clean(_req: Request) -> impl IntoResponse {
         }
     }
 
-    (StatusCode::OK, Json(json!({"message": "All tables dropped successfully."})))
+    (StatusCode::OK, Json(json!({"message": "All tables dropped successfully (audit trail kept)."})))
 }
 
 
 #[tuono_lib::api(POST)]
 async fn init(_req: Request) -> impl IntoResponse {
-    let client = match connect_db().await {
+    // Só um handler por arquivo recebe o estado (ver `clean`)
+    let config = config::atual();
+    let (usuario, forcar) = match liberar_operacao_debug(&_req, &config, Acao::Inicializar, CONFIRMACAO_INICIAR).await {
+        Ok(liberacao) => liberacao,
+        Err(resposta) => return resposta,
+    };
+
+    let mut client = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
             return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()})));
         }
     };
 
-    if let Err(e) = client.batch_execute(
-        "
-        CREATE TABLE Cliente (
-            id_cliente SERIAL PRIMARY KEY,
-            nome VARCHAR(255) NOT NULL,
-            email VARCHAR(255),
-            telefone VARCHAR(20),
-            endereco TEXT,
-            data_cadastro DATE
-        );
-
-        CREATE TABLE Pessoa_Fisica (
-            id_cliente INTEGER PRIMARY KEY REFERENCES Cliente(id_cliente),
-            cpf VARCHAR(14) UNIQUE NOT NULL
-        );
-
-        CREATE TABLE Pessoa_Juridica (
-            id_cliente INTEGER PRIMARY KEY REFERENCES Cliente(id_cliente),
-            cnpj VARCHAR(18) UNIQUE NOT NULL
-        );
-
-        CREATE TABLE Advogado (
-            id_advogado SERIAL PRIMARY KEY,
-            nome VARCHAR(255) NOT NULL,
-            oab VARCHAR(20) UNIQUE NOT NULL,
-            telefone VARCHAR(20),
-            email VARCHAR(255),
-            especialidade VARCHAR(255)
-        );
-
-        CREATE TABLE Vara_Judicial (
-            id_vara_judicial SERIAL PRIMARY KEY,
-            nome_vara VARCHAR(255) NOT NULL,
-            cidade VARCHAR(100),
-            estado VARCHAR(50)
-        );
-
-        CREATE TABLE Status (
-            id_status SERIAL PRIMARY KEY,
-            descricao VARCHAR(255) NOT NULL,
-            data_modificacao DATE
-        );
-
-        CREATE TABLE Categoria_caso (
-            id_categoria_caso SERIAL PRIMARY KEY,
-            descricao VARCHAR(255) NOT NULL,
-            ativo BOOLEAN DEFAULT TRUE
-        );
-
-        CREATE TABLE Caso (
-            id_caso SERIAL PRIMARY KEY,
-            id_cliente INTEGER NOT NULL REFERENCES Cliente(id_cliente),
-            id_advogado INTEGER NOT NULL REFERENCES Advogado(id_advogado),
-            id_status INTEGER NOT NULL REFERENCES Status(id_status),
-            id_vara_judicial INTEGER REFERENCES Vara_Judicial(id_vara_judicial),
-            id_categoria_caso INTEGER REFERENCES Categoria_caso(id_categoria_caso),
-            descricao TEXT,
-            numero_processo VARCHAR(255) UNIQUE,
-            data_fechamento DATE,
-            data_abertura DATE NOT NULL
-        );
-
-        CREATE TABLE Andamento_processual (
-            id_andamento SERIAL PRIMARY KEY,
-            id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
-            descricao TEXT,
-            data_andamento DATE NOT NULL,
-            responsavel VARCHAR(255)
-        );
-
-        CREATE TABLE Audiencia (
-            id_audiencia SERIAL PRIMARY KEY,
-            id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
-            data_audiencia TIMESTAMP NOT NULL,
-            horario TIME,
-            descricao TEXT,
-            endereco TEXT,
-            tipo_audiencia VARCHAR(100)
-        );
-
-        CREATE TABLE Pecas (
-            id_peca SERIAL PRIMARY KEY,
-            id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
-            descricao TEXT,
-            data_registro DATE NOT NULL,
-            tipo_midia VARCHAR(100)
-        );
-
-        CREATE TABLE Documento (
-            id_documento SERIAL PRIMARY KEY,
-            id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
-            descricao TEXT,
-            data_envio DATE,
-            arquivo BYTEA,
-            nome_arquivo VARCHAR(255)
-        );
-
-        CREATE TABLE Tarefa (
-            id_tarefa SERIAL PRIMARY KEY,
-            id_caso INTEGER NOT NULL REFERENCES Caso(id_caso),
-            id_advogado INTEGER NOT NULL REFERENCES Advogado(id_advogado),
-            descricao TEXT,
-            data_tarefa DATE NOT NULL
-        );
-        ").await {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e.to_string()})));
-    }
-
-    (StatusCode::OK, Json(json!({"message": "Database initialized successfully"})))
+    // Cria só o que faltar (ver `migracao`); um banco vazio é instalado pelo `migrar_banco`
+    let resposta = match migracao::migrar(&mut client).await {
+        Ok(()) => (StatusCode::OK, Json(json!({"message": "Database initialized successfully"}))),
+        Err(e) => {
+            eprintln!("{}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})))
+        }
+    };
+    registrar_desfecho(&_req, Acao::Inicializar, Some(&usuario), forcar, &resposta).await;
+    resposta
 }
 
 
 #[tuono_lib::api(PUT)]
 async fn populate_db(_req: Request) -> impl IntoResponse {
+    // Só um handler por arquivo recebe o estado (ver `clean`)
+    let config = config::atual();
+    let (usuario, forcar) = match liberar_operacao_debug(&_req, &config, Acao::Popular, CONFIRMACAO_POPULAR).await {
+        Ok(liberacao) => liberacao,
+        Err(resposta) => return resposta,
+    };
+
     let mut client = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -181,6 +270,18 @@ async fn populate_db(_req: Request) -> impl IntoResponse {
         }
     };
 
+    // A carga sobrescreve os clientes de id fixo, então não pode cair sobre dados reais
+    if let Err(resposta) = recusar_se_houver_dados_reais(&client, &config, forcar).await {
+        registrar_desfecho(&_req, Acao::Popular, Some(&usuario), forcar, &resposta).await;
+        return resposta;
+    }
+
+    let resposta = popular_banco(&mut client).await;
+    registrar_desfecho(&_req, Acao::Popular, Some(&usuario), forcar, &resposta).await;
+    resposta
+}
+
+async fn popular_banco(client: &mut DbClient) -> Resposta {
     let transaction = match client.transaction().await {
         Ok(tx) => tx,
         Err(e) => {
@@ -192,25 +293,7 @@ async fn populate_db(_req: Request) -> impl IntoResponse {
     // --- Insert into independent tables first ---
 
     // 1. Cliente (Bem mais entradas e diversidade)
-    let clients_data = vec![
-        // PF
-        (1, "João Silva", "joao.silva@example.com", "11987654321", "Rua A, 123, Florianópolis", NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(), Some("111.111.111-11"), None),
-        (2, "Maria Souza", "maria.souza@example.com", "21998765432", "Avenida B, 456, Joinville", NaiveDate::from_ymd_opt(2022, 5, 15).unwrap(), Some("222.222.222-22"), None),
-        (3, "Pedro Almeida", "pedro.almeida@example.com", "48991234567", "Rua C, 789, Blumenau", NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(), Some("333.333.333-33"), None),
-        (4, "Ana Santos", "ana.santos@example.com", "48988765432", "Travessa D, 101, Chapecó", NaiveDate::from_ymd_opt(2023, 11, 5).unwrap(), Some("444.444.444-44"), None),
-        (5, "Lucas Ferreira", "lucas.ferreira@example.com", "48992345678", "Av. E, 202, Lages", NaiveDate::from_ymd_opt(2024, 1, 20).unwrap(), Some("555.555.555-55"), None),
-        (6, "Mariana Costa", "mariana.costa@example.com", "48993456789", "Rua F, 303, Criciúma", NaiveDate::from_ymd_opt(2023, 7, 1).unwrap(), Some("666.666.666-66"), None),
-        (11, "Roberto Pereira", "roberto.p@example.com", "48994567890", "Av. do Contorno, 50, Palhoça", NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(), Some("777.777.777-77"), None),
-        (12, "Fernanda Lima", "fernanda.l@example.com", "48991122334", "Rua das Palmeiras, 10, Itajaí", NaiveDate::from_ymd_opt(2023, 9, 15).unwrap(), Some("888.888.888-88"), None),
-
-        // PJ
-        (7, "Tech Solutions Ltda", "contato@techsol.com", "4832109876", "Rua G, 404, São José", NaiveDate::from_ymd_opt(2022, 9, 1).unwrap(), None, Some("00.000.000/0001-00")),
-        (8, "Construtora Alfa", "contato@alfa.com", "4833210987", "Av. H, 505, Palhoça", NaiveDate::from_ymd_opt(2023, 3, 20).unwrap(), None, Some("11.111.111/0001-11")),
-        (9, "Serviços Beta S.A.", "info@beta.com", "4834321098", "Rua I, 606, Itajaí", NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(), None, Some("22.222.222/0001-22")),
-        (10, "Distribuidora Gama", "vendas@gama.com", "4835432109", "Rod. J, 707, Tubarão", NaiveDate::from_ymd_opt(2023, 10, 15).unwrap(), None, Some("33.333.333/0001-33")),
-        (13, "Logística Delta EIRELI", "contato@delta.com", "4836543210", "Av. Principal, 1000, Lages", NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), None, Some("44.444.444/0001-44")),
-        (14, "Consultoria Epsilon", "rh@epsilon.com", "4837654321", "Praça Central, 25, Criciúma", NaiveDate::from_ymd_opt(2023, 6, 10).unwrap(), None, Some("55.555.555/0001-55")),
-    ];
+    let clients_data = clientes_ficticios();
 
     for (id, nome, email, telefone, endereco, data_cadastro, cpf, cnpj) in clients_data.iter() {
         if let Err(e) = transaction.execute(
@@ -328,30 +411,7 @@ async fn populate_db(_req: Request) -> impl IntoResponse {
 
     // 6. Caso (MUITO MAIS ENTRADAS, sem IDs fixos, capturando os IDs gerados)
     // Usamos um template mais longo e aleatório para clientes/advogados/status/varas/categorias
-    let cases_data_template = vec![
-        // Combinando Clientes (1-14), Advogados (101-107), Status (301-307), Varas (201-208), Categorias (401-408)
-        // Cliente, Advogado, Status, Vara Judicial (Opt), Categoria (Opt), Descricao (Opt), Num Processo (Opt), Data Fechamento (Opt), Data Abertura
-        (1, 101, 301, Some(201), Some(401), Some("Ação de Cobrança de Dívida"), Some("12345-67.2023.8.24.0001"), None, NaiveDate::from_ymd_opt(2023, 3, 10).unwrap()),
-        (2, 102, 304, Some(204), Some(404), Some("Processo de Divórcio Litigioso"), Some("98765-43.2024.8.24.0003"), None, NaiveDate::from_ymd_opt(2024, 1, 20).unwrap()),
-        (3, 103, 306, Some(203), Some(403), Some("Recurso em Reclamatória Trabalhista"), Some("11223-44.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 7, 5).unwrap()),
-        (4, 104, 302, Some(205), Some(405), Some("Processo de Restituição de ICMS"), Some("55667-88.2024.8.24.0004"), Some(NaiveDate::from_ymd_opt(2024, 6, 25).unwrap()), NaiveDate::from_ymd_opt(2024, 2, 15).unwrap()),
-        (5, 105, 303, Some(206), Some(406), Some("Processo de Regularização Ambiental"), Some("99887-66.2023.8.24.0005"), None, NaiveDate::from_ymd_opt(2023, 9, 1).unwrap()),
-        (6, 106, 301, Some(207), Some(407), Some("Ação de Indenização por Vício de Produto"), Some("12398-76.2024.8.24.0006"), None, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap()),
-        (7, 107, 304, Some(208), Some(408), Some("Mandado de Segurança - Concurso Público"), Some("45678-90.2023.8.24.0007"), None, NaiveDate::from_ymd_opt(2023, 11, 20).unwrap()),
-        (8, 101, 305, Some(201), Some(401), Some("Ação de Execução Hipotecária"), Some("11335-55.2024.8.24.0001"), None, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
-        (9, 102, 301, Some(202), Some(402), Some("Ação Penal Pública Condicionada"), Some("22446-66.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 5, 30).unwrap()),
-        (10, 103, 302, Some(203), Some(403), Some("Dissídio Coletivo"), Some("33557-77.2024.8.24.0003"), Some(NaiveDate::from_ymd_opt(2024, 6, 10).unwrap()), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
-        (11, 104, 304, Some(204), Some(404), Some("Guarda Compartilhada de Menor"), Some("44668-88.2023.8.24.0004"), None, NaiveDate::from_ymd_opt(2023, 8, 15).unwrap()),
-        (12, 105, 306, Some(205), Some(405), Some("Revisão Tributária de ITBI"), Some("55779-99.2024.8.24.0005"), None, NaiveDate::from_ymd_opt(2024, 3, 5).unwrap()),
-        (13, 106, 307, Some(206), Some(406), Some("Ação de Demarcação de Terras"), Some("66880-00.2023.8.24.0006"), None, NaiveDate::from_ymd_opt(2023, 10, 1).unwrap()),
-        (14, 107, 301, Some(207), Some(407), Some("Contestação em Ação de Consumidor"), Some("77991-11.2024.8.24.0007"), None, NaiveDate::from_ymd_opt(2024, 5, 20).unwrap()),
-        (1, 103, 302, Some(208), Some(408), Some("Recurso Administrativo"), Some("88112-22.2023.8.24.0008"), Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), NaiveDate::from_ymd_opt(2023, 2, 10).unwrap()),
-        (2, 104, 303, Some(201), Some(401), Some("Cobrança de Aluguéis Atrasados"), Some("99223-33.2024.8.24.0001"), None, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()),
-        (3, 105, 304, Some(202), Some(402), Some("Habeas Corpus"), Some("00334-44.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 7, 20).unwrap()),
-        (4, 106, 301, Some(203), Some(403), Some("Mandado de Segurança Trabalhista"), Some("11445-55.2024.8.24.0003"), None, NaiveDate::from_ymd_opt(2024, 4, 10).unwrap()),
-        (5, 107, 305, Some(204), Some(404), Some("Regulamentação de Visitas"), Some("22556-66.2023.8.24.0004"), None, NaiveDate::from_ymd_opt(2023, 9, 5).unwrap()),
-        (6, 101, 306, Some(205), Some(405), Some("Defesa em Execução Fiscal"), Some("33667-77.2024.8.24.0005"), None, NaiveDate::from_ymd_opt(2024, 2, 20).unwrap()),
-    ];
+    let cases_data_template = casos_ficticios();
 
     let insert_caso_query = "
         INSERT INTO Caso (id_cliente, id_advogado, id_status, id_vara_judicial, id_categoria_caso, descricao, numero_processo, data_abertura, data_fechamento)
@@ -441,7 +501,7 @@ async fn populate_db(_req: Request) -> impl IntoResponse {
     ];
     for (id_documento, id_caso, descricao, data_envio, nome_arquivo) in documento_data.iter() {
         if let Err(e) = transaction.execute(
-            "INSERT INTO Documento (id_documento, id_caso, descricao, data_envio, nome_arquivo, arquivo) VALUES ($1, $2, $3, $4, $5, NULL) ON CONFLICT (id_documento) DO UPDATE SET id_caso = EXCLUDED.id_caso, descricao = EXCLUDED.descricao, data_envio = EXCLUDED.data_envio, nome_arquivo = EXCLUDED.nome_arquivo, arquivo = EXCLUDED.arquivo;",
+            "INSERT INTO Documento (id_documento, id_caso, descricao, data_envio, nome_arquivo, arquivo) VALUES ($1, $2, $3, $4, $5, NULL) ON CONFLICT (id_documento) DO UPDATE SET id_caso = EXCLUDED.id_caso, descricao = EXCLUDED.descricao, data_envio = EXCLUDED.data_envio, nome_arquivo = EXCLUDED.nome_arquivo, arquivo = EXCLUDED.arquivo, chave_cifrada = NULL, id_chave_mestra = NULL;",
             &[id_documento, id_caso, descricao, data_envio, nome_arquivo],
         ).await {
             let _ = transaction.rollback().await;
@@ -537,4 +597,59 @@ async fn populate_db(_req: Request) -> impl IntoResponse {
             (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})))
         }
     }
-}
\ No newline at end of file
+}
+
+
+// Id, nome, e-mail, telefone, endereço, data de cadastro, CPF (PF) e CNPJ (PJ)
+type ClienteFicticio = (i32, &'static str, &'static str, &'static str, &'static str, NaiveDate, Option<&'static str>, Option<&'static str>);
+
+fn clientes_ficticios() -> Vec<ClienteFicticio> {
+    vec![
+        // PF
+        (1, "João Silva", "joao.silva@example.com", "11987654321", "Rua A, 123, Florianópolis", NaiveDate::from_ymd_opt(2023, 1, 1).unwrap(), Some("111.111.111-11"), None),
+        (2, "Maria Souza", "maria.souza@example.com", "21998765432", "Avenida B, 456, Joinville", NaiveDate::from_ymd_opt(2022, 5, 15).unwrap(), Some("222.222.222-22"), None),
+        (3, "Pedro Almeida", "pedro.almeida@example.com", "48991234567", "Rua C, 789, Blumenau", NaiveDate::from_ymd_opt(2024, 2, 10).unwrap(), Some("333.333.333-33"), None),
+        (4, "Ana Santos", "ana.santos@example.com", "48988765432", "Travessa D, 101, Chapecó", NaiveDate::from_ymd_opt(2023, 11, 5).unwrap(), Some("444.444.444-44"), None),
+        (5, "Lucas Ferreira", "lucas.ferreira@example.com", "48992345678", "Av. E, 202, Lages", NaiveDate::from_ymd_opt(2024, 1, 20).unwrap(), Some("555.555.555-55"), None),
+        (6, "Mariana Costa", "mariana.costa@example.com", "48993456789", "Rua F, 303, Criciúma", NaiveDate::from_ymd_opt(2023, 7, 1).unwrap(), Some("666.666.666-66"), None),
+        (11, "Roberto Pereira", "roberto.p@example.com", "48994567890", "Av. do Contorno, 50, Palhoça", NaiveDate::from_ymd_opt(2024, 6, 1).unwrap(), Some("777.777.777-77"), None),
+        (12, "Fernanda Lima", "fernanda.l@example.com", "48991122334", "Rua das Palmeiras, 10, Itajaí", NaiveDate::from_ymd_opt(2023, 9, 15).unwrap(), Some("888.888.888-88"), None),
+
+        // PJ
+        (7, "Tech Solutions Ltda", "contato@techsol.com", "4832109876", "Rua G, 404, São José", NaiveDate::from_ymd_opt(2022, 9, 1).unwrap(), None, Some("00.000.000/0001-00")),
+        (8, "Construtora Alfa", "contato@alfa.com", "4833210987", "Av. H, 505, Palhoça", NaiveDate::from_ymd_opt(2023, 3, 20).unwrap(), None, Some("11.111.111/0001-11")),
+        (9, "Serviços Beta S.A.", "info@beta.com", "4834321098", "Rua I, 606, Itajaí", NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(), None, Some("22.222.222/0001-22")),
+        (10, "Distribuidora Gama", "vendas@gama.com", "4835432109", "Rod. J, 707, Tubarão", NaiveDate::from_ymd_opt(2023, 10, 15).unwrap(), None, Some("33.333.333/0001-33")),
+        (13, "Logística Delta EIRELI", "contato@delta.com", "4836543210", "Av. Principal, 1000, Lages", NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), None, Some("44.444.444/0001-44")),
+        (14, "Consultoria Epsilon", "rh@epsilon.com", "4837654321", "Praça Central, 25, Criciúma", NaiveDate::from_ymd_opt(2023, 6, 10).unwrap(), None, Some("55.555.555/0001-55")),
+    ]
+}
+
+type CasoFicticio = (i32, i32, i32, Option<i32>, Option<i32>, Option<&'static str>, Option<&'static str>, Option<NaiveDate>, NaiveDate);
+
+fn casos_ficticios() -> Vec<CasoFicticio> {
+    vec![
+        // Combinando Clientes (1-14), Advogados (101-107), Status (301-307), Varas (201-208), Categorias (401-408)
+        // Cliente, Advogado, Status, Vara Judicial (Opt), Categoria (Opt), Descricao (Opt), Num Processo (Opt), Data Fechamento (Opt), Data Abertura
+        (1, 101, 301, Some(201), Some(401), Some("Ação de Cobrança de Dívida"), Some("12345-67.2023.8.24.0001"), None, NaiveDate::from_ymd_opt(2023, 3, 10).unwrap()),
+        (2, 102, 304, Some(204), Some(404), Some("Processo de Divórcio Litigioso"), Some("98765-43.2024.8.24.0003"), None, NaiveDate::from_ymd_opt(2024, 1, 20).unwrap()),
+        (3, 103, 306, Some(203), Some(403), Some("Recurso em Reclamatória Trabalhista"), Some("11223-44.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 7, 5).unwrap()),
+        (4, 104, 302, Some(205), Some(405), Some("Processo de Restituição de ICMS"), Some("55667-88.2024.8.24.0004"), Some(NaiveDate::from_ymd_opt(2024, 6, 25).unwrap()), NaiveDate::from_ymd_opt(2024, 2, 15).unwrap()),
+        (5, 105, 303, Some(206), Some(406), Some("Processo de Regularização Ambiental"), Some("99887-66.2023.8.24.0005"), None, NaiveDate::from_ymd_opt(2023, 9, 1).unwrap()),
+        (6, 106, 301, Some(207), Some(407), Some("Ação de Indenização por Vício de Produto"), Some("12398-76.2024.8.24.0006"), None, NaiveDate::from_ymd_opt(2024, 3, 12).unwrap()),
+        (7, 107, 304, Some(208), Some(408), Some("Mandado de Segurança - Concurso Público"), Some("45678-90.2023.8.24.0007"), None, NaiveDate::from_ymd_opt(2023, 11, 20).unwrap()),
+        (8, 101, 305, Some(201), Some(401), Some("Ação de Execução Hipotecária"), Some("11335-55.2024.8.24.0001"), None, NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()),
+        (9, 102, 301, Some(202), Some(402), Some("Ação Penal Pública Condicionada"), Some("22446-66.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 5, 30).unwrap()),
+        (10, 103, 302, Some(203), Some(403), Some("Dissídio Coletivo"), Some("33557-77.2024.8.24.0003"), Some(NaiveDate::from_ymd_opt(2024, 6, 10).unwrap()), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+        (11, 104, 304, Some(204), Some(404), Some("Guarda Compartilhada de Menor"), Some("44668-88.2023.8.24.0004"), None, NaiveDate::from_ymd_opt(2023, 8, 15).unwrap()),
+        (12, 105, 306, Some(205), Some(405), Some("Revisão Tributária de ITBI"), Some("55779-99.2024.8.24.0005"), None, NaiveDate::from_ymd_opt(2024, 3, 5).unwrap()),
+        (13, 106, 307, Some(206), Some(406), Some("Ação de Demarcação de Terras"), Some("66880-00.2023.8.24.0006"), None, NaiveDate::from_ymd_opt(2023, 10, 1).unwrap()),
+        (14, 107, 301, Some(207), Some(407), Some("Contestação em Ação de Consumidor"), Some("77991-11.2024.8.24.0007"), None, NaiveDate::from_ymd_opt(2024, 5, 20).unwrap()),
+        (1, 103, 302, Some(208), Some(408), Some("Recurso Administrativo"), Some("88112-22.2023.8.24.0008"), Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()), NaiveDate::from_ymd_opt(2023, 2, 10).unwrap()),
+        (2, 104, 303, Some(201), Some(401), Some("Cobrança de Aluguéis Atrasados"), Some("99223-33.2024.8.24.0001"), None, NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()),
+        (3, 105, 304, Some(202), Some(402), Some("Habeas Corpus"), Some("00334-44.2023.8.24.0002"), None, NaiveDate::from_ymd_opt(2023, 7, 20).unwrap()),
+        (4, 106, 301, Some(203), Some(403), Some("Mandado de Segurança Trabalhista"), Some("11445-55.2024.8.24.0003"), None, NaiveDate::from_ymd_opt(2024, 4, 10).unwrap()),
+        (5, 107, 305, Some(204), Some(404), Some("Regulamentação de Visitas"), Some("22556-66.2023.8.24.0004"), None, NaiveDate::from_ymd_opt(2023, 9, 5).unwrap()),
+        (6, 101, 306, Some(205), Some(405), Some("Defesa em Execução Fiscal"), Some("33667-77.2024.8.24.0005"), None, NaiveDate::from_ymd_opt(2024, 2, 20).unwrap()),
+    ]
+}
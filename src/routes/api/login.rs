@@ -0,0 +1,98 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::{header, StatusCode};
+use tuono_lib::Request;
+use tuono_app::auth::{conferir_senha, cookie_sessao, criar_sessao, exigir_sessao, ip_da_requisicao};
+use tuono_app::limite::{limitar_chaves, Politica};
+use tuono_app::{connect_db};
+
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize)]
+struct LoginPayload {
+    email: String,
+    senha: String,
+}
+
+// GET /api/login (usuário da sessão atual; 401 se não houver)
+#[tuono_lib::api(GET)]
+async fn usuario_atual(_req: Request) -> impl IntoResponse {
+    match exigir_sessao(&_req).await {
+        Ok(usuario) => (StatusCode::OK, Json(json!(usuario))),
+        Err(resposta) => resposta,
+    }
+}
+
+// POST /api/login (confere a senha e abre uma sessão no cookie)
+#[tuono_lib::api(POST)]
+async fn login(_req: Request) -> impl IntoResponse {
+    let payload: LoginPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)}))).into_response();
+        }
+    };
+
+    // Por e-mail e, com o IP conhecido (ver `ip_da_requisicao`), também por IP:
+    // trocar de IP não libera mais tentativas na mesma conta
+    let email = payload.email.trim().to_lowercase();
+    let ip = ip_da_requisicao(&_req.headers);
+    let mut chaves = vec![format!("email:{}", email)];
+    if let Some(ip) = &ip {
+        chaves.push(format!("ip:{}", ip));
+    }
+    if let Err(resposta) = limitar_chaves(Politica::Autenticacao, &chaves).await {
+        return resposta;
+    }
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)}))).into_response();
+        }
+    };
+
+    let row = match client_db
+        .query_opt(
+            "SELECT id_usuario, nome, email, senha_hash FROM Usuario WHERE email = $1 AND ativo;",
+            &[&email],
+        )
+        .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            eprintln!("Failed to fetch user: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch user: {}", e)}))).into_response();
+        }
+    };
+
+    // Mesma mensagem e mesmo tempo para e-mail desconhecido e senha errada
+    let senha_confere = conferir_senha(&payload.senha, row.as_ref().map(|r| r.get::<_, &str>("senha_hash"))).await;
+    let Some(row) = row.filter(|_| senha_confere) else {
+        return (StatusCode::UNAUTHORIZED, Json(json!({"error": "E-mail ou senha inválidos."}))).into_response();
+    };
+    let id_usuario: i32 = row.get("id_usuario");
+
+    let user_agent = _req.headers.get(header::USER_AGENT).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+
+    let token = match criar_sessao(&client_db, id_usuario, ip.as_deref(), user_agent.as_deref()).await {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Failed to create session: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create session: {}", e)}))).into_response();
+        }
+    };
+
+    (
+        StatusCode::OK,
+        [(header::SET_COOKIE, cookie_sessao(&token))],
+        Json(json!({
+            "id_usuario": id_usuario,
+            "nome": row.get::<_, String>("nome"),
+            "email": row.get::<_, String>("email"),
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,59 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::{header, StatusCode};
+use tuono_lib::Request;
+use tuono_app::auditoria::{self, Autor};
+use tuono_app::auth::{cookie_expirado, exigir_sessao, revogar_sessao};
+use tuono_app::{connect_db};
+
+use serde_json::json;
+
+// POST /api/logout (revoga a sessão atual e apaga o cookie)
+#[tuono_lib::api(POST)]
+async fn logout(_req: Request) -> impl IntoResponse {
+    // Sem sessão válida, só limpa o cookie
+    let usuario = match exigir_sessao(&_req).await {
+        Ok(usuario) => usuario,
+        Err(_) => {
+            return (StatusCode::OK, [(header::SET_COOKIE, cookie_expirado())], Json(json!({"message": "Sessão encerrada."}))).into_response();
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)}))).into_response();
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)}))).into_response();
+        }
+    };
+
+    match revogar_sessao(&transaction, usuario.id_usuario, usuario.id_sessao).await {
+        Ok(revogada) => {
+            let revogadas: &[i32] = if revogada { &[usuario.id_sessao] } else { &[] };
+            if let Err(e) = auditoria::registrar_revogacoes(&transaction, &Autor::new(&_req, &usuario), revogadas).await {
+                eprintln!("Failed to write audit record: {}", e);
+                let _ = transaction.rollback().await;
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)}))).into_response();
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to revoke session: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to revoke session: {}", e)}))).into_response();
+        }
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)}))).into_response();
+    }
+
+    (StatusCode::OK, [(header::SET_COOKIE, cookie_expirado())], Json(json!({"message": "Sessão encerrada."}))).into_response()
+}
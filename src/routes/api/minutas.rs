@@ -0,0 +1,400 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_documentos, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::fila::Identificacao;
+use tuono_app::ia::provider_para_requisicao;
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::ia::injecao::detectar_instrucoes;
+use tuono_app::ia::peticao::{aprovar_minuta, qualificacao_do_caso, salvar_minuta, textos_de_apoio, NovaMinuta};
+use tuono_app::ia::prompts::{carregar_template, TEMPLATE_PETICAO_INICIAL};
+use tuono_app::ia::provider::{ChatMessage, ChatRequest};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+use std::collections::HashMap;
+
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct MinutaPayload {
+    id_caso: i32,
+    template: Option<String>, // peticao_inicial (padrão), contestacao, recurso ou um template próprio
+    template_versao: Option<i32>,
+    #[serde(default)]
+    ids_documento: Vec<i32>, // Documentos do caso usados como base
+    instrucoes: Option<String>,
+    model: Option<String>,
+    id_documento_anterior: Option<i32>, // Gera uma nova versão desta minuta
+    id_requisicao: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AprovacaoPayload {
+    id_advogado: i32,
+}
+
+fn id_da_query(req: &Request, nome: &str) -> Result<Option<i32>, String> {
+    let query_string = req.uri.query().unwrap_or("");
+    match extract_query_values(query_string).ok().and_then(|values| values.get(nome).cloned()) {
+        Some(valor) => valor.parse::<i32>().map(Some).map_err(|_| format!("{} deve ser um número inteiro.", nome)),
+        None => Ok(None),
+    }
+}
+
+// GET /api/minutas?id_caso= (minutas do caso, com todas as versões) ou ?id= (uma minuta com o texto)
+#[tuono_lib::api(GET)]
+async fn listar_minutas(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let (id, id_caso) = match (id_da_query(&_req, "id"), id_da_query(&_req, "id_caso")) {
+        (Ok(id), Ok(id_caso)) => (id, id_caso),
+        (Err(e), _) | (_, Err(e)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+    if id.is_none() && id_caso.is_none() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Informe id ou id_caso."})));
+    }
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)})));
+        }
+    };
+
+    let rows = match client_db
+        .query(
+            "SELECT d.id_documento, d.id_caso, d.nome_arquivo, d.versao, COALESCE(d.id_documento_original, d.id_documento) AS id_documento_original,
+                    m.tipo, m.modelo, m.documentos_base, m.instrucoes, m.texto, m.status, m.id_advogado_aprovador, m.data_aprovacao, m.data_geracao
+             FROM Documento d INNER JOIN Minuta_Ia m ON m.id_documento = d.id_documento
+             WHERE ($1::INTEGER IS NULL OR d.id_documento = $1) AND ($2::INTEGER IS NULL OR d.id_caso = $2)
+               AND ($3::INTEGER[] IS NULL OR d.id_caso = ANY($3))
+             ORDER BY id_documento_original, d.versao;",
+            &[&id, &id_caso, &ids_visiveis],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch drafts: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch drafts: {}", e)})));
+        }
+    };
+
+    let minutas: Vec<Value> = rows
+        .iter()
+        .map(|row| {
+            let data_aprovacao: Option<NaiveDateTime> = row.get("data_aprovacao");
+            let data_geracao: Option<NaiveDateTime> = row.get("data_geracao");
+            let mut minuta = json!({
+                "id_documento": row.get::<_, i32>("id_documento"),
+                "id_caso": row.get::<_, i32>("id_caso"),
+                "id_documento_original": row.get::<_, i32>("id_documento_original"),
+                "versao": row.get::<_, i32>("versao"),
+                "nome_arquivo": row.get::<_, Option<String>>("nome_arquivo"),
+                "tipo": row.get::<_, String>("tipo"),
+                "modelo": row.get::<_, String>("modelo"),
+                "documentos_base": row.get::<_, Vec<i32>>("documentos_base"),
+                "instrucoes": row.get::<_, Option<String>>("instrucoes"),
+                "status": row.get::<_, String>("status"),
+                "id_advogado_aprovador": row.get::<_, Option<i32>>("id_advogado_aprovador"),
+                "data_aprovacao": data_aprovacao.map(|d| d.to_string()),
+                "data_geracao": data_geracao.map(|d| d.to_string()),
+            });
+            if id.is_some() {
+                minuta["texto"] = json!(row.get::<_, String>("texto"));
+            }
+            minuta
+        })
+        .collect();
+
+    match id {
+        Some(_) => match minutas.into_iter().next() {
+            Some(minuta) => (StatusCode::OK, Json(minuta)),
+            None => (StatusCode::NOT_FOUND, Json(json!({"error": "Minuta não encontrada."}))),
+        },
+        None => (StatusCode::OK, Json(json!(minutas))),
+    }
+}
+
+// POST /api/minutas (gera a minuta e a grava como Documento DOCX do caso, pendente de aprovação)
+#[tuono_lib::api(POST)]
+async fn gerar_minuta(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    gerar_minuta_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn gerar_minuta_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    let payload: MinutaPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to parse request body: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body: {:?}", e)})));
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    // Os documentos de apoio já são restritos ao caso em `textos_de_apoio`
+    if let Err(resposta) = exigir_caso(&client_db, &usuario, payload.id_caso).await {
+        return resposta;
+    }
+
+    let dados_caso = match qualificacao_do_caso(&client_db, payload.id_caso).await {
+        Ok(Some(dados)) => dados,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Caso não encontrado."}))),
+        Err(e) => {
+            eprintln!("Failed to fetch case data for draft: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch case data: {}", e)})));
+        }
+    };
+
+    if let Some(id_anterior) = payload.id_documento_anterior {
+        match client_db
+            .query_opt(
+                "SELECT 1 FROM Minuta_Ia m INNER JOIN Documento d ON d.id_documento = m.id_documento WHERE m.id_documento = $1 AND d.id_caso = $2;",
+                &[&id_anterior, &payload.id_caso],
+            )
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "id_documento_anterior não é uma minuta deste caso."}))),
+            Err(e) => {
+                eprintln!("Failed to check previous draft: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to check previous draft: {}", e)})));
+            }
+        }
+    }
+
+    let mut ids_documento = payload.ids_documento.clone();
+    ids_documento.sort_unstable();
+    ids_documento.dedup();
+    let documentos = match textos_de_apoio(&client_db, payload.id_caso, &ids_documento).await {
+        Ok(texto) => texto,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+        }
+    };
+
+    let template_nome = payload.template.clone().unwrap_or_else(|| TEMPLATE_PETICAO_INICIAL.to_string());
+    let template = match carregar_template(&client_db, &template_nome, payload.template_versao).await {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+        }
+    };
+
+    let trechos_suspeitos = detectar_instrucoes(&documentos);
+    let instrucoes = payload.instrucoes.clone().filter(|i| !i.trim().is_empty());
+    let valores = HashMap::from([
+        ("dados_caso", dados_caso),
+        ("documento", documentos),
+        ("instrucoes", instrucoes.clone().unwrap_or_else(|| "Nenhuma.".to_string())),
+    ]);
+    let (system_prompt, prompt) = match template.render(&valores) {
+        Ok(rendered) => rendered,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+
+    let contexto = ContextoAuditoria {
+        id_versao_prompt: template.id_versao,
+        usuario: Some(usuario.email.clone()),
+        ..ContextoAuditoria::new(&template.nome, ids_documento.clone()).do_caso(payload.id_caso)
+    };
+    let llm = match provider_para_requisicao(fetch, payload.id_requisicao.clone().map(|id_requisicao| Identificacao { id_usuario: usuario.id_usuario, id_requisicao })) {
+        Ok(llm) => auditar(llm, contexto),
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    if let Some(system_prompt) = system_prompt {
+        messages.push(ChatMessage::system(system_prompt));
+    }
+    messages.push(ChatMessage::user(prompt));
+
+    let resposta = match llm.chat(&ChatRequest {
+        model: template.modelo(payload.model.as_deref()),
+        messages,
+        options: template.opcoes(),
+        ..Default::default()
+    }).await {
+        Ok(resposta) => resposta,
+        Err(e) => {
+            eprintln!("Draft generation failed ({}): {}", llm.name(), e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let nova = NovaMinuta {
+        id_caso: payload.id_caso,
+        tipo: &template.nome,
+        modelo: &resposta.model,
+        id_versao_prompt: template.id_versao,
+        ids_documento: &ids_documento,
+        instrucoes: instrucoes.as_deref(),
+        texto: &resposta.text,
+        id_documento_anterior: payload.id_documento_anterior,
+    };
+    let minuta = match salvar_minuta(&transaction, &nova).await {
+        Ok(minuta) => minuta,
+        Err(e) => {
+            eprintln!("{}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+        }
+    };
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Documento, minuta.id_documento, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::CREATED, Json(json!({
+        "message": "Minuta gerada. Ela fica marcada como gerada por IA até a aprovação de um advogado.",
+        "minuta": minuta,
+        "status": "pendente",
+        "texto": resposta.text,
+        "model": resposta.model,
+        "template": {
+            "nome": template.nome,
+            "versao": template.versao,
+            "id_versao": template.id_versao,
+        },
+        "seguranca": {
+            "injecao_suspeita": !trechos_suspeitos.is_empty(),
+            "trechos_suspeitos": trechos_suspeitos,
+        },
+    })))
+}
+
+// PUT /api/minutas?id= { "id_advogado": 1 } (aprovação: remove o aviso de IA do DOCX)
+#[tuono_lib::api(PUT)]
+async fn aprovar_minuta_ia(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::AprovarMinutas).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let id_documento = match id_da_query(&_req, "id") {
+        Ok(Some(id)) => id,
+        Ok(None) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "id é obrigatório."}))),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+    let payload: AprovacaoPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to parse request body: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body: {:?}", e)})));
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
+    }
+
+    match client_db.query_opt("SELECT 1 FROM Advogado WHERE id_advogado = $1;", &[&payload.id_advogado]).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Advogado não encontrado."}))),
+        Err(e) => {
+            eprintln!("Failed to check Advogado existence: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database error checking lawyer: {}", e)})));
+        }
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Documento, id_documento).await {
+        Ok(estado) => estado,
+        Err(e) => {
+            eprintln!("Failed to load Documento state for audit: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load document: {}", e)})));
+        }
+    };
+
+    match aprovar_minuta(&transaction, id_documento, payload.id_advogado).await {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Minuta pendente não encontrada."})));
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+        }
+    }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Documento, id_documento, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Minuta aprovada.", "id_documento": id_documento})))
+}
@@ -0,0 +1,147 @@
+// Administração dos modelos instalados no Ollama (baixar, remover, inspecionar).
+// Só o GET recebe o `fetch` do estado (um handler por arquivo); os outros usam `cliente_http`.
+use tuono_lib::axum::body::Body;
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::extract_query_values;
+use tuono_app::ia::{cliente_http, modelo_analise, LlmProvider};
+use tuono_app::ia::modelos::{mesmo_modelo, modelo_permitido, modelos_permitidos};
+use tuono_app::ia::provider::ollama_from_env;
+
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize)]
+struct PullRequest {
+    nome: String,
+}
+
+fn nome_da_query(req: &Request) -> Option<String> {
+    let query_string = req.uri.query().unwrap_or("");
+    extract_query_values(query_string)
+        .ok()
+        .and_then(|values| values.get("nome").cloned())
+        .map(|nome| nome.trim().to_string())
+        .filter(|nome| !nome.is_empty())
+}
+
+// GET /api/modelos_ia (modelos instalados, lista de permitidos e modelo padrão)
+// GET /api/modelos_ia?nome= (detalhes do modelo via /api/show)
+#[tuono_lib::api(GET)]
+async fn listar_modelos_ia(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::UsarIa).await {
+        return resposta;
+    }
+
+    let ollama = match ollama_from_env(fetch) {
+        Ok(ollama) => ollama,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    if let Some(nome) = nome_da_query(&_req) {
+        return match ollama.detalhes_modelo(&nome).await {
+            Ok(detalhes) => (StatusCode::OK, Json(detalhes)),
+            Err(e) => {
+                eprintln!("Failed to show model {}: {}", nome, e);
+                (e.status_code(), Json(json!({"error": e.to_string()})))
+            }
+        };
+    }
+
+    let instalados = match ollama.list_models().await {
+        Ok(instalados) => instalados,
+        Err(e) => {
+            eprintln!("Failed to list installed models: {}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let padrao = modelo_analise();
+    (StatusCode::OK, Json(json!({
+        "modelo_padrao": padrao,
+        "modelo_padrao_instalado": instalados.iter().any(|m| mesmo_modelo(m, &padrao)),
+        "permitidos": modelos_permitidos(), // null: todos os instalados
+        "instalados": instalados.iter().map(|m| json!({"nome": m, "permitido": modelo_permitido(m)})).collect::<Vec<_>>(),
+    })))
+}
+
+// POST /api/modelos_ia { "nome": "qwen2.5:7b" }
+// Baixa o modelo; o corpo da resposta é o progresso do Ollama em NDJSON, repassado à medida que chega.
+#[tuono_lib::api(POST)]
+async fn baixar_modelo_ia(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        return resposta.into_response();
+    }
+
+    let payload: PullRequest = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to parse request body: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body: {:?}", e)}))).into_response();
+        }
+    };
+    let nome = payload.nome.trim().to_string();
+    if nome.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Nome do modelo é obrigatório."}))).into_response();
+    }
+    if !modelo_permitido(&nome) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Modelo '{}' não está em LLM_MODELOS_PERMITIDOS.", nome)}))).into_response();
+    }
+
+    let ollama = match ollama_from_env(cliente_http()) {
+        Ok(ollama) => ollama,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()}))).into_response();
+        }
+    };
+
+    match ollama.puxar_modelo(&nome).await {
+        Ok(response) => {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"));
+            (StatusCode::OK, headers, Body::from_stream(response.bytes_stream())).into_response()
+        }
+        Err(e) => {
+            eprintln!("Failed to pull model {}: {}", nome, e);
+            (e.status_code(), Json(json!({"error": e.to_string()}))).into_response()
+        }
+    }
+}
+
+// DELETE /api/modelos_ia?nome=
+#[tuono_lib::api(DELETE)]
+async fn remover_modelo_ia(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        return resposta;
+    }
+
+    let nome = match nome_da_query(&_req) {
+        Some(nome) => nome,
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "nome é obrigatório."}))),
+    };
+    if mesmo_modelo(&nome, &modelo_analise()) {
+        return (StatusCode::CONFLICT, Json(json!({"error": "O modelo padrão não pode ser removido. Altere LLM_MODELO_PADRAO antes."})));
+    }
+
+    let ollama = match ollama_from_env(cliente_http()) {
+        Ok(ollama) => ollama,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    match ollama.remover_modelo(&nome).await {
+        Ok(()) => (StatusCode::OK, Json(json!({"message": format!("Modelo '{}' removido.", nome)}))),
+        Err(e) => {
+            eprintln!("Failed to delete model {}: {}", nome, e);
+            (e.status_code(), Json(json!({"error": e.to_string()})))
+        }
+    }
+}
@@ -1,37 +1,84 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_documentos, exigir_permissao, Permissao};
+use tuono_app::cifra::arquivo_da_linha;
 use tuono_app::connect_db;
-use pdf_extract::extract_text_from_mem;
+use tuono_app::ia::{cliente_http, provider_from_env, provider_para_requisicao, extrair_texto, dados_do_caso};
+use tuono_app::ia::fila::Identificacao;
+use tuono_app::ia::prompts::{carregar_template, TEMPLATE_QA_DOCUMENTO};
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::ia::injecao::detectar_instrucoes;
+use tuono_app::ia::saude::{iniciar_monitoramento, listar_modelos, marcar_indisponivel};
+use tuono_app::ia::qa::{mensagens_qa, responder_pergunta};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
 
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use serde_json::{json, Value};
 
-#[derive(Debug, Deserialize)]
-struct OllamaTagsResponse {
-    models: Vec<OllamaModelInternal>,
-}
-
-#[derive(Debug, Deserialize)]
-struct OllamaModelInternal {
-    name: String,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct OllamaGenerateRequest {
-    file_name: String, 
+    id_documento: Option<i32>,
+    id_caso: Option<i32>, // Obrigatório: caso ao qual o documento deve pertencer
+    file_name: Option<String>, // Legado: nome do arquivo não é único entre casos
     question: String,  
-    model: String,     
+    model: Option<String>, // Padrão: modelo do template
+    template: Option<String>, // Padrão: qa_documento
+    template_versao: Option<i32>, // Padrão: versão mais recente
+    id_requisicao: Option<String>, // Gerado pelo frontend para acompanhar a fila em /api/fila_ia
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct OllamaRawGenerateResponse {
-    response: String,
-}
 
+// GET /api/ollama (modelos disponíveis no provedor configurado; a última lista
+// conhecida quando o provedor está fora do ar, ver /api/saude_ia)
+#[tuono_lib::api(GET)]
+pub async fn ollama_get_models(_req: Request) -> impl IntoResponse {
+    // O estado (fetch) vai para o POST: só um handler por arquivo pode recebê-lo
+    let fetch = cliente_http();
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::UsarIa).await {
+        return resposta;
+    }
+
+    iniciar_monitoramento(fetch.clone());
+
+    let llm = match provider_from_env(fetch) {
+        Ok(llm) => llm,
+        Err(e) => {
+            eprintln!("{}", e);
+            marcar_indisponivel(&e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    match listar_modelos(llm.as_ref()).await {
+        Ok(status) => {
+            if status.modelos_em_cache {
+                eprintln!("LLM provider ({}) unavailable, serving cached model list", llm.name());
+            }
+            let models: Vec<Value> = status.modelos.into_iter().map(|name| json!({"id": name, "nome": name})).collect();
+            (StatusCode::OK, Json(json!(models)))
+        }
+        Err(e) => {
+            eprintln!("Failed to list models ({}): {}", llm.name(), e);
+            (e.status_code(), Json(json!({"error": e.to_string()})))
+        }
+    }
+}
 
 #[tuono_lib::api(POST)]
 pub async fn ollama_post_generate(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    ollama_post_generate_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn ollama_post_generate_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
     let payload: OllamaGenerateRequest = match _req.body() {
         Ok(p) => p,
         Err(e) => {
@@ -43,7 +90,6 @@ pub async fn ollama_post_generate(_req: Request, fetch: reqwest::Client) -> impl
         }
     };
 
-    let file_name = payload.file_name;
     let user_question = payload.question;
     let ollama_model = payload.model;
 
@@ -59,8 +105,66 @@ pub async fn ollama_post_generate(_req: Request, fetch: reqwest::Client) -> impl
         }
     };
 
+    // 3. Resolver o documento pelo id_documento dentro do caso informado (chamadas
+    // legadas por nome só são aceitas se não forem ambíguas no caso)
+    let Some(id_caso) = payload.id_caso else {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "O campo 'id_caso' é obrigatório."})));
+    };
+    let id_documento = match (payload.id_documento, payload.file_name) {
+        (Some(id), _) => id,
+        (None, Some(file_name)) => {
+            // Só entre os casos do usuário: a contagem de homônimos não pode revelar documentos de outros casos
+            let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+                Ok(escopo) => escopo.ids(),
+                Err(e) => {
+                    eprintln!("Failed to load case scope: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": format!("Failed to load case scope: {}", e)}))
+                    );
+                }
+            };
+            let rows = match client_db
+                .query(
+                    "SELECT id_documento FROM Documento
+                     WHERE nome_arquivo = $1 AND id_caso = $2
+                       AND ($3::INTEGER[] IS NULL OR id_caso = ANY($3));",
+                    &[&file_name, &id_caso, &ids_visiveis],
+                )
+                .await
+            {
+                Ok(rows) => rows,
+                Err(e) => {
+                    eprintln!("Failed to resolve document by name: {}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(json!({"error": format!("Failed to fetch document content from DB: {}", e)}))
+                    );
+                }
+            };
+
+            match rows.len() {
+                0 => return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."}))),
+                1 => rows[0].get("id_documento"),
+                n => return (
+                    StatusCode::CONFLICT,
+                    Json(json!({
+                        "error": format!("Nome de arquivo ambíguo: {} documentos correspondem a '{}'. Envie 'id_documento' em vez de 'file_name'.", n, file_name)
+                    }))
+                ),
+            }
+        }
+        (None, None) => {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "O campo 'id_documento' é obrigatório."})));
+        }
+    };
+
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
+    }
+
     let row = match client_db
-        .query_opt("SELECT arquivo FROM Documento WHERE nome_arquivo = $1;", &[&file_name])
+        .query_opt("SELECT id_caso, arquivo, chave_cifrada, id_chave_mestra FROM Documento WHERE id_documento = $1;", &[&id_documento])
         .await
     {
         Ok(row) => row,
@@ -73,79 +177,105 @@ pub async fn ollama_post_generate(_req: Request, fetch: reqwest::Client) -> impl
         }
     };
 
-    let document_bytes: Vec<u8> = if let Some(r) = row {
-        match r.get("arquivo") {
-            Some(bytes) => bytes,
-            None => return (StatusCode::NOT_FOUND, Json(json!({"error": "Conteúdo do arquivo não encontrado para o nome fornecido."}))),
+    let row = match row {
+        Some(r) => r,
+        None => return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado."}))),
+    };
+
+    // O documento precisa pertencer ao caso informado pelo chamador
+    let id_caso_documento: i32 = row.get("id_caso");
+    if id_caso != id_caso_documento {
+        return (StatusCode::FORBIDDEN, Json(json!({"error": "O documento não pertence ao caso informado."})));
+    }
+
+    let document_bytes: Vec<u8> = match arquivo_da_linha(&row) {
+        Ok(Some(bytes)) => bytes,
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Conteúdo do arquivo não encontrado para o documento fornecido."}))),
+        Err(e) => {
+            eprintln!("Failed to decrypt document {}: {}", id_documento, e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
         }
-    } else {
-        return (StatusCode::NOT_FOUND, Json(json!({"error": format!("Documento com nome '{}' não encontrado.", file_name)})));
     };
 
-    let extracted_text = match extract_text_from_mem(&document_bytes) {
+    let extracted_text = match extrair_texto(&document_bytes) {
         Ok(text) => text,
         Err(e) => {
-            eprintln!("Failed to extract text from PDF: {:?}", e);
+            eprintln!("{}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("Failed to extract text from PDF: {:?}", e)}))
+                Json(json!({"error": e}))
             );
         }
     };
 
-    let full_prompt = format!(
-        "Com base no seguinte documento, responda à pergunta do usuário. Se a informação não estiver no documento, diga que não pode responder.\n\nDocumento:\n```\n{}\n```\n\nPergunta do Usuário: {}",
-        extracted_text, user_question
-    );
-
-    let ollama_env = std::env::var("OLLAMA_API_URL").expect("Fail to read OLLAMA_API_URL env");
-    let ollama_api_url: &str = &format!("{}api/generate", ollama_env);
-
-    let ollama_request_body = json!({
-        "model": ollama_model,
-        "prompt": full_prompt,
-        "stream": false
-    });
+    let template_nome = payload.template.unwrap_or_else(|| TEMPLATE_QA_DOCUMENTO.to_string());
+    let template = match carregar_template(&client_db, &template_nome, payload.template_versao).await {
+        Ok(template) => template,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+        }
+    };
 
-    let ollama_response_raw = match fetch.post(ollama_api_url)
-        .json(&ollama_request_body)
-        .send()
-        .await
-    {
-        Ok(res) => res,
+    let dados_caso = match dados_do_caso(&client_db, id_caso_documento).await {
+        Ok(dados) => dados,
         Err(e) => {
-            eprintln!("Failed to send request to Ollama API (generate): {}", e);
+            eprintln!("Failed to fetch case data for prompt: {}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("Failed to connect to Ollama API for generation: {}", e)}))
+                Json(json!({"error": format!("Failed to fetch case data: {}", e)}))
             );
         }
     };
 
-    if !ollama_response_raw.status().is_success() {
-        let status = ollama_response_raw.status();
-        let text = ollama_response_raw.text().await.unwrap_or_else(|_| "No response body".to_string());
-        eprintln!("Ollama API returned an error during generation: Status={}, Body={}", status, text);
-        return (
-            StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-            Json(json!({"error": format!("Ollama API generation error: Status {}, Body: {}", status, text)}))
-        );
+    // Trechos do documento que parecem instruções ao modelo (prompt injection)
+    let trechos_suspeitos = detectar_instrucoes(&extracted_text);
+    if !trechos_suspeitos.is_empty() {
+        eprintln!("Document {} contains {} instruction-like passage(s)", id_documento, trechos_suspeitos.len());
     }
 
-    let ollama_response_data: OllamaRawGenerateResponse = match ollama_response_raw.json().await {
-        Ok(data) => data,
+    let messages = match mensagens_qa(&template, extracted_text, user_question, dados_caso) {
+        Ok(messages) => messages,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": e}))),
+    };
+
+    let contexto = ContextoAuditoria {
+        id_versao_prompt: template.id_versao,
+        usuario: Some(usuario.email.clone()),
+        ..ContextoAuditoria::new(&template.nome, vec![id_documento])
+    };
+    let llm = match provider_para_requisicao(fetch, payload.id_requisicao.map(|id_requisicao| Identificacao { id_usuario: usuario.id_usuario, id_requisicao })) {
+        Ok(llm) => auditar(llm, contexto),
         Err(e) => {
-            eprintln!("Failed to parse Ollama API generation response: {}", e);
-            return (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(json!({"error": format!("Failed to parse Ollama API generation response: {}", e)}))
-            );
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let llm_response = match responder_pergunta(llm.as_ref(), &template, template.modelo(ollama_model.as_deref()), messages).await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("LLM generation failed ({}): {}", llm.name(), e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
         }
     };
 
-    // 7. Retornar a resposta do Ollama para o frontend
+    // 7. Retornar a resposta do LLM para o frontend
     (StatusCode::OK, Json(json!({
         "message": "Resposta do LLM obtida com sucesso",
-        "llm_response": ollama_response_data.response
+        "id_documento": id_documento,
+        "id_caso": id_caso_documento,
+        "llm_response": llm_response.text,
+        "model": llm_response.model,
+        "provider": llm.name(),
+        "template": {
+            "nome": template.nome,
+            "versao": template.versao,
+            "id_versao": template.id_versao,
+        },
+        "seguranca": {
+            "injecao_suspeita": !trechos_suspeitos.is_empty(),
+            "trechos_suspeitos": trechos_suspeitos,
+        },
     })))
-}
\ No newline at end of file
+}
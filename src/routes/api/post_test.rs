@@ -1,6 +1,8 @@
 use tuono_lib::axum::response::{IntoResponse, Json};
 use tuono_lib::axum::http::StatusCode;
 use tuono_lib::Request;
+use tuono_app::auth::exigir_sessao;
+use tuono_app::cifra::arquivo_da_linha;
 use tuono_app::connect_db; // Importa connect_db do tuono_app
 
 use serde_json::{json, Value};
@@ -14,6 +16,10 @@ struct DocumentIdPayload {
 
 #[tuono_lib::api(POST)]
 async fn post_test(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_sessao(&_req).await {
+        return resposta;
+    }
+
 
     let payload: DocumentIdPayload = match _req.body() {
         Ok(p) => p,
@@ -34,7 +40,7 @@ async fn post_test(_req: Request) -> impl IntoResponse {
     };
 
     let row = match client_db
-        .query_opt("SELECT arquivo FROM Documento WHERE id_documento = $1;", &[&id_documento_to_extract])
+        .query_opt("SELECT arquivo, chave_cifrada, id_chave_mestra FROM Documento WHERE id_documento = $1;", &[&id_documento_to_extract])
         .await
     {
         Ok(row) => row,
@@ -45,9 +51,13 @@ async fn post_test(_req: Request) -> impl IntoResponse {
     };
 
     let document_bytes: Vec<u8> = if let Some(r) = row {
-        match r.get("arquivo") {
-            Some(bytes) => bytes,
-            None => return (StatusCode::NOT_FOUND, Json(json!({"error": "Conteúdo do arquivo não encontrado no documento especificado."}))),
+        match arquivo_da_linha(&r) {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Conteúdo do arquivo não encontrado no documento especificado."}))),
+            Err(e) => {
+                eprintln!("Failed to decrypt document: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+            }
         }
     } else {
         return (StatusCode::NOT_FOUND, Json(json!({"error": "Documento não encontrado com o ID fornecido."})));
@@ -0,0 +1,420 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::prompts::{criar_versao, NovaVersao};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use chrono::NaiveDateTime;
+
+#[derive(Debug, Deserialize)]
+struct PromptPayload {
+    nome: String,
+    descricao: Option<String>,
+    system_prompt: Option<String>,
+    conteudo: String,
+    modelo_padrao: Option<String>,
+    temperature: Option<f64>,
+    num_ctx: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PromptUpdatePayload {
+    id_template: i32,
+    descricao: Option<String>,
+    ativo: Option<bool>,
+    // Quando `conteudo` é enviado, uma nova versão é criada
+    system_prompt: Option<String>,
+    conteudo: Option<String>,
+    modelo_padrao: Option<String>,
+    temperature: Option<f64>,
+    num_ctx: Option<i32>,
+}
+
+fn validar_parametros(temperature: Option<f64>, num_ctx: Option<i32>) -> Result<(), String> {
+    if let Some(t) = temperature && !(0.0..=2.0).contains(&t) {
+        return Err("temperature deve estar entre 0 e 2.".to_string());
+    }
+    if let Some(n) = num_ctx && n <= 0 {
+        return Err("num_ctx deve ser positivo.".to_string());
+    }
+    Ok(())
+}
+
+// GET /api/prompts (lista os templates com a versão atual) ou ?id= (template com todas as versões)
+#[tuono_lib::api(GET)]
+async fn prompts(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::UsarIa).await {
+        return resposta;
+    }
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values_result = extract_query_values(query_string);
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Ok(values) = &query_values_result && let Some(id_str) = values.get("id") {
+        let id = match id_str.parse::<i32>() {
+            Ok(id) => id,
+            Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do template deve ser um número inteiro."}))),
+        };
+
+        let template = match client_db
+            .query_opt("SELECT id_template, nome, descricao, ativo FROM Prompt_Template WHERE id_template = $1;", &[&id])
+            .await
+        {
+            Ok(Some(row)) => row,
+            Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Template não encontrado."}))),
+            Err(e) => {
+                eprintln!("Failed to fetch prompt template: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch prompt template: {}", e)})));
+            }
+        };
+
+        let versoes = match client_db
+            .query(
+                "SELECT id_versao, versao, system_prompt, conteudo, variaveis, modelo_padrao, temperature, num_ctx, data_criacao
+                 FROM Prompt_Template_Versao WHERE id_template = $1 ORDER BY versao DESC;",
+                &[&id],
+            )
+            .await
+        {
+            Ok(rows) => rows,
+            Err(e) => {
+                eprintln!("Failed to fetch prompt template versions: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch prompt template versions: {}", e)})));
+            }
+        };
+
+        let versoes: Vec<Value> = versoes.into_iter().map(|row| {
+            let data_criacao: Option<NaiveDateTime> = row.get("data_criacao");
+            json!({
+                "id_versao": row.get::<_, i32>("id_versao"),
+                "versao": row.get::<_, i32>("versao"),
+                "system_prompt": row.get::<_, Option<String>>("system_prompt"),
+                "conteudo": row.get::<_, String>("conteudo"),
+                "variaveis": row.get::<_, Vec<String>>("variaveis"),
+                "modelo_padrao": row.get::<_, Option<String>>("modelo_padrao"),
+                "temperature": row.get::<_, Option<f64>>("temperature"),
+                "num_ctx": row.get::<_, Option<i32>>("num_ctx"),
+                "data_criacao": data_criacao.map(|d| d.to_string()),
+            })
+        }).collect();
+
+        return (StatusCode::OK, Json(json!({
+            "id_template": template.get::<_, i32>("id_template"),
+            "nome": template.get::<_, String>("nome"),
+            "descricao": template.get::<_, Option<String>>("descricao"),
+            "ativo": template.get::<_, Option<bool>>("ativo"),
+            "versoes": versoes,
+        })));
+    }
+
+    let rows = match client_db
+        .query(
+            "SELECT DISTINCT ON (t.id_template) t.id_template, t.nome, t.descricao, t.ativo,
+                    v.id_versao, v.versao, v.variaveis, v.modelo_padrao, v.temperature, v.num_ctx
+             FROM Prompt_Template t
+             INNER JOIN Prompt_Template_Versao v ON v.id_template = t.id_template
+             ORDER BY t.id_template, v.versao DESC;",
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch prompt templates: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch prompt templates: {}", e)})));
+        }
+    };
+
+    let templates: Vec<Value> = rows.into_iter().map(|row| {
+        json!({
+            "id_template": row.get::<_, i32>("id_template"),
+            "nome": row.get::<_, String>("nome"),
+            "descricao": row.get::<_, Option<String>>("descricao"),
+            "ativo": row.get::<_, Option<bool>>("ativo"),
+            "id_versao": row.get::<_, i32>("id_versao"),
+            "versao": row.get::<_, i32>("versao"),
+            "variaveis": row.get::<_, Vec<String>>("variaveis"),
+            "modelo_padrao": row.get::<_, Option<String>>("modelo_padrao"),
+            "temperature": row.get::<_, Option<f64>>("temperature"),
+            "num_ctx": row.get::<_, Option<i32>>("num_ctx"),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(templates)))
+}
+
+// POST /api/prompts (cria o template e sua versão 1)
+#[tuono_lib::api(POST)]
+async fn create_prompt(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: PromptPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    if payload.nome.trim().is_empty() || payload.conteudo.trim().is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Nome e conteúdo do template são obrigatórios."})));
+    }
+    if let Err(e) = validar_parametros(payload.temperature, payload.num_ctx) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let id_template: i32 = match transaction
+        .query_opt(
+            "INSERT INTO Prompt_Template (nome, descricao) VALUES ($1, $2) ON CONFLICT (nome) DO NOTHING RETURNING id_template;",
+            &[&payload.nome, &payload.descricao],
+        )
+        .await
+    {
+        Ok(Some(row)) => row.get("id_template"),
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::CONFLICT, Json(json!({"error": "Já existe um template com esse nome."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to insert Prompt_Template: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create prompt template: {}", e)})));
+        }
+    };
+
+    let nova = NovaVersao {
+        system_prompt: payload.system_prompt.as_deref(),
+        conteudo: &payload.conteudo,
+        modelo_padrao: payload.modelo_padrao.as_deref(),
+        temperature: payload.temperature,
+        num_ctx: payload.num_ctx,
+    };
+    let (id_versao, versao) = match criar_versao(&transaction, id_template, &nova).await {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("Failed to insert Prompt_Template_Versao: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create prompt template version: {}", e)})));
+        }
+    };
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Prompt, id_template, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::CREATED, Json(json!({"message": "Template criado com sucesso", "id_template": id_template, "id_versao": id_versao, "versao": versao})))
+}
+
+// PUT /api/prompts (atualiza metadados; com `conteudo`, grava uma nova versão)
+#[tuono_lib::api(PUT)]
+async fn update_prompt(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: PromptUpdatePayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    if let Err(e) = validar_parametros(payload.temperature, payload.num_ctx) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Prompt, payload.id_template).await {
+        Ok(antes) => antes,
+        Err(e) => {
+            eprintln!("Failed to fetch prompt template: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch prompt template: {}", e)})));
+        }
+    };
+
+    match transaction
+        .execute(
+            "UPDATE Prompt_Template SET descricao = COALESCE($1, descricao), ativo = COALESCE($2, ativo) WHERE id_template = $3;",
+            &[&payload.descricao, &payload.ativo, &payload.id_template],
+        )
+        .await
+    {
+        Ok(0) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Template não encontrado."})));
+        }
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to update Prompt_Template: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update prompt template: {}", e)})));
+        }
+    }
+
+    let mut nova_versao: Option<(i32, i32)> = None;
+    if let Some(conteudo) = payload.conteudo.as_deref() {
+        if conteudo.trim().is_empty() {
+            let _ = transaction.rollback().await;
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "O conteúdo do template não pode ser vazio."})));
+        }
+        let nova = NovaVersao {
+            system_prompt: payload.system_prompt.as_deref(),
+            conteudo,
+            modelo_padrao: payload.modelo_padrao.as_deref(),
+            temperature: payload.temperature,
+            num_ctx: payload.num_ctx,
+        };
+        match criar_versao(&transaction, payload.id_template, &nova).await {
+            Ok(v) => nova_versao = Some(v),
+            Err(e) => {
+                eprintln!("Failed to insert Prompt_Template_Versao: {}", e);
+                let _ = transaction.rollback().await;
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create prompt template version: {}", e)})));
+            }
+        }
+    }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Prompt, payload.id_template, antes).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    match nova_versao {
+        Some((id_versao, versao)) => (StatusCode::OK, Json(json!({"message": "Nova versão do template criada", "id_versao": id_versao, "versao": versao}))),
+        None => (StatusCode::OK, Json(json!({"message": "Template atualizado com sucesso"}))),
+    }
+}
+
+// DELETE /api/prompts?id= (desativa o template; as versões ficam para rastrear respostas antigas)
+#[tuono_lib::api(DELETE)]
+async fn delete_prompt(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Failed to extract query values for DELETE (Prompt): {}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)})));
+        }
+    };
+
+    let id_template = match query_values.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do template deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do template é obrigatório."}))),
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Prompt, id_template).await {
+        Ok(Some(antes)) => antes,
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Template não encontrado."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch prompt template: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch prompt template: {}", e)})));
+        }
+    };
+
+    if let Err(e) = transaction.execute("UPDATE Prompt_Template SET ativo = FALSE WHERE id_template = $1;", &[&id_template]).await {
+        eprintln!("Failed to deactivate Prompt_Template: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to deactivate prompt template: {}", e)})));
+    }
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Prompt, id_template, Some(antes)).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Template desativado com sucesso."})))
+}
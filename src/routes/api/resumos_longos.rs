@@ -0,0 +1,160 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_documentos, exigir_permissao, Permissao};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::prompts::{carregar_template, TEMPLATE_RESUMO_CONSOLIDADO};
+use tuono_app::ia::resumo_longo::{agendar_resumo_longo, registrar_resumo_longo, resumo_longo_json, SELECT_RESUMO_LONGO};
+use tuono_app::ia::{modelo_analise, provider_from_env};
+use tuono_app::ia::auditoria::{auditar, ContextoAuditoria};
+use tuono_app::auth::UsuarioSessao;
+use tuono_app::limite::{limitar_usuario, Politica};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct ResumoLongoPayload {
+    id_documento: i32,
+    model: Option<String>,
+}
+
+// GET /api/resumos_longos?id= (progresso de um job) ou ?id_documento= (jobs do documento)
+#[tuono_lib::api(GET)]
+async fn resumos_longos(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Some(id_str) = query_values.get("id") {
+        let id_resumo = match id_str.parse::<i32>() {
+            Ok(id) => id,
+            Err(_) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do resumo deve ser um número inteiro."}))),
+        };
+        return match client_db.query_opt(&format!("{} WHERE id_resumo = $1;", SELECT_RESUMO_LONGO), &[&id_resumo]).await {
+            Ok(Some(row)) => match exigir_documentos(&client_db, &usuario, &[row.get("id_documento")]).await {
+                Ok(()) => (StatusCode::OK, Json(resumo_longo_json(&row))),
+                Err(_) => (StatusCode::NOT_FOUND, Json(json!({"error": "Resumo não encontrado."}))),
+            },
+            Ok(None) => (StatusCode::NOT_FOUND, Json(json!({"error": "Resumo não encontrado."}))),
+            Err(e) => {
+                eprintln!("Failed to fetch long summary: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch long summary: {}", e)})))
+            }
+        };
+    }
+
+    let id_documento = match query_values.get("id_documento").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Informe o id do resumo ou o id_documento."}))),
+    };
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[id_documento]).await {
+        return resposta;
+    }
+
+    match client_db
+        .query(&format!("{} WHERE id_documento = $1 ORDER BY data_atualizacao DESC;", SELECT_RESUMO_LONGO), &[&id_documento])
+        .await
+    {
+        Ok(rows) => {
+            let resumos: Vec<Value> = rows.iter().map(resumo_longo_json).collect();
+            (StatusCode::OK, Json(json!(resumos)))
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch long summaries: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch long summaries: {}", e)})))
+        }
+    }
+}
+
+// POST /api/resumos_longos (inicia o resumo map-reduce ou retoma um job interrompido)
+#[tuono_lib::api(POST)]
+async fn iniciar_resumo_longo(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta.into_response(),
+    };
+    if let Err(resposta) = limitar_usuario(&usuario, Politica::Ia).await {
+        return resposta;
+    }
+    iniciar_resumo_longo_apos_limite(_req, fetch, usuario).await.into_response()
+}
+
+async fn iniciar_resumo_longo_apos_limite(_req: Request, fetch: reqwest::Client, usuario: UsuarioSessao) -> impl IntoResponse {
+    let payload: ResumoLongoPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let llm = match provider_from_env(fetch) {
+        Ok(llm) => auditar(llm, ContextoAuditoria::new("resumo_longo", vec![payload.id_documento]).por(&usuario.email)),
+        Err(e) => {
+            eprintln!("{}", e);
+            return (e.status_code(), Json(json!({"error": e.to_string()})));
+        }
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    if let Err(resposta) = exigir_documentos(&client_db, &usuario, &[payload.id_documento]).await {
+        return resposta;
+    }
+
+    let modelo = match carregar_template(&client_db, TEMPLATE_RESUMO_CONSOLIDADO, None).await {
+        Ok(template) => template.modelo(payload.model.as_deref()),
+        Err(e) => {
+            eprintln!("{}", e);
+            payload.model.unwrap_or_else(modelo_analise)
+        }
+    };
+
+    let (id_resumo, status) = match registrar_resumo_longo(&client_db, payload.id_documento, &modelo).await {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+        }
+    };
+
+    // Jobs concluídos são devolvidos direto; os demais são (re)agendados e o
+    // próprio job ignora a execução se outro processo já estiver nele.
+    let status_code = if status == "concluido" {
+        StatusCode::OK
+    } else {
+        agendar_resumo_longo(llm, id_resumo);
+        StatusCode::ACCEPTED
+    };
+
+    match client_db.query_one(&format!("{} WHERE id_resumo = $1;", SELECT_RESUMO_LONGO), &[&id_resumo]).await {
+        Ok(row) => (status_code, Json(resumo_longo_json(&row))),
+        Err(e) => {
+            eprintln!("Failed to fetch long summary: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch long summary: {}", e)})))
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::ia::saude::{iniciar_monitoramento, situacao};
+
+use serde_json::json;
+
+// GET /api/saude_ia (situação do serviço de IA: disponibilidade, falhas e modelos em cache)
+#[tuono_lib::api(GET)]
+async fn saude_ia(_req: Request, fetch: reqwest::Client) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::UsarIa).await {
+        return resposta;
+    }
+
+    iniciar_monitoramento(fetch);
+    (StatusCode::OK, Json(json!(situacao())))
+}
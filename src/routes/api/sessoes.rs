@@ -0,0 +1,142 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::auditoria::{self, Autor};
+use tuono_app::auth::{exigir_sessao, inatividade_sessao_minutos, revogar_sessao, revogar_sessoes_do_usuario};
+use tuono_app::{connect_db, extract_query_values};
+
+use serde_json::{json, Value};
+
+// GET /api/sessoes (sessões ativas do usuário logado)
+#[tuono_lib::api(GET)]
+async fn listar_sessoes(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_sessao(&_req).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let rows = match client_db
+        .query(
+            "SELECT id_sessao, to_char(data_criacao, 'YYYY-MM-DD HH24:MI:SS') AS data_criacao,
+                    to_char(ultimo_acesso, 'YYYY-MM-DD HH24:MI:SS') AS ultimo_acesso,
+                    to_char(expira_em, 'YYYY-MM-DD HH24:MI:SS') AS expira_em, ip, user_agent
+             FROM Sessao
+             WHERE id_usuario = $1 AND revogada_em IS NULL AND expira_em > CURRENT_TIMESTAMP
+               AND ultimo_acesso > CURRENT_TIMESTAMP - make_interval(mins => $2)
+             ORDER BY ultimo_acesso DESC;",
+            &[&usuario.id_usuario, &inatividade_sessao_minutos()],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch sessions: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch sessions: {}", e)})));
+        }
+    };
+
+    let sessoes: Vec<Value> = rows.into_iter().map(|row| {
+        let id_sessao: i32 = row.get("id_sessao");
+        json!({
+            "id_sessao": id_sessao,
+            "atual": id_sessao == usuario.id_sessao,
+            "data_criacao": row.get::<_, Option<String>>("data_criacao"),
+            "ultimo_acesso": row.get::<_, Option<String>>("ultimo_acesso"),
+            "expira_em": row.get::<_, Option<String>>("expira_em"),
+            "ip": row.get::<_, Option<String>>("ip"),
+            "user_agent": row.get::<_, Option<String>>("user_agent"),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(sessoes)))
+}
+
+// DELETE /api/sessoes?id= (revoga uma sessão; sem `id`, revoga todas menos a atual)
+#[tuono_lib::api(DELETE)]
+async fn revogar_sessoes(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_sessao(&_req).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let id_sessao = if query_string.trim().is_empty() {
+        None
+    } else {
+        let query_values = match extract_query_values(query_string) {
+            Ok(values) => values,
+            Err(e) => {
+                eprintln!("Failed to extract query values for DELETE (Sessao): {}", e);
+                return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)})));
+            }
+        };
+        match query_values.get("id").map(|id| id.parse::<i32>()) {
+            Some(Ok(id)) => Some(id),
+            Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID da sessão deve ser um número inteiro."}))),
+            None => None,
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let revogadas = match id_sessao {
+        Some(id_sessao) => match revogar_sessao(&transaction, usuario.id_usuario, id_sessao).await {
+            Ok(true) => vec![id_sessao],
+            Ok(false) => {
+                let _ = transaction.rollback().await;
+                return (StatusCode::NOT_FOUND, Json(json!({"error": "Sessão não encontrada."})));
+            }
+            Err(e) => {
+                eprintln!("Failed to revoke session: {}", e);
+                let _ = transaction.rollback().await;
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to revoke session: {}", e)})));
+            }
+        },
+        None => match revogar_sessoes_do_usuario(&transaction, usuario.id_usuario, Some(usuario.id_sessao)).await {
+            Ok(revogadas) => revogadas,
+            Err(e) => {
+                eprintln!("Failed to revoke sessions: {}", e);
+                let _ = transaction.rollback().await;
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to revoke sessions: {}", e)})));
+            }
+        },
+    };
+
+    if let Err(e) = auditoria::registrar_revogacoes(&transaction, &Autor::new(&_req, &usuario), &revogadas).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    match id_sessao {
+        Some(_) => (StatusCode::OK, Json(json!({"message": "Sessão revogada."}))),
+        None => (StatusCode::OK, Json(json!({"message": "Outras sessões revogadas.", "revogadas": revogadas.len()}))),
+    }
+}
@@ -0,0 +1,267 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{escopo_casos, exigir_caso, exigir_permissao, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::{connect_db, extract_query_values};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Deserialize)]
+struct DecisaoPayload {
+    id_sugestao: i32,
+    acao: String, // "aceitar" ou "rejeitar"
+    id_advogado: Option<i32>, // Padrão: advogado responsável pelo caso
+    data_tarefa: Option<String>, // AAAA-MM-DD, padrão: vencimento calculado
+    descricao: Option<String>,
+}
+
+// GET /api/tarefas_sugeridas?id_caso= ou ?id_documento= (opcional: &status=pendente)
+#[tuono_lib::api(GET)]
+async fn tarefas_sugeridas(_req: Request) -> impl IntoResponse {
+    // As sugestões trazem trechos dos documentos
+    let usuario = match exigir_permissao(&_req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)}))),
+    };
+
+    let id_caso = match query_values.get("id_caso").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do caso deve ser um número inteiro."}))),
+        None => None,
+    };
+    let id_documento = match query_values.get("id_documento").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => Some(id),
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do documento deve ser um número inteiro."}))),
+        None => None,
+    };
+    if id_caso.is_none() && id_documento.is_none() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Informe 'id_caso' ou 'id_documento'."})));
+    }
+    let status = query_values.get("status").cloned();
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to load case scope: {}", e)})));
+        }
+    };
+
+    let rows = match client_db
+        .query(
+            "SELECT ts.id_sugestao, ts.id_caso, ts.id_documento, d.nome_arquivo, ts.descricao, ts.prazo_dias, ts.dias_uteis,
+                    ts.data_base, ts.data_vencimento, ts.origem, ts.trecho, ts.inicio_trecho, ts.fim_trecho,
+                    ts.status, ts.id_tarefa, ts.data_decisao
+             FROM Tarefa_Sugerida ts
+             INNER JOIN Documento d ON ts.id_documento = d.id_documento
+             WHERE ($1::INTEGER IS NULL OR ts.id_caso = $1)
+               AND ($2::INTEGER IS NULL OR ts.id_documento = $2)
+               AND ($3::VARCHAR IS NULL OR ts.status = $3)
+               AND ($4::INTEGER[] IS NULL OR ts.id_caso = ANY($4))
+             ORDER BY ts.data_vencimento ASC, ts.id_sugestao ASC;",
+            &[&id_caso, &id_documento, &status, &ids_visiveis],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch task suggestions: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch task suggestions: {}", e)})));
+        }
+    };
+
+    let sugestoes: Vec<Value> = rows.into_iter().map(|row| {
+        let data_base: NaiveDate = row.get("data_base");
+        let data_vencimento: NaiveDate = row.get("data_vencimento");
+        let data_decisao: Option<NaiveDateTime> = row.get("data_decisao");
+        json!({
+            "id_sugestao": row.get::<_, i32>("id_sugestao"),
+            "id_caso": row.get::<_, i32>("id_caso"),
+            "id_documento": row.get::<_, i32>("id_documento"),
+            "nome_arquivo": row.get::<_, Option<String>>("nome_arquivo"),
+            "descricao": row.get::<_, String>("descricao"),
+            "prazo_dias": row.get::<_, i32>("prazo_dias"),
+            "dias_uteis": row.get::<_, bool>("dias_uteis"),
+            "data_base": data_base.to_string(),
+            "data_vencimento": data_vencimento.to_string(),
+            "origem": row.get::<_, String>("origem"),
+            // Trecho de origem e sua posição no texto extraído do documento
+            "trecho": row.get::<_, String>("trecho"),
+            "inicio_trecho": row.get::<_, i32>("inicio_trecho"),
+            "fim_trecho": row.get::<_, i32>("fim_trecho"),
+            "status": row.get::<_, String>("status"),
+            "id_tarefa": row.get::<_, Option<i32>>("id_tarefa"),
+            "data_decisao": data_decisao.map(|d| d.to_string()),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(sugestoes)))
+}
+
+// PUT /api/tarefas_sugeridas (aceitar ou rejeitar uma sugestão)
+#[tuono_lib::api(PUT)]
+async fn decidir_tarefa_sugerida(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::EditarCasos).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: DecisaoPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    if payload.acao != "aceitar" && payload.acao != "rejeitar" {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Ação inválida. Use 'aceitar' ou 'rejeitar'."})));
+    }
+
+    let data_tarefa = match payload.data_tarefa.as_deref().map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d")) {
+        Some(Ok(d)) => Some(d),
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Data da tarefa inválida. Use o formato AAAA-MM-DD."}))),
+        None => None,
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    // Só decide a sugestão quem vê o caso
+    match client_db.query_opt("SELECT id_caso FROM Tarefa_Sugerida WHERE id_sugestao = $1;", &[&payload.id_sugestao]).await {
+        Ok(Some(row)) => {
+            if let Err(resposta) = exigir_caso(&client_db, &usuario, row.get("id_caso")).await {
+                return resposta;
+            }
+        }
+        Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({"error": "Sugestão de tarefa não encontrada."}))),
+        Err(e) => {
+            eprintln!("Failed to fetch task suggestion: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch task suggestion: {}", e)})));
+        }
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let row = match transaction
+        .query_opt(
+            "SELECT ts.id_caso, ts.descricao, ts.data_vencimento, ts.status, c.id_advogado
+             FROM Tarefa_Sugerida ts
+             INNER JOIN Caso c ON ts.id_caso = c.id_caso
+             WHERE ts.id_sugestao = $1
+             FOR UPDATE OF ts;",
+            &[&payload.id_sugestao],
+        )
+        .await
+    {
+        Ok(Some(row)) => row,
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Sugestão de tarefa não encontrada."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch task suggestion: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch task suggestion: {}", e)})));
+        }
+    };
+
+    let status: String = row.get("status");
+    if status != "pendente" {
+        let _ = transaction.rollback().await;
+        return (StatusCode::CONFLICT, Json(json!({"error": format!("Sugestão já foi decidida ({}).", status)})));
+    }
+
+    if payload.acao == "rejeitar" {
+        if let Err(e) = transaction
+            .execute(
+                "UPDATE Tarefa_Sugerida SET status = 'rejeitada', data_decisao = CURRENT_TIMESTAMP WHERE id_sugestao = $1;",
+                &[&payload.id_sugestao],
+            )
+            .await
+        {
+            eprintln!("Failed to reject task suggestion: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to reject task suggestion: {}", e)})));
+        }
+
+        if let Err(e) = transaction.commit().await {
+            eprintln!("Failed to commit transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+        }
+        return (StatusCode::OK, Json(json!({"message": "Sugestão rejeitada."})));
+    }
+
+    let id_caso: i32 = row.get("id_caso");
+    let id_advogado: i32 = payload.id_advogado.unwrap_or_else(|| row.get("id_advogado"));
+    let descricao: String = payload.descricao.unwrap_or_else(|| row.get("descricao"));
+    let data_tarefa: NaiveDate = data_tarefa.unwrap_or_else(|| row.get("data_vencimento"));
+
+    let id_tarefa: i32 = match transaction
+        .query_one(
+            "INSERT INTO Tarefa (id_caso, id_advogado, descricao, data_tarefa) VALUES ($1, $2, $3, $4) RETURNING id_tarefa;",
+            &[&id_caso, &id_advogado, &descricao, &data_tarefa],
+        )
+        .await
+    {
+        Ok(row) => row.get("id_tarefa"),
+        Err(e) => {
+            eprintln!("Failed to insert Tarefa: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create task: {}", e)})));
+        }
+    };
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Tarefa, id_tarefa, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction
+        .execute(
+            "UPDATE Tarefa_Sugerida SET status = 'aceita', id_tarefa = $1, data_decisao = CURRENT_TIMESTAMP WHERE id_sugestao = $2;",
+            &[&id_tarefa, &payload.id_sugestao],
+        )
+        .await
+    {
+        eprintln!("Failed to accept task suggestion: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to accept task suggestion: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::CREATED, Json(json!({"message": "Sugestão aceita e tarefa criada.", "id_tarefa": id_tarefa})))
+}
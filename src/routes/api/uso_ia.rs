@@ -0,0 +1,52 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Permissao};
+use tuono_app::{connect_db, extract_query_values};
+use tuono_app::ia::auditoria::{uso_agregado, AGRUPAMENTOS_USO};
+
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
+use serde_json::json;
+
+// GET /api/uso_ia?agrupar=modelo|usuario|origem[&inicio=AAAA-MM-DD&fim=AAAA-MM-DD]
+#[tuono_lib::api(GET)]
+async fn uso_ia(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarIa).await {
+        return resposta;
+    }
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(_) if query_string.trim().is_empty() => HashMap::new(),
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)}))),
+    };
+
+    let agrupar = query_values.get("agrupar").map(String::as_str).unwrap_or("modelo");
+    if !AGRUPAMENTOS_USO.contains(&agrupar) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Agrupamento inválido: '{}'. Use {}.", agrupar, AGRUPAMENTOS_USO.join(", "))})));
+    }
+    let data = |campo: &str| query_values.get(campo).map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d")).transpose();
+    let (inicio, fim) = match (data("inicio"), data("fim")) {
+        (Ok(inicio), Ok(fim)) => (inicio, fim),
+        _ => return (StatusCode::BAD_REQUEST, Json(json!({"error": "Datas devem estar no formato AAAA-MM-DD."}))),
+    };
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    match uso_agregado(&client_db, agrupar, inicio, fim).await {
+        Ok(uso) => (StatusCode::OK, Json(json!(uso))),
+        Err(e) => {
+            eprintln!("{}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})))
+        }
+    }
+}
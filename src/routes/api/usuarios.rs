@@ -0,0 +1,306 @@
+use tuono_lib::axum::response::{IntoResponse, Json};
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::Request;
+use tuono_app::acesso::{exigir_permissao, Papel, Permissao};
+use tuono_app::auditoria::{self, Autor, Entidade};
+use tuono_app::auth::{conferir_senha, exigir_sessao, hash_senha, revogar_sessoes_do_usuario, validar_senha_nova};
+use tuono_app::{connect_db, extract_query_values};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+#[derive(Debug, Deserialize)]
+struct UsuarioPayload {
+    nome: String,
+    email: String,
+    senha: String,
+    papel: Papel,
+    id_advogado: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UsuarioUpdatePayload {
+    nome: Option<String>,
+    senha: Option<String>,
+    senha_atual: Option<String>, // Obrigatória para trocar a própria senha
+    ativo: Option<bool>,
+    papel: Option<Papel>,
+    id_advogado: Option<i32>,
+}
+
+// GET /api/usuarios
+#[tuono_lib::api(GET)]
+async fn listar_usuarios(_req: Request) -> impl IntoResponse {
+    if let Err(resposta) = exigir_permissao(&_req, Permissao::GerenciarUsuarios).await {
+        return resposta;
+    }
+
+    let client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let rows = match client_db
+        .query(
+            "SELECT id_usuario, nome, email, papel, id_advogado, ativo, to_char(data_criacao, 'YYYY-MM-DD HH24:MI:SS') AS data_criacao
+             FROM Usuario ORDER BY nome ASC;",
+            &[],
+        )
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            eprintln!("Failed to fetch users: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch users: {}", e)})));
+        }
+    };
+
+    let usuarios: Vec<Value> = rows.into_iter().map(|row| {
+        json!({
+            "id_usuario": row.get::<_, i32>("id_usuario"),
+            "nome": row.get::<_, String>("nome"),
+            "email": row.get::<_, String>("email"),
+            "papel": row.get::<_, String>("papel"),
+            "id_advogado": row.get::<_, Option<i32>>("id_advogado"),
+            "ativo": row.get::<_, bool>("ativo"),
+            "data_criacao": row.get::<_, Option<String>>("data_criacao"),
+        })
+    }).collect();
+
+    (StatusCode::OK, Json(json!(usuarios)))
+}
+
+// POST /api/usuarios
+#[tuono_lib::api(POST)]
+async fn create_usuario(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_permissao(&_req, Permissao::GerenciarUsuarios).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let payload: UsuarioPayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let email = payload.email.trim().to_lowercase();
+    if payload.nome.trim().is_empty() || email.is_empty() {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Nome e e-mail são obrigatórios."})));
+    }
+    if let Err(e) = validar_senha_nova(&payload.senha) {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+    }
+    let senha_hash = match hash_senha(&payload.senha) {
+        Ok(hash) => hash,
+        Err(e) => {
+            eprintln!("{}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+        }
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let id_usuario: i32 = match transaction
+        .query_opt(
+            "INSERT INTO Usuario (nome, email, senha_hash, papel, id_advogado) VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (email) DO NOTHING RETURNING id_usuario;",
+            &[&payload.nome.trim(), &email, &senha_hash, &payload.papel.as_str(), &payload.id_advogado],
+        )
+        .await
+    {
+        Ok(Some(row)) => row.get("id_usuario"),
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::CONFLICT, Json(json!({"error": "Já existe um usuário com este e-mail."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to insert Usuario: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to create user: {}", e)})));
+        }
+    };
+
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &Autor::new(&_req, &usuario), Entidade::Usuario, id_usuario, None).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::CREATED, Json(json!({"message": "Usuário criado com sucesso", "id_usuario": id_usuario})))
+}
+
+// PUT /api/usuarios?id= (desativar ou trocar a senha encerra as sessões do usuário).
+// Sem a permissão de gerenciar usuários, só é possível alterar nome e senha do próprio usuário.
+#[tuono_lib::api(PUT)]
+async fn update_usuario(_req: Request) -> impl IntoResponse {
+    let usuario = match exigir_sessao(&_req).await {
+        Ok(usuario) => usuario,
+        Err(resposta) => return resposta,
+    };
+
+    let query_string = _req.uri.query().unwrap_or("");
+    let query_values = match extract_query_values(query_string) {
+        Ok(values) => values,
+        Err(e) => {
+            eprintln!("Failed to extract query values for PUT (Usuario): {}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid query parameters: {}", e)})));
+        }
+    };
+    let id_usuario = match query_values.get("id").map(|id| id.parse::<i32>()) {
+        Some(Ok(id)) => id,
+        Some(Err(_)) => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do usuário deve ser um número inteiro."}))),
+        None => return (StatusCode::BAD_REQUEST, Json(json!({"error": "ID do usuário é obrigatório."}))),
+    };
+
+    let payload: UsuarioUpdatePayload = match _req.body() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to read/parse JSON body from Request: {:?}", e);
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": format!("Invalid request body or JSON parsing error: {:?}", e)})));
+        }
+    };
+
+    let altera_perfil = payload.ativo.is_some() || payload.papel.is_some() || payload.id_advogado.is_some();
+    if !usuario.pode(Permissao::GerenciarUsuarios) && (id_usuario != usuario.id_usuario || altera_perfil) {
+        return (StatusCode::FORBIDDEN, Json(json!({"error": "Seu perfil não tem permissão para esta ação."})));
+    }
+
+    if payload.ativo == Some(false) && id_usuario == usuario.id_usuario {
+        return (StatusCode::BAD_REQUEST, Json(json!({"error": "Você não pode desativar o próprio usuário."})));
+    }
+
+    let senha_hash = match payload.senha.as_deref() {
+        Some(senha) => {
+            if let Err(e) = validar_senha_nova(senha) {
+                return (StatusCode::BAD_REQUEST, Json(json!({"error": e})));
+            }
+            match hash_senha(senha) {
+                Ok(hash) => Some(hash),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": e})));
+                }
+            }
+        }
+        None => None,
+    };
+
+    let mut client_db = match connect_db().await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to connect to database: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Database connection error: {}", e)})));
+        }
+    };
+
+    // Quem troca a própria senha confirma a atual: uma sessão esquecida aberta
+    // não basta para tomar a conta
+    if senha_hash.is_some() && id_usuario == usuario.id_usuario {
+        let hash_atual = match client_db.query_one("SELECT senha_hash FROM Usuario WHERE id_usuario = $1;", &[&id_usuario]).await {
+            Ok(row) => row.get::<_, String>("senha_hash"),
+            Err(e) => {
+                eprintln!("Failed to fetch user: {}", e);
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch user: {}", e)})));
+            }
+        };
+        let Some(senha_atual) = payload.senha_atual.as_deref() else {
+            return (StatusCode::BAD_REQUEST, Json(json!({"error": "Informe a senha atual ('senha_atual') para trocar a senha."})));
+        };
+        if !conferir_senha(senha_atual, Some(&hash_atual)).await {
+            return (StatusCode::FORBIDDEN, Json(json!({"error": "Senha atual incorreta."})));
+        }
+    }
+
+    let transaction = match client_db.transaction().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            eprintln!("Failed to start transaction: {}", e);
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to start transaction: {}", e)})));
+        }
+    };
+
+    let antes = match auditoria::estado(&transaction, Entidade::Usuario, id_usuario).await {
+        Ok(Some(antes)) => antes,
+        Ok(None) => {
+            let _ = transaction.rollback().await;
+            return (StatusCode::NOT_FOUND, Json(json!({"error": "Usuário não encontrado."})));
+        }
+        Err(e) => {
+            eprintln!("Failed to fetch user: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to fetch user: {}", e)})));
+        }
+    };
+
+    let nome = payload.nome.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if let Err(e) = transaction
+        .execute(
+            "UPDATE Usuario SET nome = COALESCE($1, nome), senha_hash = COALESCE($2, senha_hash), ativo = COALESCE($3, ativo),
+                    papel = COALESCE($4, papel), id_advogado = COALESCE($5, id_advogado)
+             WHERE id_usuario = $6;",
+            &[&nome, &senha_hash, &payload.ativo, &payload.papel.map(|p| p.as_str()), &payload.id_advogado, &id_usuario],
+        )
+        .await
+    {
+        eprintln!("Failed to update Usuario: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to update user: {}", e)})));
+    }
+
+    let autor = Autor::new(&_req, &usuario);
+    if let Err(e) = auditoria::registrar_alteracao(&transaction, &autor, Entidade::Usuario, id_usuario, Some(antes)).await {
+        eprintln!("Failed to write audit record: {}", e);
+        let _ = transaction.rollback().await;
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+    }
+
+    // Quem troca a própria senha continua logado nesta sessão; as outras caem
+    if senha_hash.is_some() || payload.ativo == Some(false) {
+        let exceto = (id_usuario == usuario.id_usuario).then_some(usuario.id_sessao);
+        let revogadas = match revogar_sessoes_do_usuario(&transaction, id_usuario, exceto).await {
+            Ok(revogadas) => revogadas,
+            Err(e) => {
+                eprintln!("Failed to revoke sessions: {}", e);
+                let _ = transaction.rollback().await;
+                return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to revoke sessions: {}", e)})));
+            }
+        };
+        if let Err(e) = auditoria::registrar_revogacoes(&transaction, &autor, &revogadas).await {
+            eprintln!("Failed to write audit record: {}", e);
+            let _ = transaction.rollback().await;
+            return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to write audit record: {}", e)})));
+        }
+    }
+
+    if let Err(e) = transaction.commit().await {
+        eprintln!("Failed to commit transaction: {}", e);
+        return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({"error": format!("Failed to commit transaction: {}", e)})));
+    }
+
+    (StatusCode::OK, Json(json!({"message": "Usuário atualizado com sucesso"})))
+}
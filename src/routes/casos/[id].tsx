@@ -3,11 +3,41 @@ import { useEffect, useState } from 'react';
 import type { JSX } from 'react';
 import type { Case } from '../../components/CaseCard';
 
+interface MatchingPassage {
+  origem: string;
+  trecho: string;
+  origem_referencia: string;
+  trecho_referencia: string;
+  similaridade: number;
+}
+
+interface SimilarCase {
+  id_caso: number;
+  numero_processo: string | null;
+  descricao: string | null;
+  similaridade: number;
+  trechos: MatchingPassage[];
+}
+
+interface CategorySuggestion {
+  id_sugestao: number;
+  id_categoria_caso: number;
+  categoria: string;
+  confianca: number;
+  origem: string;
+  justificativa: string | null;
+}
+
 export default function CaseDetailsPage(): JSX.Element {
   const router = useRouter();
   const [caseItem, setCaseItem] = useState<Case | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState<boolean>(true);
+  const [similarCases, setSimilarCases] = useState<SimilarCase[] | null>(null);
+  const [isLoadingSimilar, setIsLoadingSimilar] = useState<boolean>(false);
+  const [similarError, setSimilarError] = useState<string | null>(null);
+  const [categorySuggestion, setCategorySuggestion] = useState<CategorySuggestion | null>(null);
+  const [suggestionError, setSuggestionError] = useState<string | null>(null);
 
   const id = router.pathname.split('/').pop();
 
@@ -38,6 +68,57 @@ export default function CaseDetailsPage(): JSX.Element {
       });
   }, [id]);
 
+  // Sugestão de categoria pendente, só para casos ainda sem categoria
+  useEffect(() => {
+    if (!caseItem || caseItem.categoria_descricao) return;
+    fetch(`/api/categorias_sugeridas?id_caso=${caseItem.id_caso}&status=pendente`)
+      .then(res => res.json())
+      .then((data) => {
+        if (Array.isArray(data) && data.length > 0) setCategorySuggestion(data[0]);
+      })
+      .catch(() => setCategorySuggestion(null));
+  }, [caseItem]);
+
+  const handleDecideSuggestion = async (acao: 'aceitar' | 'rejeitar') => {
+    if (!categorySuggestion) return;
+    setSuggestionError(null);
+    try {
+      const res = await fetch('/api/categorias_sugeridas', {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ id_sugestao: categorySuggestion.id_sugestao, acao }),
+      });
+      const data = await res.json();
+      if (!res.ok || data.error) {
+        throw new Error(data.error || `Erro ${res.status}`);
+      }
+      if (acao === 'aceitar' && caseItem) {
+        setCaseItem({ ...caseItem, categoria_descricao: categorySuggestion.categoria });
+      }
+      setCategorySuggestion(null);
+    } catch (err: any) {
+      setSuggestionError(`Erro ao decidir a sugestão: ${err.message}`);
+    }
+  };
+
+  // Busca sob demanda: na primeira vez, calcula os embeddings de todos os casos
+  const handleFindSimilar = async () => {
+    setIsLoadingSimilar(true);
+    setSimilarError(null);
+    try {
+      const res = await fetch(`/api/casos/similares?id=${id}`);
+      const data = await res.json();
+      if (!res.ok || data.error) {
+        throw new Error(data.error || `Erro ${res.status}`);
+      }
+      setSimilarCases(data.similares);
+    } catch (err: any) {
+      setSimilarError(`Erro ao buscar casos semelhantes: ${err.message}`);
+    } finally {
+      setIsLoadingSimilar(false);
+    }
+  };
+
   if (isLoading) {
     return <div className="loading-container"><h1>Carregando detalhes do caso...</h1></div>;
   }
@@ -83,6 +164,23 @@ export default function CaseDetailsPage(): JSX.Element {
             <p className="form-input" style={{ backgroundColor: '#f0f0f0', border: '1px solid #e0e0e0' }}>{caseItem.categoria_descricao}</p>
           </div>
         )}
+        {categorySuggestion && (
+          <div className="form-group">
+            <label className="form-label">Categoria sugerida (aguardando confirmação):</label>
+            <div style={{ padding: '0.75rem', backgroundColor: '#fff8e1', border: '1px solid #ffe082', borderRadius: '0.5rem' }}>
+              <p>
+                <strong>{categorySuggestion.categoria}</strong> ({Math.round(categorySuggestion.confianca * 100)}% de confiança,{' '}
+                {categorySuggestion.origem === 'llm' ? 'sugerida por IA' : 'por palavras-chave'})
+              </p>
+              {categorySuggestion.justificativa && <p style={{ color: '#555', marginTop: '0.25rem' }}>{categorySuggestion.justificativa}</p>}
+              <div style={{ display: 'flex', gap: '0.5rem', marginTop: '0.5rem' }}>
+                <button type="button" className="submit-button" onClick={() => handleDecideSuggestion('aceitar')}>Aceitar</button>
+                <button type="button" className="cancel-button" onClick={() => handleDecideSuggestion('rejeitar')}>Rejeitar</button>
+              </div>
+              {suggestionError && <p className="error-message" style={{ marginTop: '0.5rem' }}>{suggestionError}</p>}
+            </div>
+          </div>
+        )}
         <div className="form-group">
           <label className="form-label">Descrição do Caso:</label>
           <p className="form-input" style={{ backgroundColor: '#f0f0f0', border: '1px solid #e0e0e0' }}>{caseItem.descricao || 'N/A'}</p>
@@ -106,7 +204,32 @@ export default function CaseDetailsPage(): JSX.Element {
         <Link href="/casos" className="cancel-button">
           Voltar para Casos
         </Link>
+        <button type="button" className="submit-button" onClick={handleFindSimilar} disabled={isLoadingSimilar}>
+          {isLoadingSimilar ? 'Buscando casos semelhantes...' : 'Casos Semelhantes'}
+        </button>
       </div>
+
+      {similarError && <p className="error-message" style={{ marginTop: '1rem' }}>{similarError}</p>}
+
+      {similarCases && (
+        <div className="response-section" style={{ marginTop: '2rem', padding: '1.5rem', backgroundColor: '#f0f8ff', borderRadius: '0.75rem', boxShadow: 'var(--shadow-light)' }}>
+          <h2 style={{ fontSize: '1.5rem', color: 'var(--dark-text)', marginBottom: '1rem' }}>Casos Semelhantes</h2>
+          {similarCases.length === 0 && <p>Nenhum caso semelhante encontrado.</p>}
+          {similarCases.map(similar => (
+            <div key={similar.id_caso} style={{ marginBottom: '1.5rem' }}>
+              <Link href={`/casos/${similar.id_caso}`}>
+                Caso #{similar.id_caso}{similar.numero_processo ? ` - ${similar.numero_processo}` : ''}
+              </Link>
+              {' '}({Math.round(similar.similaridade * 100)}% de similaridade)
+              {similar.trechos.map((t, i) => (
+                <p key={i} style={{ whiteSpace: 'pre-wrap', color: '#333', marginTop: '0.5rem' }}>
+                  <strong>{t.origem}:</strong> {t.trecho.length > 300 ? `${t.trecho.slice(0, 300)}...` : t.trecho}
+                </p>
+              ))}
+            </div>
+          ))}
+        </div>
+      )}
     </div>
   );
 }
\ No newline at end of file
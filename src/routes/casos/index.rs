@@ -1,6 +1,7 @@
 // src/routes/casos/index.rs
 use serde::{Deserialize, Serialize};
 use tuono_lib::{Props, Request, Response};
+use tuono_app::acesso::{escopo_casos, exigir_permissao_pagina, Permissao};
 use tuono_lib::axum::http;
 use tuono_app::connect_db;
 use chrono::NaiveDate;
@@ -39,6 +40,11 @@ struct CasesPageProps {
 #[allow(unused_variables)]
 #[tuono_lib::handler]
 async fn get_cases(req: Request) -> Response {
+    let usuario = match exigir_permissao_pagina(&req, Permissao::VerCasos).await {
+        Ok(usuario) => usuario,
+        Err(redirecionamento) => return redirecionamento,
+    };
+
     let client_db = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -47,6 +53,15 @@ async fn get_cases(req: Request) -> Response {
         }
     };
 
+    // Só os casos visíveis para o usuário ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Database query error: {}", e)));
+        }
+    };
+
     let base_query = "
         SELECT
             c.id_caso, c.descricao, c.numero_processo, c.data_abertura, c.data_fechamento,
@@ -61,11 +76,12 @@ async fn get_cases(req: Request) -> Response {
         INNER JOIN Status s ON c.id_status = s.id_status
         LEFT JOIN Vara_Judicial vj ON c.id_vara_judicial = vj.id_vara_judicial
         LEFT JOIN Categoria_caso cc ON c.id_categoria_caso = cc.id_categoria_caso
+        WHERE ($1::INTEGER[] IS NULL OR c.id_caso = ANY($1))
         ORDER BY c.data_abertura DESC;
     ";
 
     let rows = match client_db
-        .query(base_query, &[])
+        .query(base_query, &[&ids_visiveis])
         .await
     {
         Ok(rows) => rows,
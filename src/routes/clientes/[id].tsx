@@ -8,6 +8,7 @@ export default function ClientePage(): JSX.Element {
   const [cliente, setCliente] = useState<any>(null)
   const [erro, setErro] = useState<string | null>(null)
   const [isLoading, setIsLoading] = useState<boolean>(true); // Add loading state
+  const [relatorio, setRelatorio] = useState<any>(null) // Relatório da anonimização (LGPD)
 
   const id = router.pathname.split('/').pop()
 
@@ -45,6 +46,40 @@ export default function ClientePage(): JSX.Element {
       })
   }, [id])
 
+  // Mostra a prévia do que será anonimizado e pede a confirmação por escrito
+  const handleAnonimizar = async () => {
+    try {
+      const previa = await fetch(`/api/clientes/${id}/anonimizacao`)
+      const dadosPrevia = await previa.json()
+      if (!previa.ok) {
+        alert(`Erro: ${dadosPrevia.error}`)
+        return
+      }
+      const mantidos = dadosPrevia.mantidos.map((m: any) => `- ${m.item}: ${m.motivo}`).join('\n')
+      const confirmacao = window.prompt(
+        `Os dados pessoais do cliente serão trocados por pseudônimos. ` +
+        `Casos anonimizados: ${dadosPrevia.casos_anonimizados.length}. ` +
+        `Documentos removidos: ${dadosPrevia.documentos_removidos.length}.\n\nSerão mantidos:\n${mantidos}\n\n` +
+        `Esta ação não pode ser desfeita. Para confirmar, digite: ANONIMIZAR`
+      )
+      if (confirmacao === null) return
+
+      const response = await fetch(`/api/clientes/${id}/anonimizacao`, {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ confirmacao }),
+      })
+      const data = await response.json()
+      if (!response.ok) {
+        alert(`Erro: ${data.error}`)
+        return
+      }
+      setRelatorio(data)
+    } catch (err: any) {
+      alert(`Erro ao anonimizar cliente: ${err.message}`)
+    }
+  }
+
   if (isLoading) {
     return <div className="loading-container"><h1>Carregando detalhes do cliente...</h1></div>;
   }
@@ -100,10 +135,31 @@ export default function ClientePage(): JSX.Element {
         <Link href={`/clientes/edit/${cliente.id_cliente}`} className="submit-button" style={{ backgroundColor: '#ffc107', color: '#333' }}>
           Editar Cliente
         </Link>
+        <a href={`/api/clientes/${cliente.id_cliente}/export`} className="submit-button">
+          Exportar Dados (LGPD)
+        </a>
+        <button type="button" className="cancel-button" onClick={handleAnonimizar} disabled={relatorio !== null}>
+          Anonimizar Cliente
+        </button>
         <Link href="/clientes" className="cancel-button">
           Voltar para Clientes
         </Link>
       </div>
+
+      {relatorio && (
+        <div className="client-form" style={{ gap: '0.5rem' }}>
+          <h2>Cliente anonimizado</h2>
+          <p>Campos pseudonimizados: {relatorio.campos_pseudonimizados.join(', ')}</p>
+          <p>Casos anonimizados: {relatorio.casos_anonimizados.length > 0 ? relatorio.casos_anonimizados.join(', ') : 'nenhum'}</p>
+          <p>Documentos removidos: {relatorio.documentos_removidos.length > 0 ? relatorio.documentos_removidos.join(', ') : 'nenhum'}</p>
+          <h3>Mantidos</h3>
+          <ul>
+            {relatorio.mantidos.map((m: any, i: number) => (
+              <li key={i}><strong>{m.item}</strong>: {m.motivo}</li>
+            ))}
+          </ul>
+        </div>
+      )}
     </div>
   )
 }
\ No newline at end of file
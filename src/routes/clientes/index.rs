@@ -1,6 +1,7 @@
 // src/routes/index.rs
 use serde::{Deserialize, Serialize};
 use tuono_lib::{Props, Request, Response};
+use tuono_app::acesso::{escopo_casos, exigir_permissao_pagina, Permissao};
 use tuono_lib::axum::http;
 use tuono_app::{connect_db}; 
 use chrono::NaiveDate; 
@@ -25,6 +26,11 @@ struct Cliente {
 #[allow(unused_variables)] 
 #[tuono_lib::handler]
 async fn get_clientes(req: Request) -> Response {
+    let usuario = match exigir_permissao_pagina(&req, Permissao::VerClientes).await {
+        Ok(usuario) => usuario,
+        Err(redirecionamento) => return redirecionamento,
+    };
+
     let client_db = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -33,6 +39,15 @@ async fn get_clientes(req: Request) -> Response {
         }
     };
 
+    // Só clientes com algum caso visível para o usuário ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Database query error: {}", e)));
+        }
+    };
+
     let rows = match client_db
         .query(
             "SELECT 
@@ -42,8 +57,9 @@ async fn get_clientes(req: Request) -> Response {
             FROM Cliente c
             LEFT JOIN Pessoa_Fisica pf ON c.id_cliente = pf.id_cliente
             LEFT JOIN Pessoa_Juridica pj ON c.id_cliente = pj.id_cliente
+            WHERE $1::INTEGER[] IS NULL OR EXISTS (SELECT 1 FROM Caso k WHERE k.id_cliente = c.id_cliente AND k.id_caso = ANY($1))
             ORDER BY c.nome",
-            &[],
+            &[&ids_visiveis],
         )
         .await
     {
@@ -6,14 +6,22 @@ export default function ConfiguracoesPage(): JSX.Element {
   const [message, setMessage] = useState<string | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState<string | null>(null); // Para indicar qual botão está carregando
+  const [forcar, setForcar] = useState(false);
 
-  const handleDbOperation = async (method: string, endpoint: string, buttonLabel: string) => {
+  // O servidor só executa com a frase digitada exatamente (ver src/routes/api/init_clean.rs)
+  const handleDbOperation = async (method: string, endpoint: string, buttonLabel: string, frase: string) => {
     setMessage(null);
     setError(null);
+
+    const confirmacao = window.prompt(`Para confirmar "${buttonLabel}", digite: ${frase}`);
+    if (confirmacao === null) return;
+
     setIsLoading(buttonLabel); // Define qual botão está carregando
 
     try {
-      const response = await fetch(endpoint, {
+      const params = new URLSearchParams({ confirmacao });
+      if (forcar) params.set('forcar', 'true');
+      const response = await fetch(`${endpoint}?${params.toString()}`, {
         method: method,
       });
 
@@ -45,14 +53,20 @@ export default function ConfiguracoesPage(): JSX.Element {
       <h1 className="page-title">Configurações de Banco de Dados</h1>
       <p className="page-description">
         Utilize estes botões para gerenciar o estado do seu banco de dados durante o desenvolvimento.
-        **Cuidado**: A operação DELETE limpa todos os dados!
+        **Cuidado**: A operação DELETE limpa todos os dados! Limpar e popular só funcionam com
+        BANCO_OPERACOES_DEBUG=true no servidor e para administradores.
       </p>
 
+      <label style={{ display: 'flex', gap: '0.5rem', alignItems: 'center', justifyContent: 'center' }}>
+        <input type="checkbox" checked={forcar} onChange={(e) => setForcar(e.target.checked)} />
+        Forçar mesmo com dados que não são da carga fictícia
+      </label>
+
       <div className="form-actions" style={{ flexDirection: 'column', gap: '1.5rem', alignItems: 'center' }}>
         {/* Botão DELETE */}
         <button
           className="submit-button delete-button"
-          onClick={() => handleDbOperation('DELETE', '/api/init_clean', 'LIMPAR DB (DEBUG)')}
+          onClick={() => handleDbOperation('DELETE', '/api/init_clean', 'LIMPAR DB (DEBUG)', 'APAGAR TODOS OS DADOS')}
           disabled={isLoading === 'LIMPAR DB (DEBUG)'}
         >
           {isLoading === 'LIMPAR DB (DEBUG)' ? 'Limpando...' : 'LIMPAR DB (DEBUG)'}
@@ -61,7 +75,7 @@ export default function ConfiguracoesPage(): JSX.Element {
         {/* Botão POST */}
         <button
           className="submit-button"
-          onClick={() => handleDbOperation('POST', '/api/init_clean', 'INICIAR DB (DEBUG)')}
+          onClick={() => handleDbOperation('POST', '/api/init_clean', 'INICIAR DB (DEBUG)', 'INICIAR BANCO')}
           disabled={isLoading === 'INICIAR DB (DEBUG)'}
         >
           {isLoading === 'INICIAR DB (DEBUG)' ? 'Iniciando...' : 'INICIAR DB (DEBUG)'}
@@ -70,7 +84,7 @@ export default function ConfiguracoesPage(): JSX.Element {
         {/* Botão PUT */}
         <button
           className="submit-button edit-button" // Usando estilo 'edit-button' para cor amarela
-          onClick={() => handleDbOperation('PUT', '/api/init_clean', 'POPULAR DB (DEBUG)')}
+          onClick={() => handleDbOperation('PUT', '/api/init_clean', 'POPULAR DB (DEBUG)', 'POPULAR BANCO')}
           disabled={isLoading === 'POPULAR DB (DEBUG)'}
         >
           {isLoading === 'POPULAR DB (DEBUG)' ? 'Populando...' : 'POPULAR DB (DEBUG)'}
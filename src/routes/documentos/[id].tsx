@@ -3,11 +3,31 @@ import { useEffect, useState } from 'react';
 import type { JSX } from 'react';
 import type { Document } from '../../components/DocumentCard'; // Reutiliza a interface
 
+interface DocumentAnalysis {
+  id_analise: number;
+  modelo: string;
+  status: string; // pendente, concluida, erro
+  resumo: string | null;
+  extracao: Record<string, unknown> | null;
+  erro: string | null;
+  data_analise: string | null;
+}
+
+type DocumentWithAnalysis = Document & { analise?: DocumentAnalysis | null };
+
+interface Lawyer {
+  id_advogado: number;
+  nome: string;
+}
+
 export default function DocumentoPage(): JSX.Element {
   const router = useRouter();
-  const [documento, setDocumento] = useState<Document | null>(null);
+  const [documento, setDocumento] = useState<DocumentWithAnalysis | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isLoading, setIsLoading] = useState<boolean>(true);
+  const [lawyers, setLawyers] = useState<Lawyer[]>([]);
+  const [approverId, setApproverId] = useState<string>('');
+  const [isApproving, setIsApproving] = useState<boolean>(false);
 
   const id = router.pathname.split('/').pop();
 
@@ -38,6 +58,38 @@ export default function DocumentoPage(): JSX.Element {
       });
   }, [id]);
 
+  // Advogados para a aprovação de minutas geradas por IA
+  useEffect(() => {
+    if (documento?.status_minuta !== 'pendente') return;
+    fetch('/api/advogados')
+      .then(res => res.json())
+      .then((data) => {
+        if (Array.isArray(data)) setLawyers(data);
+      })
+      .catch(err => console.error('Erro ao buscar advogados:', err));
+  }, [documento?.status_minuta]);
+
+  const handleApprove = async () => {
+    if (!documento || !approverId) return;
+    setIsApproving(true);
+    try {
+      const response = await fetch(`/api/minutas?id=${documento.id_documento}`, {
+        method: 'PUT',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ id_advogado: Number(approverId) }),
+      });
+      const data = await response.json();
+      if (!response.ok) {
+        throw new Error(data.error || `Erro ${response.status}`);
+      }
+      setDocumento({ ...documento, status_minuta: 'aprovada' });
+    } catch (err: any) {
+      setError(`Erro ao aprovar minuta: ${err.message}`);
+    } finally {
+      setIsApproving(false);
+    }
+  };
+
   const handleDownload = async () => {
     if (!documento) {
       setError('Nenhum documento para baixar.');
@@ -80,6 +132,26 @@ export default function DocumentoPage(): JSX.Element {
       <h1 className="page-title">Detalhes do Documento</h1>
       <p className="page-description">Informações completas sobre o documento.</p>
 
+      {documento.gerado_por_ia && documento.status_minuta === 'pendente' && (
+        <div className="error-message" style={{ margin: '1rem 0' }}>
+          <p>Minuta gerada por IA (versão {documento.versao ?? 1}), pendente de revisão. Não protocole antes da aprovação de um advogado.</p>
+          <div className="form-group" style={{ marginTop: '0.75rem' }}>
+            <select className="form-input" value={approverId} onChange={(e) => setApproverId(e.target.value)}>
+              <option value="">Selecione o advogado que revisou</option>
+              {lawyers.map(l => (
+                <option key={l.id_advogado} value={l.id_advogado}>{l.nome}</option>
+              ))}
+            </select>
+          </div>
+          <button className="submit-button" onClick={handleApprove} disabled={!approverId || isApproving}>
+            {isApproving ? 'Aprovando...' : 'Aprovar minuta'}
+          </button>
+        </div>
+      )}
+      {documento.gerado_por_ia && documento.status_minuta === 'aprovada' && (
+        <p className="page-description">Minuta gerada por IA (versão {documento.versao ?? 1}) e aprovada por advogado.</p>
+      )}
+
       <div className="client-form" style={{ gap: '1rem' }}>
         <div className="form-group">
           <label className="form-label">ID Documento:</label>
@@ -106,6 +178,18 @@ export default function DocumentoPage(): JSX.Element {
         </div>
       </div>
 
+      {documento.analise && (
+        <div className="response-section" style={{ marginTop: '2rem', padding: '1.5rem', backgroundColor: '#f0f8ff', borderRadius: '0.75rem', boxShadow: 'var(--shadow-light)' }}>
+          <h2 style={{ fontSize: '1.5rem', color: 'var(--dark-text)', marginBottom: '1rem' }}>Análise da IA ({documento.analise.modelo}):</h2>
+          {documento.analise.status === 'pendente' && <p>Análise em andamento...</p>}
+          {documento.analise.status === 'erro' && <p className="error-message">{documento.analise.erro}</p>}
+          {documento.analise.resumo && <p style={{ whiteSpace: 'pre-wrap', color: '#333' }}>{documento.analise.resumo}</p>}
+          {documento.analise.extracao && (
+            <pre style={{ whiteSpace: 'pre-wrap', color: '#333', marginTop: '1rem' }}>{JSON.stringify(documento.analise.extracao, null, 2)}</pre>
+          )}
+        </div>
+      )}
+
       <div className="form-actions" style={{ justifyContent: 'flex-start', flexDirection: 'column' }}>
         <button onClick={handleDownload} className="submit-button" style={{ marginBottom: '1rem' }}>
           Download Arquivo
@@ -1,6 +1,7 @@
 // src/routes/documentos/index.rs
 use serde::{Deserialize, Serialize};
 use tuono_lib::{Props, Request, Response};
+use tuono_app::acesso::{escopo_casos, exigir_permissao_pagina, Permissao};
 use tuono_lib::axum::http;
 use tuono_app::connect_db;
 use chrono::NaiveDate;
@@ -22,6 +23,11 @@ struct Documentos {
 #[allow(unused_variables)]
 #[tuono_lib::handler]
 async fn get_documents(req: Request) -> Response {
+    let usuario = match exigir_permissao_pagina(&req, Permissao::VerDocumentos).await {
+        Ok(usuario) => usuario,
+        Err(redirecionamento) => return redirecionamento,
+    };
+
     let client_db = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -30,10 +36,19 @@ async fn get_documents(req: Request) -> Response {
         }
     };
 
+    // Só os casos visíveis para o usuário ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Database query error: {}", e)));
+        }
+    };
+
     let rows = match client_db
         .query(
-            "SELECT id_documento, id_caso, descricao, data_envio, nome_arquivo FROM Documento ORDER BY data_envio DESC", // 'tipo' removido da SELECT
-            &[],
+            "SELECT id_documento, id_caso, descricao, data_envio, nome_arquivo FROM Documento WHERE ($1::INTEGER[] IS NULL OR id_caso = ANY($1)) ORDER BY data_envio DESC", // 'tipo' removido da SELECT
+            &[&ids_visiveis],
         )
         .await
     {
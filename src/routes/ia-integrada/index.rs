@@ -1,8 +1,11 @@
 // src/routes/ia-integrada/index.rs
 use serde::{Deserialize, Serialize};
 use tuono_lib::{Props, Request, Response};
+use tuono_app::acesso::{escopo_casos, exigir_permissao_pagina, Permissao};
 use tuono_lib::axum::http;
 use tuono_app::connect_db;
+use tuono_app::ia::provider_from_env;
+use tuono_app::ia::saude::{iniciar_monitoramento, listar_modelos, marcar_indisponivel, situacao, SituacaoIa};
 
 // --- Structs para dados de modelos Ollama (para o frontend) ---
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,57 +14,52 @@ struct OllamaModelFrontend {
     nome: String,
 }
 
-// --- Structs para a Resposta da API do Ollama /api/tags (interna para o proxy) ---
-#[derive(Debug, Deserialize)]
-struct OllamaTagsResponse {
-    models: Vec<OllamaModelInternal>,
-}
-
-#[derive(Debug, Deserialize)]
-struct OllamaModelInternal {
-    name: String,
-}
-
-
 // --- Structs para documentos (para o frontend) ---
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct DocumentFrontend {
     id_documento: i32,
+    id_caso: i32,
     nome_arquivo: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Clone)]
 struct IaIntegratedPageProps {
     ollama_models: Vec<OllamaModelFrontend>,
     documents: Vec<DocumentFrontend>,
+    // Situação do serviço de IA; a página continua utilizável (documentos) quando ele está fora do ar
+    status_ia: SituacaoIa,
 }
 
 #[allow(unused_variables)]
 #[tuono_lib::handler]
 async fn get_ia_integrated_data(req: Request, fetch: reqwest::Client) -> Response {
-    let ollama_env = std::env::var("OLLAMA_API_URL").expect("Fail to read OLLAMA_API_URL env");
-    let ollama_api_url: &str = &format!("{}api/tags", ollama_env); 
-
-    let models_response = match fetch.get(ollama_api_url).send().await {
-        Ok(res) => res,
-        Err(e) => {
-            eprintln!("Failed to connect to Ollama API (tags) for pre-render: {}", e);
-            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Failed to fetch Ollama models for page: {}", e)));
-        }
+    let usuario = match exigir_permissao_pagina(&req, Permissao::UsarIa).await {
+        Ok(usuario) => usuario,
+        Err(redirecionamento) => return redirecionamento,
     };
 
-    let models_data_raw: OllamaTagsResponse = match models_response.json().await {
-        Ok(data) => data,
+    iniciar_monitoramento(fetch.clone());
+
+    // Falhas do provedor não derrubam a página: usa a última lista de modelos conhecida
+    let status_ia = match provider_from_env(fetch) {
+        Ok(llm) => match listar_modelos(llm.as_ref()).await {
+            Ok(status) => status,
+            Err(e) => {
+                eprintln!("Failed to fetch models ({}) for pre-render: {}", llm.name(), e);
+                situacao()
+            }
+        },
         Err(e) => {
-            eprintln!("Failed to parse Ollama API (tags) response for pre-render: {}", e);
-            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Failed to parse Ollama models response for page: {}", e)));
+            eprintln!("{}", e);
+            marcar_indisponivel(&e);
+            situacao()
         }
     };
 
-    let models_for_frontend: Vec<OllamaModelFrontend> = models_data_raw.models.into_iter().map(|model| {
+    let models_for_frontend: Vec<OllamaModelFrontend> = status_ia.modelos.iter().map(|name| {
         OllamaModelFrontend {
-            id: model.name.clone(),
-            nome: model.name,
+            id: name.clone(),
+            nome: name.clone(),
         }
     }).collect();
 
@@ -74,8 +72,20 @@ async fn get_ia_integrated_data(req: Request, fetch: reqwest::Client) -> Respons
         }
     };
 
+    // Só os documentos dos casos visíveis para o usuário ($1 nulo = todos)
+    let ids_visiveis = match escopo_casos(&client_db, &usuario).await {
+        Ok(escopo) => escopo.ids(),
+        Err(e) => {
+            eprintln!("Failed to load case scope: {}", e);
+            return Response::Custom((http::StatusCode::INTERNAL_SERVER_ERROR, http::HeaderMap::new(), format!("Failed to fetch documents: {}", e)));
+        }
+    };
+
     let document_rows = match client_db
-        .query("SELECT id_documento, nome_arquivo FROM Documento ORDER BY nome_arquivo ASC;", &[])
+        .query(
+            "SELECT id_documento, id_caso, nome_arquivo FROM Documento WHERE ($1::INTEGER[] IS NULL OR id_caso = ANY($1)) ORDER BY nome_arquivo ASC, id_documento ASC;",
+            &[&ids_visiveis],
+        )
         .await
     {
         Ok(rows) => rows,
@@ -88,6 +98,7 @@ async fn get_ia_integrated_data(req: Request, fetch: reqwest::Client) -> Respons
     let documents_list: Vec<DocumentFrontend> = document_rows.into_iter().map(|row| {
         DocumentFrontend {
             id_documento: row.get("id_documento"),
+            id_caso: row.get("id_caso"),
             nome_arquivo: row.get("nome_arquivo"),
         }
     }).collect();
@@ -95,5 +106,6 @@ async fn get_ia_integrated_data(req: Request, fetch: reqwest::Client) -> Respons
     Response::Props(Props::new(IaIntegratedPageProps {
         ollama_models: models_for_frontend,
         documents: documents_list,
+        status_ia,
     }))
 }
\ No newline at end of file
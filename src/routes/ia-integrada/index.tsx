@@ -1,4 +1,4 @@
-import { useState, useEffect, useCallback } from 'react';
+import { useState, useEffect, useCallback, useRef } from 'react';
 import type { JSX } from 'react';
 import { useRouter } from 'tuono';
 import type { TuonoRouteProps } from 'tuono';
@@ -11,12 +11,25 @@ interface OllamaModelProps {
 
 interface DocumentProps {
   id_documento: number;
+  id_caso: number;
   nome_arquivo: string;
 }
 
+interface StatusIa {
+  status: 'disponivel' | 'degradado' | 'indisponivel' | 'desconhecido';
+  mensagem: string | null;
+  modelos: string[];
+  modelos_em_cache: boolean;
+  modelo_padrao_instalado: boolean | null;
+  ultimo_sucesso: string | null;
+  ultima_falha: string | null;
+  falhas_consecutivas: number;
+}
+
 interface IaIntegratedPageData {
   ollama_models: OllamaModelProps[];
   documents: DocumentProps[];
+  status_ia: StatusIa;
 }
 
 export default function IaIntegratedPage({ data, isLoading: propIsLoading }: TuonoRouteProps<IaIntegratedPageData>): JSX.Element {
@@ -24,12 +37,17 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
   const [ollamaModels, setOllamaModels] = useState<OllamaModelProps[]>(data?.ollama_models || []);
   const [documents, setDocuments] = useState<DocumentProps[]>(data?.documents || []);
   const [selectedModel, setSelectedModel] = useState<string>('');
-  const [selectedDocumentName, setSelectedDocumentName] = useState<string>('');
+  const [selectedDocumentId, setSelectedDocumentId] = useState<string>('');
   const [question, setQuestion] = useState<string>('');
   const [llmResponse, setLlmResponse] = useState<string>('');
+  const [suspiciousPassages, setSuspiciousPassages] = useState<string[]>([]);
+  const [queuePosition, setQueuePosition] = useState<number | null>(null);
+  const requestIdRef = useRef<string | null>(null);
+  const abortControllerRef = useRef<AbortController | null>(null);
   const [isLoadingResponse, setIsLoadingResponse] = useState(false);
   const [error, setError] = useState<string | null>(null);
   const [isLoadingPage, setIsLoadingPage] = useState(propIsLoading);
+  const [statusIa, setStatusIa] = useState<StatusIa | null>(data?.status_ia || null);
 
   useEffect(() => {
     const initializeDropdowns = (models: OllamaModelProps[], docs: DocumentProps[]) => {
@@ -40,22 +58,28 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
       }
 
       if (docs.length > 0) {
-        setSelectedDocumentName(docs[0].nome_arquivo);
+        setSelectedDocumentId(String(docs[0].id_documento));
       } else {
-        setSelectedDocumentName(''); // Garante que esteja vazio se não houver documentos
+        setSelectedDocumentId(''); // Garante que esteja vazio se não houver documentos
       }
     };
 
     const fetchDataClient = async () => {
       setIsLoadingPage(true);
       try {
+        // Sem modelos a página continua útil: os documentos vêm do nosso banco
+        let modelsData: OllamaModelProps[] = [];
         const modelsRes = await fetch('/api/ollama');
-        if (!modelsRes.ok) {
-            const errorBody = await modelsRes.text();
-            throw new Error(`Falha ao carregar modelos Ollama: ${modelsRes.status} - ${errorBody}`);
+        if (modelsRes.ok) {
+          modelsData = await modelsRes.json();
+        } else {
+          console.error(`Falha ao carregar modelos Ollama: ${modelsRes.status} - ${await modelsRes.text()}`);
         }
-        const modelsData: OllamaModelProps[] = await modelsRes.json();
         setOllamaModels(modelsData);
+        const saudeRes = await fetch('/api/saude_ia');
+        if (saudeRes.ok) {
+          setStatusIa(await saudeRes.json());
+        }
 
         const docsRes = await fetch('/api/documentos');
         if (!docsRes.ok) {
@@ -82,6 +106,7 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
     if (data?.ollama_models && data?.documents) {
       setOllamaModels(data.ollama_models);
       setDocuments(data.documents);
+      setStatusIa(data.status_ia ?? null);
       initializeDropdowns(data.ollama_models, data.documents);
       setIsLoadingPage(false);
     } else {
@@ -89,25 +114,74 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
     }
   }, [data]); // Depende de 'data' para saber se foi pré-renderizado
 
+  // Enquanto o serviço de IA estiver fora do ar, consulta a saúde até ele voltar e recarrega os modelos
+  useEffect(() => {
+    if (statusIa?.status !== 'indisponivel') return;
+    const interval = setInterval(async () => {
+      try {
+        const res = await fetch('/api/saude_ia');
+        if (!res.ok) return;
+        const novoStatus: StatusIa = await res.json();
+        setStatusIa(novoStatus);
+        if (novoStatus.status === 'disponivel') {
+          const models = novoStatus.modelos.map(nome => ({ id: nome, nome }));
+          setOllamaModels(models);
+          setSelectedModel(current => current || (models[0]?.id ?? ''));
+        }
+      } catch {
+        // Mantém o aviso atual; tenta de novo no próximo intervalo
+      }
+    }, 10000);
+    return () => clearInterval(interval);
+  }, [statusIa?.status]);
+
   const handleSubmit = async (e: React.FormEvent) => {
     e.preventDefault();
     setLlmResponse('');
+    setSuspiciousPassages([]);
     setError(null);
     setIsLoadingResponse(true);
 
     // Validação robusta ANTES de enviar
-    if (!selectedModel || !selectedDocumentName || !question.trim()) { // Adicionado .trim() para pergunta vazia
+    if (!selectedModel || !selectedDocumentId || !question.trim()) { // Adicionado .trim() para pergunta vazia
       setError('Por favor, selecione um modelo, um documento e insira uma pergunta.');
       setIsLoadingResponse(false);
       return;
     }
 
+    const selectedDocument = documents.find(doc => String(doc.id_documento) === selectedDocumentId);
+    if (!selectedDocument) {
+      setError('Documento selecionado não encontrado.');
+      setIsLoadingResponse(false);
+      return;
+    }
+
+    const requestId = crypto.randomUUID();
+    const abortController = new AbortController();
+    requestIdRef.current = requestId;
+    abortControllerRef.current = abortController;
+
     const payload = {
-      file_name: selectedDocumentName,
+      id_documento: selectedDocument.id_documento,
+      id_caso: selectedDocument.id_caso,
       question: question,
       model: selectedModel,
+      id_requisicao: requestId,
     };
 
+    // Acompanha a posição na fila do modelo enquanto a resposta não chega
+    const queuePolling = setInterval(async () => {
+      try {
+        const queueRes = await fetch(`/api/fila_ia?id_requisicao=${requestId}`);
+        if (queueRes.ok) {
+          const queueData = await queueRes.json();
+          setQueuePosition(queueData.posicao ?? null);
+        }
+      } catch {
+        // A consulta da fila é apenas informativa
+      }
+    }, 1000);
+
     try {
       const response = await fetch('/api/ollama', {
         method: 'POST',
@@ -115,6 +189,7 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
           'Content-Type': 'application/json',
         },
         body: JSON.stringify(payload),
+        signal: abortController.signal,
       });
 
       if (!response.ok) {
@@ -127,15 +202,33 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
         setError(responseData.error);
       } else {
         setLlmResponse(responseData.llm_response);
+        setSuspiciousPassages((responseData.seguranca?.trechos_suspeitos || []).map((t: { trecho: string }) => t.trecho));
       }
     } catch (err: any) {
-      console.error('Erro na requisição ao Ollama:', err);
-      setError(`Erro ao obter resposta do LLM: ${err.message || 'Erro desconhecido.'}`);
+      if (err.name === 'AbortError') {
+        setError('Requisição cancelada.');
+      } else {
+        console.error('Erro na requisição ao Ollama:', err);
+        setError(`Erro ao obter resposta do LLM: ${err.message || 'Erro desconhecido.'}`);
+      }
     } finally {
+      clearInterval(queuePolling);
+      setQueuePosition(null);
+      requestIdRef.current = null;
+      abortControllerRef.current = null;
       setIsLoadingResponse(false);
     }
   };
 
+  const handleCancel = async () => {
+    const requestId = requestIdRef.current;
+    abortControllerRef.current?.abort();
+    if (requestId) {
+      // Libera a vaga na fila mesmo que o servidor ainda não tenha percebido a desconexão
+      await fetch(`/api/fila_ia?id_requisicao=${requestId}`, { method: 'DELETE' }).catch(() => undefined);
+    }
+  };
+
   if (isLoadingPage) {
     return <div className="loading-container"><h1>Carregando página de IA Integrada...</h1></div>;
   }
@@ -148,7 +241,21 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
       <h1 className="page-title">IA Integrada (Ollama)</h1>
       <p className="page-description">Faça perguntas sobre seus documentos usando modelos de linguagem locais.</p>
 
-      {showNoOptionsError && (
+      {statusIa?.status === 'indisponivel' && (
+        <p className="error-message" style={{ margin: '1rem 0' }}>
+          Serviço de IA indisponível. Os documentos continuam acessíveis e a conexão é retomada automaticamente.
+          {statusIa.modelos_em_cache && ' A lista de modelos abaixo é a última conhecida.'}
+          {statusIa.ultimo_sucesso && ` Última conexão bem-sucedida: ${new Date(statusIa.ultimo_sucesso).toLocaleString('pt-BR')}.`}
+        </p>
+      )}
+
+      {statusIa?.status === 'degradado' && statusIa.mensagem && (
+        <p className="error-message" style={{ margin: '1rem 0' }}>
+          {statusIa.mensagem}
+        </p>
+      )}
+
+      {showNoOptionsError && statusIa?.status !== 'indisponivel' && (
         <p className="error-message" style={{ margin: '1rem 0' }}>
           Não foi possível carregar modelos Ollama ou documentos. Verifique se o Ollama está rodando e se há documentos no banco de dados.
         </p>
@@ -177,14 +284,14 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
           <select
             id="document-select"
             className="form-input"
-            value={selectedDocumentName}
-            onChange={(e) => setSelectedDocumentName(e.target.value)}
+            value={selectedDocumentId}
+            onChange={(e) => setSelectedDocumentId(e.target.value)}
             required
             disabled={documents.length === 0} // Desabilita se não houver documentos
           >
             <option value="">{documents.length === 0 ? 'Carregando documentos...' : 'Selecione um documento'}</option>
             {documents.map(doc => (
-              <option key={doc.id_documento} value={doc.nome_arquivo}>{doc.nome_arquivo}</option>
+              <option key={doc.id_documento} value={doc.id_documento}>{doc.nome_arquivo} (Caso #{doc.id_caso})</option>
             ))}
           </select>
         </div>
@@ -205,14 +312,26 @@ export default function IaIntegratedPage({ data, isLoading: propIsLoading }: Tuo
 
         <div className="form-actions" style={{ justifyContent: 'center' }}>
           <button type="submit" className="submit-button" disabled={isLoadingResponse || showNoOptionsError}>
-            {isLoadingResponse ? 'Gerando resposta...' : 'Obter Resposta do LLM'}
+            {isLoadingResponse
+              ? (queuePosition ? `Aguardando na fila (posição ${queuePosition})...` : 'Gerando resposta...')
+              : 'Obter Resposta do LLM'}
           </button>
+          {isLoadingResponse && (
+            <button type="button" className="cancel-button" onClick={handleCancel}>
+              Cancelar
+            </button>
+          )}
         </div>
       </form>
 
       {llmResponse && (
         <div className="response-section" style={{ marginTop: '2rem', padding: '1.5rem', backgroundColor: '#f0f8ff', borderRadius: '0.75rem', boxShadow: 'var(--shadow-light)' }}>
           <h2 style={{ fontSize: '1.5rem', color: 'var(--dark-text)', marginBottom: '1rem' }}>Resposta do LLM:</h2>
+          {suspiciousPassages.length > 0 && (
+            <p className="error-message" style={{ marginBottom: '1rem' }}>
+              Atenção: o documento contém trechos que parecem instruções para a IA ({suspiciousPassages.map(t => `"${t}"`).join(', ')}). Confira a resposta com cuidado.
+            </p>
+          )}
           <p style={{ whiteSpace: 'pre-wrap', color: '#333' }}>{llmResponse}</p>
         </div>
       )}
@@ -2,13 +2,10 @@
 use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use tuono_lib::{Props, Request, Response};
-use tuono_lib::tokio;
-use tokio_postgres::NoTls;
-
-const HEALTH_CHECK: &str = "http://localhost:3000/api/health_check";
-
-const DATABASE_URL: &str = "host=localhost port=5432 user=usuario password=1234 dbname=banco_de_dados";
-
+use tuono_app::auth::exigir_sessao_pagina;
+use tuono_app::connect_db;
+use tuono_app::config::Config;
+use std::sync::Arc;
 
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -19,9 +16,14 @@ struct Status {
 
 #[allow(unused_imports)]
 #[tuono_lib::handler]
-async fn get_all_pokemons(_req: Request, fetch: Client) -> Response {
+async fn get_all_pokemons(_req: Request, fetch: Client, config: Arc<Config>) -> Response {
+    if let Err(redirecionamento) = exigir_sessao_pagina(&_req).await {
+        return redirecionamento;
+    }
+
     let api_check = {
-        let response = fetch.get(HEALTH_CHECK).send().await;
+        let health_check = format!("{}/api/health_check", config.servidor.url_base);
+        let response = fetch.get(&health_check).send().await;
         match response {
             Ok(res) => res.status().to_string(),
             Err(_) => "Error".to_string(),
@@ -30,13 +32,8 @@ async fn get_all_pokemons(_req: Request, fetch: Client) -> Response {
     };
 
     let db_check = {
-        match tokio_postgres::connect(DATABASE_URL, NoTls).await {
-            Ok((client, connection)) => {
-                tokio::spawn(async move {
-                    if let Err(e) = connection.await {
-                        eprintln!("Erro na conexão do banco de dados (background task): {}", e);
-                    }
-                });
+        match connect_db().await {
+            Ok(client) => {
                 match client.query_one("SELECT 1", &[]).await {
                     Ok(_) => "OK".to_string(),
                     Err(e) => format!("Database query error: {}", e),
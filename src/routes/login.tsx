@@ -0,0 +1,87 @@
+import { useState } from 'react';
+import type { JSX } from 'react';
+
+// Só aceita caminhos internos, para o parâmetro não virar redirecionamento para fora
+function destinoSeguro(): string {
+  const destino = new URLSearchParams(window.location.search).get('destino');
+  if (destino && destino.startsWith('/') && !destino.startsWith('//') && destino !== '/login') {
+    return destino;
+  }
+  return '/';
+}
+
+export default function LoginPage(): JSX.Element {
+  const [email, setEmail] = useState('');
+  const [senha, setSenha] = useState('');
+  const [error, setError] = useState('');
+  const [isLoading, setIsLoading] = useState(false);
+
+  const handleSubmit = async (e: React.FormEvent) => {
+    e.preventDefault();
+    setError('');
+    setIsLoading(true);
+
+    try {
+      const response = await fetch('/api/login', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ email, senha }),
+      });
+
+      if (response.ok) {
+        window.location.href = destinoSeguro();
+        return;
+      }
+      const data = await response.json().catch(() => ({}));
+      setError(data.error || `Falha no login: ${response.status}`);
+    } catch (err: any) {
+      console.error('Erro de rede ou servidor no login:', err);
+      setError(`Erro de rede ou servidor: ${err.message || 'Erro desconhecido.'}`);
+    } finally {
+      setIsLoading(false);
+    }
+  };
+
+  return (
+    <div className="new-client-page-container">
+      <h1 className="page-title">Entrar</h1>
+      <p className="page-description">Informe seu e-mail e senha para acessar o sistema LawIA.</p>
+
+      <form onSubmit={handleSubmit} className="client-form">
+        <div className="form-group">
+          <label htmlFor="email" className="form-label">Email:</label>
+          <input
+            type="email"
+            id="email"
+            value={email}
+            onChange={(e) => setEmail(e.target.value)}
+            className="form-input"
+            autoComplete="username"
+            required
+          />
+        </div>
+
+        <div className="form-group">
+          <label htmlFor="senha" className="form-label">Senha:</label>
+          <input
+            type="password"
+            id="senha"
+            value={senha}
+            onChange={(e) => setSenha(e.target.value)}
+            className="form-input"
+            autoComplete="current-password"
+            required
+          />
+        </div>
+
+        {error && <p className="error-message">{error}</p>}
+
+        <div className="form-actions">
+          <button type="submit" className="submit-button" disabled={isLoading}>
+            {isLoading ? 'Entrando...' : 'Entrar'}
+          </button>
+        </div>
+      </form>
+    </div>
+  );
+}
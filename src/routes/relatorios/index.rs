@@ -1,9 +1,11 @@
 // src/routes/relatorios/index.rs
 use serde::{Deserialize, Serialize};
 use tuono_lib::{Props, Request, Response};
+use tuono_app::acesso::{exigir_permissao_pagina, Permissao};
 use tuono_lib::axum::http::{StatusCode, HeaderMap}; // Importado HeaderMap e StatusCode
 use tuono_lib::axum::response::{IntoResponse, Json}; // Importado IntoResponse e Json
 use tuono_app::connect_db; // Para conectar diretamente ao DB
+use tuono_app::ia::auditoria::{uso_agregado, UsoIa};
 use serde_json::json; // Importado a macro json!
 
 
@@ -38,11 +40,17 @@ struct RelatoriosPageProps {
     report_data_docs_clientes_casos: Vec<RelatorioDocumentosClienteCaso>,
     report_data_casos_advogado_status: Vec<RelatorioCasosAdvogadoStatus>,
     report_data_audiencias_cliente_advogado: Vec<RelatorioAudienciasClienteAdvogado>,
+    report_data_uso_ia_modelo: Vec<UsoIa>,
+    report_data_uso_ia_usuario: Vec<UsoIa>,
 }
 
 #[allow(unused_variables)] 
 #[tuono_lib::handler]
 async fn get_relatorios_data(req: Request) -> Response {
+    if let Err(redirecionamento) = exigir_permissao_pagina(&req, Permissao::VerRelatorios).await {
+        return redirecionamento;
+    }
+
     let client_db = match connect_db().await {
         Ok(client) => client,
         Err(e) => {
@@ -140,9 +148,29 @@ async fn get_relatorios_data(req: Request) -> Response {
     }).collect();
 
 
+    // --- 4 e 5. Relatórios: Uso de IA por Modelo e por Usuário ---
+    // Descrição: Chamadas, tokens e latência média registrados em Llm_Chamada.
+    let report_data_uso_ia_modelo = match uso_agregado(&client_db, "modelo", None, None).await {
+        Ok(uso) => uso,
+        Err(e) => {
+            eprintln!("Failed to fetch report_data_uso_ia_modelo: {}", e);
+            return Response::Custom((StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new(), json!({"error": e}).to_string()));
+        }
+    };
+    let report_data_uso_ia_usuario = match uso_agregado(&client_db, "usuario", None, None).await {
+        Ok(uso) => uso,
+        Err(e) => {
+            eprintln!("Failed to fetch report_data_uso_ia_usuario: {}", e);
+            return Response::Custom((StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new(), json!({"error": e}).to_string()));
+        }
+    };
+
+
     Response::Props(Props::new(RelatoriosPageProps {
         report_data_docs_clientes_casos,
         report_data_casos_advogado_status,
         report_data_audiencias_cliente_advogado,
+        report_data_uso_ia_modelo,
+        report_data_uso_ia_usuario,
     }))
 }
\ No newline at end of file
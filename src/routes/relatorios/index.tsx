@@ -27,12 +27,24 @@ interface ReportDataItemAudienciasClienteAdvogado {
   total_audiencias: number;
 }
 
+// Interfaces para os dados dos relatórios de "Uso de IA" (por modelo ou por usuário)
+interface ReportDataItemUsoIa {
+  chave: string;
+  chamadas: number;
+  erros: number;
+  prompt_tokens: number;
+  completion_tokens: number;
+  latencia_media_ms: number;
+}
+
 
 // Propriedades da Página de Relatórios (Reflete as props do backend)
 interface RelatoriosPageProps {
   report_data_docs_clientes_casos: ReportDataItemDocsClientesCasos[];
   report_data_casos_advogado_status: ReportDataItemCasosAdvogadoStatus[];
   report_data_audiencias_cliente_advogado: ReportDataItemAudienciasClienteAdvogado[];
+  report_data_uso_ia_modelo: ReportDataItemUsoIa[];
+  report_data_uso_ia_usuario: ReportDataItemUsoIa[];
 }
 
 export default function RelatoriosPage({ data, isLoading: propIsLoading }: TuonoRouteProps<RelatoriosPageProps>): JSX.Element {
@@ -40,6 +52,8 @@ export default function RelatoriosPage({ data, isLoading: propIsLoading }: Tuono
   const [reportDataDocsClientesCasos, setReportDataDocsClientesCasos] = useState<ReportDataItemDocsClientesCasos[]>(data?.report_data_docs_clientes_casos || []);
   const [reportDataCasosAdvogadoStatus, setReportDataCasosAdvogadoStatus] = useState<ReportDataItemCasosAdvogadoStatus[]>(data?.report_data_casos_advogado_status || []);
   const [reportDataAudienciasClienteAdvogado, setReportDataAudienciasClienteAdvogado] = useState<ReportDataItemAudienciasClienteAdvogado[]>(data?.report_data_audiencias_cliente_advogado || []);
+  const [reportDataUsoIaModelo, setReportDataUsoIaModelo] = useState<ReportDataItemUsoIa[]>(data?.report_data_uso_ia_modelo || []);
+  const [reportDataUsoIaUsuario, setReportDataUsoIaUsuario] = useState<ReportDataItemUsoIa[]>(data?.report_data_uso_ia_usuario || []);
   
   const [error, setError] = useState<string | null>(null);
   const [isLoadingPage, setIsLoadingPage] = useState(propIsLoading);
@@ -49,6 +63,8 @@ export default function RelatoriosPage({ data, isLoading: propIsLoading }: Tuono
       setReportDataDocsClientesCasos(data.report_data_docs_clientes_casos);
       setReportDataCasosAdvogadoStatus(data.report_data_casos_advogado_status);
       setReportDataAudienciasClienteAdvogado(data.report_data_audiencias_cliente_advogado);
+      setReportDataUsoIaModelo(data.report_data_uso_ia_modelo || []);
+      setReportDataUsoIaUsuario(data.report_data_uso_ia_usuario || []);
       setIsLoadingPage(false);
     } else {
       console.warn("Dados do relatório não pré-renderizados. O relatório pode estar vazio.");
@@ -82,6 +98,15 @@ export default function RelatoriosPage({ data, isLoading: propIsLoading }: Tuono
     "Total de Audiências": item.total_audiencias,
   }));
 
+  // --- Preparação dos dados para o Recharts (Reports 4 e 5: Uso de IA por Modelo e por Usuário) ---
+  const toChartDataUsoIa = (items: ReportDataItemUsoIa[]) => items.map(item => ({
+    name: `${item.chave} (${item.chamadas} chamadas, ${item.erros} erros, ${Math.round(item.latencia_media_ms)} ms)`,
+    "Tokens de Entrada": item.prompt_tokens,
+    "Tokens de Saída": item.completion_tokens,
+  }));
+  const chartDataUsoIaModelo = toChartDataUsoIa(reportDataUsoIaModelo);
+  const chartDataUsoIaUsuario = toChartDataUsoIa(reportDataUsoIaUsuario);
+
 
   return (
     <div className="new-client-page-container">
@@ -160,6 +185,50 @@ export default function RelatoriosPage({ data, isLoading: propIsLoading }: Tuono
         </div>
       )}
 
+      {/* Relatório 4: Uso de IA por Modelo */}
+      <h2 style={{ fontSize: '1.8rem', fontWeight: 600, color: 'var(--dark-text)', marginTop: '3rem', marginBottom: '1.5rem', textAlign: 'center' }}>
+        Uso de IA por Modelo
+      </h2>
+      {reportDataUsoIaModelo.length === 0 ? (
+        <p className="no-results-message">Nenhuma chamada de IA registrada.</p>
+      ) : (
+        <div style={{ width: '100%', height: 400 }}>
+          <ResponsiveContainer>
+            <BarChart data={chartDataUsoIaModelo} margin={{ top: 20, right: 30, left: 20, bottom: 5 }}>
+              <CartesianGrid strokeDasharray="3 3" />
+              <XAxis dataKey="name" interval={0} angle={-30} textAnchor="end" height={100} />
+              <YAxis />
+              <Tooltip />
+              <Legend />
+              <Bar dataKey="Tokens de Entrada" stackId="tokens" fill="#6f42c1" />
+              <Bar dataKey="Tokens de Saída" stackId="tokens" fill="#17a2b8" />
+            </BarChart>
+          </ResponsiveContainer>
+        </div>
+      )}
+
+      {/* Relatório 5: Uso de IA por Usuário */}
+      <h2 style={{ fontSize: '1.8rem', fontWeight: 600, color: 'var(--dark-text)', marginTop: '3rem', marginBottom: '1.5rem', textAlign: 'center' }}>
+        Uso de IA por Usuário
+      </h2>
+      {reportDataUsoIaUsuario.length === 0 ? (
+        <p className="no-results-message">Nenhuma chamada de IA registrada.</p>
+      ) : (
+        <div style={{ width: '100%', height: 400 }}>
+          <ResponsiveContainer>
+            <BarChart data={chartDataUsoIaUsuario} margin={{ top: 20, right: 30, left: 20, bottom: 5 }}>
+              <CartesianGrid strokeDasharray="3 3" />
+              <XAxis dataKey="name" interval={0} angle={-30} textAnchor="end" height={100} />
+              <YAxis />
+              <Tooltip />
+              <Legend />
+              <Bar dataKey="Tokens de Entrada" stackId="tokens" fill="#6f42c1" />
+              <Bar dataKey="Tokens de Saída" stackId="tokens" fill="#17a2b8" />
+            </BarChart>
+          </ResponsiveContainer>
+        </div>
+      )}
+
     </div>
   );
 }
\ No newline at end of file